@@ -0,0 +1,768 @@
+use crate::{
+    filter::{Filter, FilterMode},
+    models::{column, Overview, QueueInfo},
+    widgets::{chart::clock, notif::Level},
+    ColumnDef, Identifiable, Rowable, SortValue, Sortable,
+};
+
+use std::{
+    collections::HashMap,
+    process::{Command, Stdio},
+    thread,
+};
+
+use serde::Deserialize;
+use tui::style::Color;
+
+#[derive(Deserialize, Clone, Copy, PartialEq, PartialOrd, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    #[default]
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    pub fn name(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Severity::Info => Color::Cyan,
+            Severity::Warning => Color::Yellow,
+            Severity::Critical => Color::Red,
+        }
+    }
+}
+
+/// What a rule watches, each queue matching
+/// a pattern or the cluster wide overview.
+#[derive(Clone, Debug)]
+pub enum Target {
+    Queues {
+        pattern: Filter,
+        vhost: Option<String>,
+    },
+    Overview,
+}
+
+const QUEUE_METRICS: [&str; 8] = [
+    "ready",
+    "unacked",
+    "total",
+    "consumers",
+    "memory",
+    "publish_rate",
+    "deliver_rate",
+    "ack_rate",
+];
+
+const OVERVIEW_METRICS: [&str; 11] = [
+    "ready",
+    "unacked",
+    "total",
+    "consumers",
+    "connections",
+    "channels",
+    "queues",
+    "exchanges",
+    "publish_rate",
+    "deliver_rate",
+    "ack_rate",
+];
+
+fn queue_metric(q: &QueueInfo, metric: &str) -> f64 {
+    let stats = &q.message_stats;
+    match metric {
+        "ready" => q.ready as f64,
+        "unacked" => q.unacked as f64,
+        "total" => q.total as f64,
+        "consumers" => q.consumers as f64,
+        "memory" => q.memory as f64,
+        "publish_rate" => stats.publish_details.rate,
+        "deliver_rate" => stats.deliver_get_details.rate,
+        "ack_rate" => stats.ack_details.rate,
+        _ => f64::NAN,
+    }
+}
+
+fn overview_metric(o: &Overview, metric: &str) -> f64 {
+    let totals = &o.queue_totals;
+    let objects = &o.object_totals;
+    let stats = &o.message_stats;
+    match metric {
+        "ready" => totals.messages_ready,
+        "unacked" => totals.messages_unacked,
+        "total" => totals.messages,
+        "consumers" => objects.consumers as f64,
+        "connections" => objects.connections as f64,
+        "channels" => objects.channels as f64,
+        "queues" => objects.queues as f64,
+        "exchanges" => objects.exchanges as f64,
+        "publish_rate" => stats.publish_details.rate,
+        "deliver_rate" => stats.deliver_get_details.rate,
+        "ack_rate" => stats.ack_details.rate,
+        _ => f64::NAN,
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Op {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Eq,
+    Ne,
+}
+
+impl Op {
+    fn parse(s: &str) -> Option<Self> {
+        Some(match s {
+            ">" => Op::Gt,
+            ">=" => Op::Ge,
+            "<" => Op::Lt,
+            "<=" => Op::Le,
+            "==" => Op::Eq,
+            "!=" => Op::Ne,
+            _ => return None,
+        })
+    }
+
+    fn holds(self, a: f64, b: f64) -> bool {
+        match self {
+            Op::Gt => a > b,
+            Op::Ge => a >= b,
+            Op::Lt => a < b,
+            Op::Le => a <= b,
+            Op::Eq => a == b,
+            Op::Ne => a != b,
+        }
+    }
+}
+
+/// A `metric op threshold` comparison,
+/// such as `ready > 10000`.
+#[derive(Clone, Debug)]
+pub struct Condition {
+    text: String,
+    metric: &'static str,
+    op: Op,
+    threshold: f64,
+}
+
+impl Condition {
+    fn parse(text: &str, target: &Target) -> Result<Self, String> {
+        let known: &[&'static str] = match target {
+            Target::Queues { .. } => &QUEUE_METRICS,
+            Target::Overview => &OVERVIEW_METRICS,
+        };
+        let parts: Vec<&str> = text.split_whitespace().collect();
+        let [metric, op, threshold] = parts[..] else {
+            return Err(format!(
+                "invalid condition {:?}, expected \"metric op number\"",
+                text
+            ));
+        };
+        let metric = known.iter().find(|m| **m == metric).ok_or_else(|| {
+            format!(
+                "unknown metric {:?}, expected one of {}",
+                metric,
+                known.join(", ")
+            )
+        })?;
+        let op = Op::parse(op).ok_or_else(|| {
+            format!(
+                "unknown comparison {:?}, expected one of >, >=, <, <=, ==, !=",
+                op
+            )
+        })?;
+        let threshold = threshold
+            .parse()
+            .map_err(|_| format!("invalid threshold {:?}", threshold))?;
+        Ok(Self {
+            text: parts.join(" "),
+            metric,
+            op,
+            threshold,
+        })
+    }
+}
+
+/// Parses durations such as `90s`, `2m` or `1h`,
+/// a bare number being seconds.
+pub fn parse_duration(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let (n, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => s.split_at(i),
+        None => (s, "s"),
+    };
+    let scale = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3_600,
+        _ => return Err(format!("invalid duration {:?}, expected 30s, 2m or 1h", s)),
+    };
+    n.parse::<u64>()
+        .map(|n| n * scale)
+        .map_err(|_| format!("invalid duration {:?}, expected 30s, 2m or 1h", s))
+}
+
+#[derive(Clone, Debug)]
+pub struct Rule {
+    pub name: String,
+    pub target: Target,
+    pub condition: Condition,
+    /// Seconds the condition has to hold before firing.
+    pub for_secs: u64,
+    pub severity: Severity,
+    /// Run with `sh -c` when the alert fires or resolves.
+    pub command: Option<String>,
+}
+
+impl Rule {
+    /// Builds a rule on the queues matching the glob
+    /// `queue`, or on the overview when there is none.
+    pub fn new(
+        name: String,
+        queue: Option<&str>,
+        vhost: Option<String>,
+        when: &str,
+        for_secs: u64,
+    ) -> Result<Self, String> {
+        let target = match queue {
+            Some(q) => Target::Queues {
+                pattern: Filter::new(q, FilterMode::Glob).map_err(|e| e.to_string())?,
+                vhost,
+            },
+            None => Target::Overview,
+        };
+        let condition = Condition::parse(when, &target)?;
+        Ok(Self {
+            name,
+            target,
+            condition,
+            for_secs,
+            severity: Severity::default(),
+            command: None,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct AlertConfig {
+    pub rules: Vec<Rule>,
+    /// Whether alerts also show up as desktop notifications.
+    pub desktop: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum AlertState {
+    Firing,
+    Resolved,
+}
+
+impl AlertState {
+    pub fn name(self) -> &'static str {
+        match self {
+            AlertState::Firing => "firing",
+            AlertState::Resolved => "resolved",
+        }
+    }
+}
+
+/// A rule starting or stopping to fire for a subject,
+/// a queue or the cluster.
+#[derive(Clone, Debug)]
+pub struct AlertEvent {
+    pub rule: String,
+    pub severity: Severity,
+    pub subject: String,
+    pub condition: String,
+    pub value: f64,
+    pub state: AlertState,
+    /// Seconds since the epoch.
+    pub at: f64,
+}
+
+impl AlertEvent {
+    /// Level of the notification raised for the event.
+    pub fn level(&self) -> Level {
+        match (self.state, self.severity) {
+            (AlertState::Resolved, _) => Level::Success,
+            (_, Severity::Info) => Level::Info,
+            (_, Severity::Warning) => Level::Warning,
+            (_, Severity::Critical) => Level::Error,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        format!(
+            "[{}] {} {}: {} on {} (now {})",
+            self.severity.name(),
+            self.rule,
+            self.state.name(),
+            self.condition,
+            self.subject,
+            self.value
+        )
+    }
+}
+
+const ALERT_COLUMNS: [ColumnDef; 7] = [
+    column("state", "State", 10, true),
+    column("severity", "Severity", 10, true),
+    column("rule", "Rule", 20, true),
+    column("subject", "Subject", 20, true),
+    column("condition", "Condition", 20, true),
+    column("value", "Value", 10, true),
+    column("at", "Since", 10, true),
+];
+
+impl Rowable for AlertEvent {
+    fn columns() -> &'static [ColumnDef] {
+        &ALERT_COLUMNS
+    }
+
+    fn cell(&self, field: usize) -> String {
+        match ALERT_COLUMNS[field].key {
+            "state" => self.state.name().to_string(),
+            "severity" => self.severity.name().to_string(),
+            "rule" => self.rule.clone(),
+            "subject" => self.subject.clone(),
+            "condition" => self.condition.clone(),
+            "value" => self.value.to_string(),
+            "at" => clock(self.at, true),
+            _ => String::new(),
+        }
+    }
+}
+
+impl Sortable for AlertEvent {
+    fn sort_value(&self, field: usize) -> SortValue {
+        match ALERT_COLUMNS[field].key {
+            "severity" => SortValue::Number(self.severity as u8 as f64),
+            "value" => SortValue::Number(self.value),
+            "at" => SortValue::Number(self.at),
+            _ => SortValue::Text(self.cell(field).to_lowercase()),
+        }
+    }
+}
+
+impl Identifiable for AlertEvent {
+    type Id = (String, String);
+
+    fn id(&self) -> Self::Id {
+        (self.rule.clone(), self.subject.clone())
+    }
+}
+
+/// Where a rule stands for one subject.
+struct Pending {
+    // When the condition started to hold.
+    since: f64,
+    firing: bool,
+    seen: bool,
+    value: f64,
+}
+
+/// Evaluates the rules against every refresh, keeping
+/// track of how long conditions have held.
+pub struct AlertEngine {
+    config: AlertConfig,
+    // Keyed by (rule index, subject).
+    pending: HashMap<(usize, String), Pending>,
+}
+
+impl AlertEngine {
+    pub fn new(config: AlertConfig) -> Self {
+        Self {
+            config,
+            pending: HashMap::new(),
+        }
+    }
+
+    pub fn evaluate_queues(&mut self, queues: &[QueueInfo], now: f64) -> Vec<AlertEvent> {
+        let mut values = Vec::new();
+        for (i, rule) in self.config.rules.iter().enumerate() {
+            let Target::Queues { pattern, vhost } = &rule.target else {
+                continue;
+            };
+            for q in queues {
+                let in_vhost = vhost.as_ref().is_none_or(|v| *v == q.vhost);
+                if in_vhost && pattern.find(&q.name).is_some() {
+                    let subject = format!("{} on {}", q.name, q.vhost);
+                    values.push((i, subject, queue_metric(q, rule.condition.metric)));
+                }
+            }
+        }
+        self.evaluate(values, |t| matches!(t, Target::Queues { .. }), now)
+    }
+
+    pub fn evaluate_overview(&mut self, overview: &Overview, now: f64) -> Vec<AlertEvent> {
+        let values = self
+            .config
+            .rules
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| matches!(r.target, Target::Overview))
+            .map(|(i, r)| {
+                let value = overview_metric(overview, r.condition.metric);
+                (i, "cluster".to_string(), value)
+            })
+            .collect();
+        self.evaluate(values, |t| matches!(t, Target::Overview), now)
+    }
+
+    /// Moves the rules along with the `(rule, subject, value)`
+    /// just seen. Subjects of `kind` rules that are no longer
+    /// seen, such as deleted queues, resolve.
+    fn evaluate(
+        &mut self,
+        values: Vec<(usize, String, f64)>,
+        kind: impl Fn(&Target) -> bool,
+        now: f64,
+    ) -> Vec<AlertEvent> {
+        let rules = &self.config.rules;
+        let event = |i: usize, subject: &str, value: f64, state| {
+            let rule: &Rule = &rules[i];
+            AlertEvent {
+                rule: rule.name.clone(),
+                severity: rule.severity,
+                subject: subject.to_string(),
+                condition: rule.condition.text.clone(),
+                value,
+                state,
+                at: now,
+            }
+        };
+        let mut events = Vec::new();
+        self.pending.values_mut().for_each(|p| p.seen = false);
+        for (i, subject, value) in values {
+            let c = &rules[i].condition;
+            let key = (i, subject);
+            if !c.op.holds(value, c.threshold) {
+                if self.pending.remove(&key).is_some_and(|p| p.firing) {
+                    events.push(event(i, &key.1, value, AlertState::Resolved));
+                }
+                continue;
+            }
+            let p = self.pending.entry(key.clone()).or_insert(Pending {
+                since: now,
+                firing: false,
+                seen: true,
+                value,
+            });
+            p.seen = true;
+            p.value = value;
+            if !p.firing && now - p.since >= rules[i].for_secs as f64 {
+                p.firing = true;
+                events.push(event(i, &key.1, value, AlertState::Firing));
+            }
+        }
+        self.pending.retain(|(i, subject), p| {
+            if p.seen || !kind(&rules[*i].target) {
+                return true;
+            }
+            if p.firing {
+                events.push(event(*i, subject, p.value, AlertState::Resolved));
+            }
+            false
+        });
+        events
+    }
+
+    /// Runs the command hook of the rule behind `event`
+    /// and raises a desktop notification when enabled.
+    /// Neither is waited for and failures are ignored.
+    pub fn dispatch(&self, event: &AlertEvent) {
+        let command = self
+            .config
+            .rules
+            .iter()
+            .find(|r| r.name == event.rule)
+            .and_then(|r| r.command.as_deref());
+        if let Some(command) = command {
+            let mut cmd = Command::new("sh");
+            cmd.arg("-c")
+                .arg(command)
+                .env("RABBITUI_ALERT", &event.rule)
+                .env("RABBITUI_SEVERITY", event.severity.name())
+                .env("RABBITUI_STATE", event.state.name())
+                .env("RABBITUI_SUBJECT", &event.subject)
+                .env("RABBITUI_CONDITION", &event.condition)
+                .env("RABBITUI_VALUE", event.value.to_string());
+            spawn_detached(&mut cmd);
+        }
+        if self.config.desktop {
+            spawn_detached(&mut desktop_notification(event));
+        }
+    }
+}
+
+/// Starts `cmd` away from the terminal, reaping
+/// it on its own thread once done.
+fn spawn_detached(cmd: &mut Command) {
+    let child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    if let Ok(mut child) = child {
+        thread::spawn(move || child.wait());
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn desktop_notification(event: &AlertEvent) -> Command {
+    let script = format!(
+        "display notification {:?} with title {:?}",
+        event.message(),
+        "rabbitui"
+    );
+    let mut cmd = Command::new("osascript");
+    cmd.arg("-e").arg(script);
+    cmd
+}
+
+#[cfg(not(target_os = "macos"))]
+fn desktop_notification(event: &AlertEvent) -> Command {
+    let urgency = match event.severity {
+        Severity::Info => "low",
+        Severity::Warning => "normal",
+        Severity::Critical => "critical",
+    };
+    let mut cmd = Command::new("notify-send");
+    cmd.arg("-u")
+        .arg(urgency)
+        .arg(format!("rabbitui: {}", event.rule))
+        .arg(event.message());
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        parse_duration, AlertConfig, AlertEngine, AlertEvent, AlertState, Condition, Rule, Target,
+    };
+    use crate::models::{Overview, QueueInfo};
+
+    use serde_json::json;
+
+    fn queue(vhost: &str, name: &str, ready: u64) -> QueueInfo {
+        serde_json::from_value(json!({
+            "name": name,
+            "type": "classic",
+            "state": "running",
+            "messages_ready": ready,
+            "messages_unacknowledged": 0,
+            "messages": ready,
+            "vhost": vhost,
+        }))
+        .unwrap()
+    }
+
+    fn engine(rules: Vec<Rule>) -> AlertEngine {
+        AlertEngine::new(AlertConfig {
+            rules,
+            desktop: false,
+        })
+    }
+
+    /// An engine with a single rule on the ready
+    /// messages of the queues named `orders*`.
+    fn orders_engine(for_secs: u64) -> AlertEngine {
+        let rule = Rule::new(
+            "backlog".to_string(),
+            Some("orders*"),
+            None,
+            "ready > 10",
+            for_secs,
+        );
+        engine(vec![rule.unwrap()])
+    }
+
+    fn states(events: &[AlertEvent]) -> Vec<(&str, AlertState, f64)> {
+        events
+            .iter()
+            .map(|e| (e.subject.as_str(), e.state, e.value))
+            .collect()
+    }
+
+    #[test]
+    fn durations_take_a_unit() {
+        assert_eq!(parse_duration("90s"), Ok(90));
+        assert_eq!(parse_duration("2m"), Ok(120));
+        assert_eq!(parse_duration("1h"), Ok(3_600));
+        assert_eq!(parse_duration(" 45 "), Ok(45));
+        assert_eq!(parse_duration("0"), Ok(0));
+    }
+
+    #[test]
+    fn invalid_durations_are_errors() {
+        for d in ["", "m", "1d", "-1s", "1.5m", "1 m", "1mm"] {
+            assert!(parse_duration(d).is_err(), "{:?}", d);
+        }
+    }
+
+    #[test]
+    fn conditions_are_metric_op_number() {
+        let c = Condition::parse("ready   >=  5", &Target::Overview).unwrap();
+        assert_eq!(c.text, "ready >= 5");
+        assert_eq!(c.metric, "ready");
+        assert_eq!(c.threshold, 5.);
+        assert!(c.op.holds(5., 5.));
+        assert!(!c.op.holds(4.9, 5.));
+        assert!(Condition::parse("publish_rate != 0.5", &Target::Overview).is_ok());
+    }
+
+    #[test]
+    fn conditions_only_know_their_target_metrics() {
+        let queues = Rule::new("r".to_string(), Some("*"), None, "connections > 1", 0);
+        assert!(queues.unwrap_err().contains("unknown metric"));
+        assert!(Condition::parse("connections > 1", &Target::Overview).is_ok());
+        let overview = Condition::parse("memory > 1", &Target::Overview);
+        assert!(overview.unwrap_err().contains("unknown metric"));
+    }
+
+    #[test]
+    fn invalid_conditions_are_errors() {
+        let err = |text| Condition::parse(text, &Target::Overview).unwrap_err();
+        assert!(err("ready >").contains("expected \"metric op number\""));
+        assert!(err("ready > 1 2").contains("expected \"metric op number\""));
+        assert!(err("ready => 1").contains("unknown comparison"));
+        assert!(err("ready > lots").contains("invalid threshold"));
+    }
+
+    #[test]
+    fn alerts_fire_once_the_condition_held_long_enough() {
+        let mut engine = orders_engine(60);
+        let queues = [queue("/", "orders", 20)];
+        assert!(engine.evaluate_queues(&queues, 0.).is_empty());
+        assert!(engine.evaluate_queues(&queues, 59.).is_empty());
+        let events = engine.evaluate_queues(&queues, 60.);
+        assert_eq!(states(&events), [("orders on /", AlertState::Firing, 20.)]);
+        assert_eq!(events[0].rule, "backlog");
+        assert_eq!(events[0].condition, "ready > 10");
+        assert_eq!(events[0].at, 60.);
+        // Firing is only reported once.
+        assert!(engine.evaluate_queues(&queues, 90.).is_empty());
+    }
+
+    #[test]
+    fn alerts_without_a_delay_fire_right_away() {
+        let mut engine = orders_engine(0);
+        let events = engine.evaluate_queues(&[queue("/", "orders", 11)], 0.);
+        assert_eq!(states(&events), [("orders on /", AlertState::Firing, 11.)]);
+    }
+
+    #[test]
+    fn the_delay_starts_over_when_the_condition_stops_holding() {
+        let mut engine = orders_engine(60);
+        assert!(engine
+            .evaluate_queues(&[queue("/", "orders", 20)], 0.)
+            .is_empty());
+        assert!(engine
+            .evaluate_queues(&[queue("/", "orders", 5)], 30.)
+            .is_empty());
+        assert!(engine
+            .evaluate_queues(&[queue("/", "orders", 20)], 60.)
+            .is_empty());
+        let events = engine.evaluate_queues(&[queue("/", "orders", 20)], 120.);
+        assert_eq!(states(&events), [("orders on /", AlertState::Firing, 20.)]);
+    }
+
+    #[test]
+    fn alerts_resolve_when_the_condition_stops_holding() {
+        let mut engine = orders_engine(0);
+        engine.evaluate_queues(&[queue("/", "orders", 20)], 0.);
+        let events = engine.evaluate_queues(&[queue("/", "orders", 3)], 10.);
+        assert_eq!(states(&events), [("orders on /", AlertState::Resolved, 3.)]);
+        assert!(engine
+            .evaluate_queues(&[queue("/", "orders", 3)], 20.)
+            .is_empty());
+    }
+
+    #[test]
+    fn alerts_of_vanished_queues_resolve() {
+        let mut engine = orders_engine(0);
+        engine.evaluate_queues(
+            &[queue("/", "orders", 20), queue("/", "orders.dlq", 30)],
+            0.,
+        );
+        let events = engine.evaluate_queues(&[queue("/", "orders", 20)], 10.);
+        assert_eq!(
+            states(&events),
+            [("orders.dlq on /", AlertState::Resolved, 30.)]
+        );
+        // Coming back, the queue starts over.
+        let events = engine.evaluate_queues(&[queue("/", "orders.dlq", 30)], 20.);
+        assert_eq!(
+            states(&events),
+            [
+                ("orders.dlq on /", AlertState::Firing, 30.),
+                ("orders on /", AlertState::Resolved, 20.),
+            ]
+        );
+    }
+
+    #[test]
+    fn vanished_queues_not_firing_resolve_nothing() {
+        let mut engine = orders_engine(60);
+        engine.evaluate_queues(&[queue("/", "orders", 20)], 0.);
+        assert!(engine.evaluate_queues(&[], 10.).is_empty());
+        // The delay starts over once the queue is back.
+        assert!(engine
+            .evaluate_queues(&[queue("/", "orders", 20)], 60.)
+            .is_empty());
+    }
+
+    #[test]
+    fn overview_refreshes_leave_queue_alerts_alone() {
+        let mut engine = engine(vec![
+            Rule::new("backlog".to_string(), Some("orders"), None, "ready > 10", 0).unwrap(),
+            Rule::new("cluster".to_string(), None, None, "queues > 1", 0).unwrap(),
+        ]);
+        engine.evaluate_queues(&[queue("/", "orders", 20)], 0.);
+        let overview: Overview = serde_json::from_value(json!({
+            "queue_totals": {"messages": 20, "messages_ready": 20, "messages_unacknowledged": 0},
+            "object_totals": {"queues": 2},
+        }))
+        .unwrap();
+        let events = engine.evaluate_overview(&overview, 10.);
+        assert_eq!(states(&events), [("cluster", AlertState::Firing, 2.)]);
+        assert!(engine
+            .evaluate_queues(&[queue("/", "orders", 20)], 20.)
+            .is_empty());
+    }
+
+    #[test]
+    fn queue_rules_match_their_vhost_and_pattern() {
+        let rule = Rule::new(
+            "backlog".to_string(),
+            Some("orders"),
+            Some("prod".to_string()),
+            "ready > 10",
+            0,
+        );
+        let mut engine = engine(vec![rule.unwrap()]);
+        let events = engine.evaluate_queues(
+            &[
+                queue("/", "orders", 20),
+                queue("prod", "orders", 20),
+                queue("prod", "orders.dlq", 20),
+            ],
+            0.,
+        );
+        assert_eq!(
+            states(&events),
+            [("orders on prod", AlertState::Firing, 20.)]
+        );
+    }
+}
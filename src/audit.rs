@@ -0,0 +1,475 @@
+use crate::{
+    client::ApiError,
+    models::{
+        column, Effect, ExchangeBindings, ExchangeInfo, HistoryWindow, MQMessage, MQMessageGetBody,
+        Overview, PayloadPost, Policy, PublishResponse, QueueInfo,
+    },
+    widgets::{chart::now, notif::Notifier},
+    ColumnDef, Identifiable, ManagementClient, Rowable, SortValue, Sortable,
+};
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use chrono::{DateTime, SecondsFormat};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// Most entries read back from the log,
+/// older ones being left out.
+const MAX_ENTRIES: usize = 5_000;
+
+/// A mutating call made through rabbitui, one
+/// line of the audit log.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AuditEntry {
+    /// UTC, RFC 3339.
+    pub timestamp: String,
+    pub broker: String,
+    pub user: String,
+    pub vhost: String,
+    /// Queue or exchange acted upon.
+    pub target: String,
+    /// `publish`, `purge`, `get` or `import`.
+    pub action: String,
+    /// Hex SHA-256 of the published payload
+    /// or of the imported definitions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload_sha256: Option<String>,
+    /// Parameters of the call worth keeping, such as
+    /// the routing key or the ack mode of a get.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+    /// `ok`, or the error returned by the API.
+    pub result: String,
+    // Line of the entry in the log, set when read back.
+    #[serde(skip)]
+    pub(crate) line: usize,
+}
+
+/// Default location of the audit log,
+/// `rabbitui/audit.jsonl` in the user data dir.
+pub fn default_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|d| d.join("rabbitui").join("audit.jsonl"))
+}
+
+/// Append-only JSON Lines file of audit entries.
+pub struct AuditLog {
+    file: Mutex<File>,
+}
+
+impl AuditLog {
+    /// Opens the log at `path` for appending,
+    /// creating it and its directory if needed.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Appends an entry as a single write, so that
+    /// concurrent writers never interleave lines.
+    pub fn record(&self, entry: &AuditEntry) -> io::Result<()> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        file.write_all(&line)?;
+        file.flush()
+    }
+}
+
+/// Reads the last entries of the log at `path`, oldest
+/// first. Lines that are not entries are skipped.
+pub fn read_log(path: &Path) -> io::Result<Vec<AuditEntry>> {
+    let file = match File::open(path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut entries = Vec::new();
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        let Ok(mut entry) = serde_json::from_str::<AuditEntry>(&line?) else {
+            continue;
+        };
+        entry.line = i + 1;
+        entries.push(entry);
+    }
+    let skip = entries.len().saturating_sub(MAX_ENTRIES);
+    entries.drain(..skip);
+    Ok(entries)
+}
+
+/// Wraps a client, recording every call that changes
+/// the broker state in an audit log. Reads go straight
+/// through. Without a log, which only happens in read-only
+/// sessions, there is nothing to record.
+pub struct AuditedClient<M: ManagementClient> {
+    inner: M,
+    log: Option<AuditLog>,
+    broker: String,
+    user: String,
+    notifier: Notifier,
+}
+
+impl<M: ManagementClient> AuditedClient<M> {
+    pub fn new(
+        inner: M,
+        log: Option<AuditLog>,
+        broker: &str,
+        user: &str,
+        notifier: Notifier,
+    ) -> Self {
+        Self {
+            inner,
+            log,
+            broker: broker.to_string(),
+            user: user.to_string(),
+            notifier,
+        }
+    }
+
+    fn record<T>(
+        &self,
+        action: &str,
+        vhost: &str,
+        target: &str,
+        payload: Option<&str>,
+        details: Option<String>,
+        result: &Result<T, ApiError>,
+    ) {
+        let Some(log) = &self.log else {
+            return;
+        };
+        let entry = AuditEntry {
+            timestamp: rfc3339(now()),
+            broker: self.broker.clone(),
+            user: self.user.clone(),
+            vhost: vhost.to_string(),
+            target: target.to_string(),
+            action: action.to_string(),
+            payload_sha256: payload.map(sha256_hex),
+            details,
+            result: match result {
+                Ok(_) => "ok".to_string(),
+                Err(e) => e.to_string(),
+            },
+            line: 0,
+        };
+        // A call that went through must not turn into an
+        // error, but the missing entry has to be known.
+        if let Err(e) = log.record(&entry) {
+            self.notifier.error(format!(
+                "Unable to record {} of {} in the audit log: {}",
+                action, target, e
+            ));
+        }
+    }
+}
+
+impl<M: ManagementClient> ManagementClient for AuditedClient<M> {
+    fn get_exchange_overview(&self) -> Result<Vec<ExchangeInfo>, ApiError> {
+        self.inner.get_exchange_overview()
+    }
+
+    fn get_exchange_bindings(
+        &self,
+        exch: &ExchangeInfo,
+    ) -> Result<Vec<ExchangeBindings>, ApiError> {
+        self.inner.get_exchange_bindings(exch)
+    }
+
+    fn get_overview(&self) -> Result<Overview, ApiError> {
+        self.inner.get_overview()
+    }
+
+    fn get_overview_history(&self, window: HistoryWindow) -> Result<Overview, ApiError> {
+        self.inner.get_overview_history(window)
+    }
+
+    fn get_queues_info(&self) -> Result<Vec<QueueInfo>, ApiError> {
+        self.inner.get_queues_info()
+    }
+
+    fn get_queue(&self, queue_name: &str, vhost: &str) -> Result<QueueInfo, ApiError> {
+        self.inner.get_queue(queue_name, vhost)
+    }
+
+    fn get_queue_history(
+        &self,
+        queue_name: &str,
+        vhost: &str,
+        window: HistoryWindow,
+    ) -> Result<QueueInfo, ApiError> {
+        self.inner.get_queue_history(queue_name, vhost, window)
+    }
+
+    fn publish(
+        &self,
+        vhost: &str,
+        exchange: &str,
+        body: &PayloadPost,
+    ) -> Result<PublishResponse, ApiError> {
+        let res = self.inner.publish(vhost, exchange, body);
+        let mut details = format!("routing_key={}", body.routing_key);
+        if let Ok(r) = &res {
+            if !r.routed {
+                details.push_str(" unroutable");
+            }
+        }
+        let target = match exchange {
+            "" => "amq.default",
+            e => e,
+        };
+        self.record(
+            "publish",
+            vhost,
+            target,
+            Some(&body.payload),
+            Some(details),
+            &res,
+        );
+        res
+    }
+
+    fn get_messages(
+        &self,
+        queue_name: &str,
+        vhost: &str,
+        body: &MQMessageGetBody,
+    ) -> Result<Vec<MQMessage>, ApiError> {
+        let res = self.inner.get_messages(queue_name, vhost, body);
+        // Gets may consume messages depending on their
+        // ack mode, which is kept along with the count.
+        let details = serde_json::to_value(body).ok().map(|b| {
+            format!(
+                "ackmode={} count={}",
+                b["ackmode"].as_str().unwrap_or_default(),
+                b["count"]
+            )
+        });
+        self.record("get", vhost, queue_name, None, details, &res);
+        res
+    }
+
+    fn ping(&self) -> Result<(), ApiError> {
+        self.inner.ping()
+    }
+
+    fn purge_queue(&self, queue_name: &str, vhost: &str) -> Result<Effect, ApiError> {
+        let res = self.inner.purge_queue(queue_name, vhost);
+        self.record("purge", vhost, queue_name, None, None, &res);
+        res
+    }
+
+    fn get_bindings(&self) -> Result<Vec<ExchangeBindings>, ApiError> {
+        self.inner.get_bindings()
+    }
+
+    fn get_policies(&self) -> Result<Vec<Policy>, ApiError> {
+        self.inner.get_policies()
+    }
+
+    fn get_definitions(&self, vhost: Option<&str>) -> Result<Value, ApiError> {
+        self.inner.get_definitions(vhost)
+    }
+
+    fn post_definitions(&self, vhost: Option<&str>, defs: &Value) -> Result<Effect, ApiError> {
+        let res = self.inner.post_definitions(vhost, defs);
+        let payload = defs.to_string();
+        let vhost = vhost.unwrap_or("*");
+        self.record("import", vhost, "definitions", Some(&payload), None, &res);
+        res
+    }
+}
+
+fn sha256_hex(payload: &str) -> String {
+    Sha256::digest(payload.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// UTC date and time of `secs` since the epoch,
+/// such as `2024-05-01T09:30:00.250Z`.
+pub fn rfc3339(secs: f64) -> String {
+    DateTime::from_timestamp_millis((secs * 1000.).floor() as i64)
+        .map(|t| t.to_rfc3339_opts(SecondsFormat::Millis, true))
+        .unwrap_or_default()
+}
+
+const AUDIT_COLUMNS: [ColumnDef; 9] = [
+    column("timestamp", "Time (UTC)", 16, true),
+    column("user", "User", 8, true),
+    column("broker", "Broker", 12, true),
+    column("vhost", "Vhost", 6, true),
+    column("action", "Action", 7, true),
+    column("target", "Target", 14, true),
+    column("details", "Details", 15, true),
+    column("payload_sha256", "Payload SHA-256", 10, true),
+    column("result", "Result", 12, true),
+];
+
+impl Rowable for AuditEntry {
+    fn columns() -> &'static [ColumnDef] {
+        &AUDIT_COLUMNS
+    }
+
+    fn cell(&self, field: usize) -> String {
+        match AUDIT_COLUMNS[field].key {
+            "timestamp" => self.timestamp.replace('T', " ").replace('Z', ""),
+            "user" => self.user.clone(),
+            "broker" => self.broker.clone(),
+            "vhost" => self.vhost.clone(),
+            "action" => self.action.clone(),
+            "target" => self.target.clone(),
+            "details" => self.details.clone().unwrap_or_default(),
+            "payload_sha256" => self.payload_sha256.clone().unwrap_or_default(),
+            "result" => self.result.clone(),
+            _ => String::new(),
+        }
+    }
+}
+
+impl Sortable for AuditEntry {
+    fn sort_value(&self, field: usize) -> SortValue {
+        match AUDIT_COLUMNS[field].key {
+            "timestamp" => SortValue::Number(self.line as f64),
+            _ => SortValue::Text(self.cell(field).to_lowercase()),
+        }
+    }
+}
+
+impl Identifiable for AuditEntry {
+    type Id = usize;
+
+    fn id(&self) -> Self::Id {
+        self.line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_log, rfc3339, sha256_hex, AuditEntry, AuditLog, MAX_ENTRIES};
+
+    use std::{
+        env, fs,
+        io::Write,
+        path::{Path, PathBuf},
+        process,
+    };
+
+    /// A fresh log path, unique to the test.
+    fn log_path(test: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("rabbitui-audit-{}-{}", process::id(), test));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("audit.jsonl")
+    }
+
+    fn entry(target: &str) -> AuditEntry {
+        AuditEntry {
+            timestamp: rfc3339(0.),
+            broker: "http://localhost:15672".to_string(),
+            user: "guest".to_string(),
+            vhost: "/".to_string(),
+            target: target.to_string(),
+            action: "purge".to_string(),
+            payload_sha256: None,
+            details: None,
+            result: "ok".to_string(),
+            line: 0,
+        }
+    }
+
+    fn targets(path: &Path) -> Vec<(usize, String)> {
+        read_log(path)
+            .unwrap()
+            .into_iter()
+            .map(|e| (e.line, e.target))
+            .collect()
+    }
+
+    #[test]
+    fn timestamps_are_utc_with_milliseconds() {
+        assert_eq!(rfc3339(0.), "1970-01-01T00:00:00.000Z");
+        assert_eq!(rfc3339(1e9), "2001-09-09T01:46:40.000Z");
+        assert_eq!(rfc3339(951_782_400.), "2000-02-29T00:00:00.000Z");
+        assert_eq!(rfc3339(1_714_555_800.25), "2024-05-01T09:30:00.250Z");
+        assert_eq!(rfc3339(1_735_689_599.999), "2024-12-31T23:59:59.999Z");
+    }
+
+    #[test]
+    fn payloads_are_hashed_as_hex_sha256() {
+        assert_eq!(
+            sha256_hex(""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            sha256_hex("1"),
+            "6b86b273ff34fce19d6b804eff5a3f5747ada4eaa22f1d49c01e52ddb7875b4b"
+        );
+    }
+
+    #[test]
+    fn a_missing_log_has_no_entries() {
+        assert!(read_log(&log_path("missing")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn recorded_entries_read_back_in_order() {
+        let path = log_path("order");
+        let log = AuditLog::open(&path).unwrap();
+        for target in ["a", "b", "c"] {
+            log.record(&entry(target)).unwrap();
+        }
+        let entries = read_log(&path).unwrap();
+        assert_eq!(
+            targets(&path),
+            [
+                (1, "a".to_string()),
+                (2, "b".to_string()),
+                (3, "c".to_string())
+            ]
+        );
+        assert_eq!(entries[0].timestamp, "1970-01-01T00:00:00.000Z");
+        assert_eq!(entries[0].payload_sha256, None);
+    }
+
+    #[test]
+    fn malformed_lines_are_skipped() {
+        let path = log_path("malformed");
+        let log = AuditLog::open(&path).unwrap();
+        log.record(&entry("a")).unwrap();
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        writeln!(file, "not json").unwrap();
+        writeln!(file, r#"{{"target": "missing fields"}}"#).unwrap();
+        writeln!(file).unwrap();
+        log.record(&entry("b")).unwrap();
+        assert_eq!(targets(&path), [(1, "a".to_string()), (5, "b".to_string())]);
+    }
+
+    #[test]
+    fn only_the_last_entries_are_read() {
+        let path = log_path("cap");
+        let log = AuditLog::open(&path).unwrap();
+        for i in 0..MAX_ENTRIES + 10 {
+            log.record(&entry(&i.to_string())).unwrap();
+        }
+        let entries = read_log(&path).unwrap();
+        assert_eq!(entries.len(), MAX_ENTRIES);
+        assert_eq!(entries[0].target, "10");
+        assert_eq!(entries[0].line, 11);
+        assert_eq!(
+            entries.last().map(|e| e.target.clone()),
+            Some((MAX_ENTRIES + 9).to_string())
+        );
+    }
+}
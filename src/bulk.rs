@@ -0,0 +1,327 @@
+use crate::{
+    models::{Effect, PayloadPost},
+    ManagementClient,
+};
+
+use std::{
+    fs, io,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// A single line of a JSON Lines capture. Lines that
+/// are not an object with a `payload` key are published
+/// verbatim instead.
+#[derive(Deserialize, Debug)]
+struct Envelope {
+    payload: Value,
+    routing_key: Option<String>,
+    #[serde(default)]
+    properties: Map<String, Value>,
+    #[serde(default)]
+    headers: Map<String, Value>,
+    payload_encoding: Option<String>,
+}
+
+impl Envelope {
+    fn into_post(self, default_key: &str) -> PayloadPost {
+        let payload = match self.payload {
+            Value::String(s) => s,
+            other => other.to_string(),
+        };
+        let mut properties = self.properties;
+        if !self.headers.is_empty() {
+            // Headers given both ways are merged, the
+            // top level ones winning.
+            let mut headers = match properties.remove("headers") {
+                Some(Value::Object(h)) => h,
+                _ => Map::new(),
+            };
+            headers.extend(self.headers);
+            properties.insert("headers".to_string(), Value::Object(headers));
+        }
+        let post = PayloadPost::default()
+            .routing_key(self.routing_key.unwrap_or_else(|| default_key.to_string()))
+            .payload(payload)
+            .properties(properties);
+        match self.payload_encoding {
+            Some(e) => post.encoding(e),
+            None => post,
+        }
+    }
+}
+
+pub fn is_jsonl(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("jsonl") | Some("ndjson")
+    )
+}
+
+fn parse_line(line: &str, default_key: &str) -> PayloadPost {
+    match serde_json::from_str::<Envelope>(line) {
+        Ok(env) => env.into_post(default_key),
+        Err(_) => PayloadPost::default()
+            .routing_key(default_key.to_string())
+            .payload(line.to_string()),
+    }
+}
+
+fn load_file(path: &Path, default_key: &str, out: &mut Vec<PayloadPost>) -> io::Result<()> {
+    let content = fs::read_to_string(path)?;
+    if is_jsonl(path) {
+        out.extend(
+            content
+                .lines()
+                .filter(|l| !l.trim().is_empty())
+                .map(|l| parse_line(l, default_key)),
+        );
+    } else {
+        out.push(
+            PayloadPost::default()
+                .routing_key(default_key.to_string())
+                .payload(content),
+        );
+    }
+    Ok(())
+}
+
+/// Reads every message contained in `path`. A `.jsonl` file
+/// yields one message per line, any other file is a single
+/// message, and a directory yields the messages of every
+/// file directly inside it, in name order.
+pub fn load_messages(path: &Path, default_key: &str) -> io::Result<Vec<PayloadPost>> {
+    let mut out = Vec::new();
+    if path.is_dir() {
+        let mut files: Vec<_> = fs::read_dir(path)?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.is_file())
+            .collect();
+        files.sort();
+        for f in files {
+            load_file(&f, default_key, &mut out)?;
+        }
+    } else {
+        load_file(path, default_key, &mut out)?;
+    }
+    Ok(out)
+}
+
+/// Spaces out consecutive sends so that at most
+/// `rate` go out per second, 0 meaning unlimited.
+pub struct Pacer {
+    interval: Duration,
+    next: Instant,
+}
+
+impl Pacer {
+    pub fn new(rate: u32) -> Self {
+        let interval = if rate == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs(1) / rate
+        };
+        Self {
+            interval,
+            next: Instant::now(),
+        }
+    }
+
+    /// Blocks until the next send is allowed.
+    pub fn wait(&mut self) {
+        let now = Instant::now();
+        if self.next > now {
+            thread::sleep(self.next - now);
+        }
+        self.next = self.next.max(now) + self.interval;
+    }
+}
+
+enum Progress {
+    Routed(bool),
+    DryRun,
+    Failed(String),
+    Done,
+}
+
+/// Tally of a bulk publish.
+#[derive(Default, Debug, Clone)]
+pub struct BulkSummary {
+    pub total: usize,
+    pub routed: usize,
+    pub unroutable: usize,
+    pub failed: usize,
+    /// Only shown, in a dry run.
+    pub dry_run: usize,
+    /// What went wrong with the first message failing.
+    pub first_error: Option<String>,
+    pub finished: bool,
+}
+
+impl BulkSummary {
+    pub fn sent(&self) -> usize {
+        self.routed + self.unroutable + self.failed + self.dry_run
+    }
+
+    pub fn ratio(&self) -> f64 {
+        if self.total == 0 {
+            1.
+        } else {
+            self.sent() as f64 / self.total as f64
+        }
+    }
+}
+
+/// A bulk publish running in its own thread. Progress
+/// is pulled from the worker with `poll`.
+pub struct BulkJob {
+    rx: mpsc::Receiver<Progress>,
+    cancelled: Arc<AtomicBool>,
+    pub summary: BulkSummary,
+}
+
+impl BulkJob {
+    /// Starts publishing `messages` into `vhost` through the
+    /// default exchange. `rate` caps the messages sent per
+    /// second, 0 meaning unlimited.
+    pub fn spawn<M>(client: Arc<M>, vhost: String, messages: Vec<PayloadPost>, rate: u32) -> Self
+    where
+        M: ManagementClient + 'static,
+    {
+        let (tx, rx) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let summary = BulkSummary {
+            total: messages.len(),
+            ..BulkSummary::default()
+        };
+        let thread_cancelled = Arc::clone(&cancelled);
+        thread::spawn(move || {
+            let mut pacer = Pacer::new(rate);
+            let mut dry_run = false;
+            for m in messages {
+                if thread_cancelled.load(Ordering::Relaxed) {
+                    break;
+                }
+                // Dry runs send nothing, so there is nothing to pace.
+                if !dry_run {
+                    pacer.wait();
+                }
+                let p = match client.publish(&vhost, "", &m) {
+                    Ok(res) if res.effect == Effect::DryRun => {
+                        dry_run = true;
+                        Progress::DryRun
+                    }
+                    Ok(res) => Progress::Routed(res.routed),
+                    Err(e) => Progress::Failed(e.to_string()),
+                };
+                if tx.send(p).is_err() {
+                    return;
+                }
+            }
+            let _ = tx.send(Progress::Done);
+        });
+        Self {
+            rx,
+            cancelled,
+            summary,
+        }
+    }
+
+    /// Drains pending progress from the worker.
+    pub fn poll(&mut self) {
+        for p in self.rx.try_iter() {
+            match p {
+                Progress::Routed(true) => self.summary.routed += 1,
+                Progress::Routed(false) => self.summary.unroutable += 1,
+                Progress::DryRun => self.summary.dry_run += 1,
+                Progress::Failed(e) => {
+                    self.summary.failed += 1;
+                    self.summary.first_error.get_or_insert(e);
+                }
+                Progress::Done => self.summary.finished = true,
+            }
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_line;
+
+    use serde_json::json;
+
+    #[test]
+    fn envelopes_give_payload_key_and_properties() {
+        let line = json!({
+            "payload": "hello",
+            "routing_key": "orders",
+            "properties": {"content_type": "text/plain"},
+            "payload_encoding": "base64",
+        })
+        .to_string();
+        let post = parse_line(&line, "default");
+        assert_eq!(post.payload, "hello");
+        assert_eq!(post.routing_key, "orders");
+        assert_eq!(post.encoding, "base64");
+        assert_eq!(post.properties["content_type"], "text/plain");
+    }
+
+    #[test]
+    fn envelopes_default_to_the_given_key() {
+        let post = parse_line(r#"{"payload": "hello"}"#, "default");
+        assert_eq!(post.routing_key, "default");
+        assert_eq!(post.encoding, "string");
+        assert!(post.properties.is_empty());
+    }
+
+    #[test]
+    fn non_string_payloads_are_sent_as_json() {
+        let post = parse_line(r#"{"payload": {"id": 1}}"#, "default");
+        assert_eq!(post.payload, r#"{"id":1}"#);
+    }
+
+    #[test]
+    fn top_level_headers_win_over_property_headers() {
+        let line = json!({
+            "payload": "hello",
+            "properties": {"headers": {"a": "property", "b": "property"}, "priority": 1},
+            "headers": {"a": "top", "c": "top"},
+        })
+        .to_string();
+        let post = parse_line(&line, "default");
+        assert_eq!(
+            post.properties["headers"],
+            json!({"a": "top", "b": "property", "c": "top"})
+        );
+        assert_eq!(post.properties["priority"], 1);
+    }
+
+    #[test]
+    fn property_headers_alone_are_kept() {
+        let line = r#"{"payload": "hello", "properties": {"headers": {"a": "1"}}}"#;
+        let post = parse_line(line, "default");
+        assert_eq!(post.properties["headers"], json!({"a": "1"}));
+    }
+
+    #[test]
+    fn other_lines_are_published_verbatim() {
+        for line in [r#"{"id": 1}"#, "[1, 2]", "not json", r#""text""#] {
+            let post = parse_line(line, "default");
+            assert_eq!(post.payload, line);
+            assert_eq!(post.routing_key, "default");
+            assert!(post.properties.is_empty());
+        }
+    }
+}
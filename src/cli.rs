@@ -1,21 +1,194 @@
-use clap::Parser;
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand, ValueEnum};
 
 #[derive(Parser, Debug, Clone)]
 #[command(name = "rabbitui")]
 #[command(about = "A TUI application for RabbitMQ management")]
 pub struct Cli {
-    #[arg(short, long)]
-    #[arg(default_value="http://localhost:15672")]
-    #[arg(help = "Http(s) address of the API. Excludes trailing slash")]
-    pub addr: String,
-
-    #[arg(short, long)]
-    #[arg(default_value="guest")]
-    #[arg(help = "Username for the API auth")]
-    pub user: String,
-
-    #[arg(short, long)]
-    #[arg(default_value="guest")]
-    #[arg(help = "Password for the API auth")]
-    pub pass: String,
+    #[arg(short, long, global = true)]
+    #[arg(
+        help = "Http(s) address of the API. Excludes trailing slash [default: http://localhost:15672]"
+    )]
+    pub addr: Option<String>,
+
+    #[arg(short, long, global = true)]
+    #[arg(help = "Username for the API auth [default: guest]")]
+    pub user: Option<String>,
+
+    #[arg(short, long, global = true)]
+    #[arg(help = "Password for the API auth [default: guest]")]
+    pub pass: Option<String>,
+
+    #[arg(long, global = true)]
+    #[arg(help = "Profile of the config file to connect with")]
+    pub profile: Option<String>,
+
+    #[arg(long, global = true)]
+    #[arg(help = "Disable every action changing the broker state")]
+    pub read_only: bool,
+
+    #[arg(long, global = true)]
+    #[arg(help = "Show the requests changing the broker state instead of sending them")]
+    pub dry_run: bool,
+
+    #[arg(short, long, global = true)]
+    #[arg(help = "Config file, defaults to rabbitui/config.toml in the user config dir")]
+    pub config: Option<PathBuf>,
+
+    #[arg(long, global = true)]
+    #[arg(help = "Messages per second for bulk publishes, 0 for unlimited")]
+    pub publish_rate: Option<u32>,
+
+    #[arg(long, global = true)]
+    #[arg(help = "Audit log file, defaults to rabbitui/audit.jsonl in the user data dir")]
+    pub audit_log: Option<PathBuf>,
+
+    #[arg(short, long, global = true)]
+    #[arg(value_enum, default_value_t = OutputFormat::Table)]
+    #[arg(help = "Output format of the headless commands")]
+    pub output: OutputFormat,
+
+    /// Runs a single command instead of the TUI.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum OutputFormat {
+    Table,
+    Json,
+    Csv,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Inspect queues
+    Queues {
+        #[command(subcommand)]
+        action: ListAction,
+    },
+    /// Inspect exchanges
+    Exchanges {
+        #[command(subcommand)]
+        action: ListAction,
+    },
+    /// Publish messages to an exchange
+    Publish {
+        #[arg(long, default_value = "/")]
+        vhost: String,
+
+        #[arg(short, long, default_value = "")]
+        #[arg(help = "Exchange to publish to, the default exchange if empty")]
+        exchange: String,
+
+        #[arg(short = 'k', long)]
+        #[arg(help = "Routing key, the queue name for the default exchange")]
+        routing_key: String,
+
+        #[arg(short, long, conflicts_with = "payload")]
+        #[arg(help = "File, .jsonl file or directory of messages to publish")]
+        file: Option<PathBuf>,
+
+        #[arg(help = "Payload to publish, read from stdin when omitted")]
+        payload: Option<String>,
+    },
+    /// Show which queues a message would reach, without publishing it
+    Route {
+        #[arg(long, default_value = "/")]
+        vhost: String,
+
+        #[arg(short, long, default_value = "")]
+        #[arg(help = "Exchange to publish to, the default exchange if empty")]
+        exchange: String,
+
+        #[arg(short = 'k', long, default_value = "")]
+        routing_key: String,
+
+        #[arg(short = 'H', long = "header")]
+        #[arg(help = "Header of the message as key=value, can be repeated")]
+        headers: Vec<String>,
+    },
+    /// Fetch messages from the head of a queue
+    Get {
+        queue: String,
+
+        #[arg(long, default_value = "/")]
+        vhost: String,
+
+        #[arg(short = 'n', long, default_value_t = 1)]
+        count: u64,
+
+        #[arg(long)]
+        #[arg(help = "Acknowledge the messages, removing them from the queue")]
+        ack: bool,
+    },
+    /// Purge every message of a queue
+    Purge {
+        queue: String,
+
+        #[arg(long, default_value = "/")]
+        vhost: String,
+
+        #[arg(short, long)]
+        #[arg(help = "Do not ask for confirmation, unless the queue name has to be typed")]
+        yes: bool,
+
+        #[arg(long, value_name = "QUEUE")]
+        #[arg(help = "Confirm with the queue name, for purges needing it typed")]
+        confirm: Option<String>,
+    },
+    /// Export or import definitions
+    Definitions {
+        #[command(subcommand)]
+        action: DefinitionsAction,
+    },
+    /// Compare exchanges, queues, bindings and policies
+    /// with another profile or a definitions file
+    Diff {
+        #[arg(help = "Profile of the config file, or definitions file, to compare with")]
+        against: String,
+
+        #[arg(long)]
+        #[arg(help = "Vhost to compare, every vhost if omitted")]
+        vhost: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum DefinitionsAction {
+    /// Save the definitions of the broker or of a vhost
+    Export {
+        #[arg(long)]
+        #[arg(help = "Vhost to export, the whole broker if omitted")]
+        vhost: Option<String>,
+
+        #[arg(short, long)]
+        #[arg(help = "File to write, stdout if omitted")]
+        file: Option<PathBuf>,
+    },
+    /// Show what a definitions file would create, then import it
+    Import {
+        file: PathBuf,
+
+        #[arg(long)]
+        #[arg(help = "Vhost to import into, the whole broker if omitted")]
+        vhost: Option<String>,
+
+        #[arg(short, long)]
+        #[arg(
+            help = "Do not ask for confirmation, unless the vhost or cluster name has to be typed"
+        )]
+        yes: bool,
+
+        #[arg(long, value_name = "NAME")]
+        #[arg(help = "Confirm with the vhost or cluster name, for imports needing it typed")]
+        confirm: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone, Copy)]
+pub enum ListAction {
+    /// List every entry
+    List,
 }
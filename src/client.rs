@@ -1,15 +1,64 @@
 use crate::{
+    config::{Profile, DEFAULT_ADDR, DEFAULT_PASS, DEFAULT_USER},
     models::{
-        ExchangeBindings, ExchangeInfo, MQMessage, MQMessageGetBody, Overview, PayloadPost,
-        QueueInfo,
+        Effect, ExchangeBindings, ExchangeInfo, HistoryWindow, MQMessage, MQMessageGetBody,
+        Overview, PayloadPost, Policy, PublishResponse, QueueInfo,
     },
     ManagementClient,
 };
 
+use std::{error::Error, fmt};
+
+use reqwest::StatusCode;
 use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+/// Why a call to the management API failed,
+/// or was refused before being sent.
+#[derive(Debug)]
+pub enum ApiError {
+    Http(reqwest::Error),
+    /// The call would change the broker in a
+    /// read-only session, along with why.
+    ReadOnly(String),
+}
+
+impl ApiError {
+    /// Status of the API's answer, if there was one.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            ApiError::Http(e) => e.status(),
+            ApiError::ReadOnly(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ApiError::Http(e) => e.fmt(f),
+            ApiError::ReadOnly(why) => f.write_str(why),
+        }
+    }
+}
+
+impl Error for ApiError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ApiError::Http(e) => Some(e),
+            ApiError::ReadOnly(_) => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for ApiError {
+    fn from(e: reqwest::Error) -> Self {
+        ApiError::Http(e)
+    }
+}
 
 #[allow(dead_code)] // we dont use all variants yet, but we might
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Clone, Copy)]
 #[serde(rename_all = "snake_case")]
 pub enum Ackmode {
     AckRequeueTrue,
@@ -18,6 +67,52 @@ pub enum Ackmode {
     RejectRequeueFalse,
 }
 
+impl Ackmode {
+    /// Whether the messages got stay in the queue.
+    pub fn requeues(self) -> bool {
+        matches!(self, Ackmode::AckRequeueTrue | Ackmode::RejectRequeueTrue)
+    }
+}
+
+/// Endpoint publishing to `exchange`. The default
+/// exchange has no name, the API addresses it as
+/// amq.default instead.
+pub fn publish_endpoint(vhost: &str, exchange: &str) -> String {
+    let exchange = if exchange.is_empty() {
+        "amq.default"
+    } else {
+        exchange
+    };
+    format!(
+        "/api/exchanges/{}/{}/publish",
+        vhost.replace("/", "%2F"),
+        exchange
+    )
+}
+
+pub fn get_endpoint(queue_name: &str, vhost: &str) -> String {
+    format!(
+        "/api/queues/{}/{}/get",
+        vhost.replace("/", "%2F"),
+        queue_name
+    )
+}
+
+pub fn purge_endpoint(queue_name: &str, vhost: &str) -> String {
+    format!(
+        "/api/queues/{}/{}/contents",
+        vhost.replace("/", "%2F"),
+        queue_name
+    )
+}
+
+pub fn definitions_endpoint(vhost: Option<&str>) -> String {
+    match vhost {
+        Some(v) => format!("/api/definitions/{}", v.replace("/", "%2F")),
+        None => "/api/definitions".to_string(),
+    }
+}
+
 pub struct Client {
     addr: String,
     user: String,
@@ -35,18 +130,27 @@ impl Client {
         }
     }
 
-    pub fn delete(&self, endpoint: &str) {
+    /// Connects as `profile`, with the defaults
+    /// for whatever it leaves out.
+    pub fn from_profile(profile: &Profile) -> Self {
+        Self::new(
+            profile.addr.as_deref().unwrap_or(DEFAULT_ADDR),
+            profile.user.as_deref().unwrap_or(DEFAULT_USER),
+            Some(profile.pass.as_deref().unwrap_or(DEFAULT_PASS).to_string()),
+        )
+    }
+
+    pub fn delete(&self, endpoint: &str) -> Result<(), ApiError> {
         let url = format!("{}{}", self.addr, endpoint);
-        // TODO care about this result.
-        let _ = self
-            .client
+        self.client
             .delete(url)
             .basic_auth(&self.user, self.pass.as_ref())
-            .send();
+            .send()?
+            .error_for_status()?;
+        Ok(())
     }
 
-    // TODO change this to Result and cover api failures!!
-    pub fn get<T>(&self, endpoint: &str) -> Result<T, reqwest::Error>
+    pub fn get<T>(&self, endpoint: &str) -> Result<T, ApiError>
     where
         T: DeserializeOwned,
     {
@@ -55,10 +159,12 @@ impl Client {
             .get(url)
             .basic_auth(&self.user, self.pass.as_ref())
             .send()?
+            .error_for_status()?
             .json()
+            .map_err(ApiError::from)
     }
 
-    pub fn post<T, S>(&self, endpoint: &str, body: &S) -> Result<T, reqwest::Error>
+    pub fn post<T, S>(&self, endpoint: &str, body: &S) -> Result<T, ApiError>
     where
         T: DeserializeOwned,
         S: Serialize,
@@ -69,69 +175,113 @@ impl Client {
             .basic_auth(&self.user, self.pass.as_ref())
             .json(body)
             .send()?
+            .error_for_status()?
             .json()
+            .map_err(ApiError::from)
+    }
+
+    /// Posts `body`, with no interest in what the API answers.
+    pub fn post_no_content<S>(&self, endpoint: &str, body: &S) -> Result<(), ApiError>
+    where
+        S: Serialize,
+    {
+        let url = format!("{}{}", self.addr, endpoint);
+        self.client
+            .post(url)
+            .basic_auth(&self.user, self.pass.as_ref())
+            .json(body)
+            .send()?
+            .error_for_status()?;
+        Ok(())
     }
 }
 
 impl ManagementClient for Client {
-    fn get_exchange_overview(&self) -> Vec<ExchangeInfo> {
-        self.get::<Vec<ExchangeInfo>>("/api/exchanges").unwrap()
+    fn get_exchange_overview(&self) -> Result<Vec<ExchangeInfo>, ApiError> {
+        self.get::<Vec<ExchangeInfo>>("/api/exchanges")
     }
 
-    fn get_exchange_bindings(&self, exch: &ExchangeInfo) -> Vec<ExchangeBindings> {
+    fn get_exchange_bindings(
+        &self,
+        exch: &ExchangeInfo,
+    ) -> Result<Vec<ExchangeBindings>, ApiError> {
         let n = exch.vhost.replace("/", "%2F");
         let endpoint = format!("/api/exchanges/{}/{}/bindings/source", n, exch.name);
-        self.get::<Vec<ExchangeBindings>>(&endpoint).unwrap()
-    }
-
-    fn get_overview(&self) -> Overview {
-        self.get::<Overview>("/api/overview").unwrap()
-    }
-
-    fn get_queues_info(&self) -> Vec<QueueInfo> {
-        self.get::<Vec<QueueInfo>>("/api/queues").unwrap()
-    }
-
-    fn post_queue_payload(&self, queue_name: String, vhost: &str, payload: String) {
-        let vhost_encoded = vhost.replace("/", "%2F");
-        let endpoint = format!("{}/api/exchanges/{}//publish", self.addr, vhost_encoded);
-        let body = PayloadPost::default()
-            .routing_key(queue_name)
-            .payload(payload);
-        // TODO consider failures
-        let _ = self
-            .client
-            .post(endpoint)
-            .basic_auth("guest", Some("guest"))
-            .json(&body)
-            .send();
-    }
-
-    fn pop_queue_item(&self, queue_name: &str, vhost: &str) -> Option<MQMessage> {
-        let vhost_encoded = vhost.replace("/", "%2F");
-        let endpoint = format!("/api/queues/{}/{}/get", vhost_encoded, queue_name);
-        let body = MQMessageGetBody::default();
-        let mut res = self
-            .post::<Vec<MQMessage>, MQMessageGetBody>(&endpoint, &body)
-            .unwrap();
-        if res.is_empty() {
-            None
-        } else {
-            Some(res.remove(0))
-        }
+        self.get::<Vec<ExchangeBindings>>(&endpoint)
     }
 
-    fn ping(&self) -> Result<(), ()> {
-        // TODO better ping?
-        match self.get::<Overview>("/api/overview") {
-            Ok(_) => Ok(()),
-            Err(_) => Err(()),
-        }
+    fn get_overview(&self) -> Result<Overview, ApiError> {
+        self.get::<Overview>("/api/overview")
+    }
+
+    fn get_overview_history(&self, window: HistoryWindow) -> Result<Overview, ApiError> {
+        self.get::<Overview>(&format!("/api/overview?{}", window.query()))
+    }
+
+    fn get_queues_info(&self) -> Result<Vec<QueueInfo>, ApiError> {
+        self.get::<Vec<QueueInfo>>("/api/queues")
+    }
+
+    fn get_queue(&self, queue_name: &str, vhost: &str) -> Result<QueueInfo, ApiError> {
+        let encoded = vhost.replace("/", "%2F");
+        self.get::<QueueInfo>(&format!("/api/queues/{}/{}", encoded, queue_name))
     }
 
-    fn purge_queue(&self, queue_name: &str, vhost: &str) {
+    fn get_queue_history(
+        &self,
+        queue_name: &str,
+        vhost: &str,
+        window: HistoryWindow,
+    ) -> Result<QueueInfo, ApiError> {
         let encoded = vhost.replace("/", "%2F");
-        let endpoint = format!("/api/queues/{}/{}/contents", encoded, queue_name);
-        self.delete(&endpoint);
+        let endpoint = format!("/api/queues/{}/{}?{}", encoded, queue_name, window.query());
+        self.get::<QueueInfo>(&endpoint)
+    }
+
+    fn publish(
+        &self,
+        vhost: &str,
+        exchange: &str,
+        body: &PayloadPost,
+    ) -> Result<PublishResponse, ApiError> {
+        let endpoint = publish_endpoint(vhost, exchange);
+        self.post::<PublishResponse, PayloadPost>(&endpoint, body)
+    }
+
+    fn get_messages(
+        &self,
+        queue_name: &str,
+        vhost: &str,
+        body: &MQMessageGetBody,
+    ) -> Result<Vec<MQMessage>, ApiError> {
+        let endpoint = get_endpoint(queue_name, vhost);
+        self.post::<Vec<MQMessage>, MQMessageGetBody>(&endpoint, body)
+    }
+
+    fn ping(&self) -> Result<(), ApiError> {
+        // TODO better ping?
+        self.get::<Overview>("/api/overview").map(|_| ())
+    }
+
+    fn purge_queue(&self, queue_name: &str, vhost: &str) -> Result<Effect, ApiError> {
+        self.delete(&purge_endpoint(queue_name, vhost))?;
+        Ok(Effect::Applied)
+    }
+
+    fn get_bindings(&self) -> Result<Vec<ExchangeBindings>, ApiError> {
+        self.get::<Vec<ExchangeBindings>>("/api/bindings")
+    }
+
+    fn get_policies(&self) -> Result<Vec<Policy>, ApiError> {
+        self.get::<Vec<Policy>>("/api/policies")
+    }
+
+    fn get_definitions(&self, vhost: Option<&str>) -> Result<Value, ApiError> {
+        self.get::<Value>(&definitions_endpoint(vhost))
+    }
+
+    fn post_definitions(&self, vhost: Option<&str>, defs: &Value) -> Result<Effect, ApiError> {
+        self.post_no_content(&definitions_endpoint(vhost), defs)?;
+        Ok(Effect::Applied)
     }
 }
@@ -0,0 +1,142 @@
+use std::{
+    env,
+    error::Error,
+    io::{self, Write},
+    process::{Command, Stdio},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clipboard::{ClipboardContext, ClipboardProvider};
+
+type ClipResult<T> = Result<T, Box<dyn Error>>;
+
+enum Backend {
+    /// Wayland, through the wl-clipboard tools.
+    Wayland,
+    /// X11, through the clipboard crate.
+    X11(ClipboardContext),
+    /// OSC 52 escape sequences, understood by most terminal
+    /// emulators, including over SSH. Copy only.
+    Osc52,
+    /// Last resort, only shared within rabbitui.
+    Memory,
+}
+
+/// System clipboard with fallbacks for headless and
+/// remote sessions. Whatever the backend, the last copied
+/// content is also kept in process so that pasting keeps
+/// working when the backend cannot be read from.
+pub struct Clipboard {
+    backend: Backend,
+    buffer: Option<String>,
+}
+
+fn has_var(name: &str) -> bool {
+    env::var_os(name).is_some_and(|v| !v.is_empty())
+}
+
+fn wl_clipboard_available() -> bool {
+    Command::new("wl-paste")
+        .arg("--version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success())
+}
+
+impl Default for Clipboard {
+    fn default() -> Self {
+        let backend = if has_var("WAYLAND_DISPLAY") && wl_clipboard_available() {
+            Backend::Wayland
+        } else if let Some(ctx) = has_var("DISPLAY")
+            .then(|| ClipboardProvider::new().ok())
+            .flatten()
+        {
+            Backend::X11(ctx)
+        } else if termion::is_tty(&io::stdout()) {
+            Backend::Osc52
+        } else {
+            Backend::Memory
+        };
+        Self {
+            backend,
+            buffer: None,
+        }
+    }
+}
+
+impl Clipboard {
+    /// Short name of the backend in use.
+    pub fn name(&self) -> &'static str {
+        match self.backend {
+            Backend::Wayland => "wayland",
+            Backend::X11(_) => "x11",
+            Backend::Osc52 => "osc52",
+            Backend::Memory => "memory",
+        }
+    }
+
+    pub fn set_contents(&mut self, content: String) -> ClipResult<()> {
+        let res = match &mut self.backend {
+            Backend::Wayland => wl_copy(&content),
+            Backend::X11(ctx) => ctx.set_contents(content.clone()),
+            Backend::Osc52 => osc52_copy(&content),
+            Backend::Memory => Ok(()),
+        };
+        self.buffer = Some(content);
+        res
+    }
+
+    pub fn get_contents(&mut self) -> ClipResult<String> {
+        let res = match &mut self.backend {
+            Backend::Wayland => wl_paste(),
+            Backend::X11(ctx) => ctx.get_contents(),
+            Backend::Osc52 | Backend::Memory => Err("no system clipboard to paste from".into()),
+        };
+        match (res, &self.buffer) {
+            (Ok(s), _) => Ok(s),
+            (Err(_), Some(b)) => Ok(b.clone()),
+            (Err(e), None) => Err(e),
+        }
+    }
+}
+
+fn wl_copy(content: &str) -> ClipResult<()> {
+    let mut child = Command::new("wl-copy")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(stdin) = child.stdin.as_mut() {
+        stdin.write_all(content.as_bytes())?;
+    }
+    // wl-copy forks to serve the selection, so this returns
+    // as soon as the content has been handed over.
+    child.wait()?;
+    Ok(())
+}
+
+fn wl_paste() -> ClipResult<String> {
+    let out = Command::new("wl-paste")
+        .arg("--no-newline")
+        .stderr(Stdio::null())
+        .output()?;
+    if !out.status.success() {
+        return Err("wl-paste failed".into());
+    }
+    Ok(String::from_utf8(out.stdout)?)
+}
+
+fn osc52_copy(content: &str) -> ClipResult<()> {
+    let seq = format!("\x1b]52;c;{}\x07", STANDARD.encode(content));
+    // tmux only forwards sequences wrapped in its passthrough.
+    let seq = if has_var("TMUX") {
+        format!("\x1bPtmux;{}\x1b\\", seq.replace('\x1b', "\x1b\x1b"))
+    } else {
+        seq
+    };
+    let mut out = io::stdout();
+    out.write_all(seq.as_bytes())?;
+    out.flush()?;
+    Ok(())
+}
@@ -0,0 +1,471 @@
+use crate::{
+    bulk::{self, Pacer},
+    cli::{Command, DefinitionsAction, ListAction, OutputFormat},
+    client::Ackmode,
+    config::AppConfig,
+    definitions::{self, Change, Status},
+    models::{Effect, ExchangeInfo, MQMessageGetBody, PayloadPost, QueueInfo},
+    routing::{self, Delivery, Message},
+    safety::Action,
+    topology, Column, ManagementClient, Rowable,
+};
+
+use std::{
+    error::Error,
+    fs,
+    io::{self, BufRead, Read},
+    path::Path,
+};
+
+use reqwest::StatusCode;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+type CommandResult = Result<(), Box<dyn Error>>;
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn headers<T: Rowable>(columns: &[Column]) -> Vec<&'static str> {
+    columns
+        .iter()
+        .map(|c| T::columns()[c.field].title)
+        .collect()
+}
+
+/// Prints rows in the requested format. Tables and CSV are
+/// built from `headers` and `rows`, while JSON serializes
+/// `items` so that scripts get typed values.
+fn print_rows<S: Serialize + ?Sized>(
+    format: OutputFormat,
+    headers: &[&str],
+    rows: Vec<Vec<String>>,
+    items: &S,
+) -> CommandResult {
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(items)?);
+        }
+        OutputFormat::Csv => {
+            let line = |cells: Vec<String>| {
+                cells
+                    .iter()
+                    .map(|c| csv_field(c))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            };
+            println!("{}", line(headers.iter().map(|h| h.to_string()).collect()));
+            for r in rows {
+                println!("{}", line(r));
+            }
+        }
+        OutputFormat::Table => {
+            let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+            for r in &rows {
+                for (w, c) in widths.iter_mut().zip(r) {
+                    *w = (*w).max(c.chars().count());
+                }
+            }
+            let line = |cells: Vec<String>| {
+                cells
+                    .iter()
+                    .zip(&widths)
+                    .map(|(c, w)| format!("{:<w$}", c, w = w))
+                    .collect::<Vec<_>>()
+                    .join("  ")
+                    .trim_end()
+                    .to_string()
+            };
+            println!("{}", line(headers.iter().map(|h| h.to_string()).collect()));
+            for r in rows {
+                println!("{}", line(r));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn publish<M: ManagementClient>(
+    client: &M,
+    format: OutputFormat,
+    config: &AppConfig,
+    vhost: &str,
+    exchange: &str,
+    messages: Vec<PayloadPost>,
+) -> CommandResult {
+    let mut pacer = Pacer::new(config.publish_rate);
+    let (mut routed, mut unroutable) = (0, 0);
+    for m in &messages {
+        // Dry runs send nothing, so there is nothing to pace.
+        if !config.dry_run {
+            pacer.wait();
+        }
+        match client.publish(vhost, exchange, m) {
+            Ok(r) if r.effect == Effect::DryRun => {}
+            Ok(r) if r.routed => routed += 1,
+            Ok(_) => unroutable += 1,
+            Err(e) => {
+                return Err(format!(
+                    "publish failed after {} messages: {}",
+                    routed + unroutable,
+                    e
+                )
+                .into());
+            }
+        }
+    }
+    if config.dry_run {
+        eprintln!("Dry run: would publish {} messages", messages.len());
+        return Ok(());
+    }
+    print_rows(
+        format,
+        &["Published", "Routed", "Unroutable"],
+        vec![vec![
+            messages.len().to_string(),
+            routed.to_string(),
+            unroutable.to_string(),
+        ]],
+        &json!({
+            "published": messages.len(),
+            "routed": routed,
+            "unroutable": unroutable,
+        }),
+    )
+}
+
+fn export<M: ManagementClient>(
+    client: &M,
+    vhost: Option<&str>,
+    file: Option<&Path>,
+) -> CommandResult {
+    let defs = client.get_definitions(vhost)?;
+    match file {
+        Some(f) => {
+            definitions::write_file(f, &defs).map_err(|e| format!("{}: {}", f.display(), e))?;
+            eprintln!("Exported to {}", f.display());
+        }
+        None => println!("{}", serde_json::to_string_pretty(&defs)?),
+    }
+    Ok(())
+}
+
+/// Shows what the definitions in `file` would create
+/// or find different, and imports them once confirmed.
+fn import<M: ManagementClient>(
+    client: &M,
+    format: OutputFormat,
+    config: &AppConfig,
+    file: &Path,
+    vhost: Option<&str>,
+    (yes, confirm): (bool, Option<&str>),
+) -> CommandResult {
+    config.safety.check(Action::Import)?;
+    let contents = fs::read_to_string(file).map_err(|e| format!("{}: {}", file.display(), e))?;
+    let defs: Value =
+        serde_json::from_str(&contents).map_err(|e| format!("{}: {}", file.display(), e))?;
+    // What is posted is what the preview shows.
+    let defs = definitions::scoped(&defs, vhost);
+    let current = client.get_definitions(vhost)?;
+    let (changes, unchanged) = definitions::import_preview(&current, &defs, vhost);
+    let columns = Change::default_columns();
+    let rows = changes.iter().map(|c| c.to_row(&columns)).collect();
+    print_rows(format, &headers::<Change>(&columns), rows, &changes)?;
+    eprintln!(
+        "{} to create or differing, {} already there",
+        changes.len(),
+        unchanged
+    );
+    if changes.is_empty() {
+        return Ok(());
+    }
+    // Definitions hold no messages, only
+    // production profiles ask for the name.
+    let typed = config.safety.needs_typed_confirmation(0);
+    if typed || !yes {
+        let cluster = client.get_overview()?.cluster_name;
+        let scope = vhost.unwrap_or(&cluster);
+        let prompt = format!("Import {} into {}?", file.display(), scope);
+        ask("import", &prompt, scope, typed, (yes, confirm))?;
+    }
+    match client.post_definitions(vhost, &defs)? {
+        Effect::DryRun => eprintln!("Dry run: would import {}", file.display()),
+        Effect::Applied => eprintln!("Imported {}", file.display()),
+    }
+    Ok(())
+}
+
+fn route<M: ManagementClient>(
+    client: &M,
+    format: OutputFormat,
+    vhost: &str,
+    exchange: &str,
+    message: &Message,
+) -> CommandResult {
+    let exchanges = client.get_exchange_overview()?;
+    let bindings = client.get_bindings()?;
+    let policies = client.get_policies()?;
+    let outcome = routing::route(vhost, exchange, message, &exchanges, &bindings, &policies);
+    let columns = Delivery::default_columns();
+    let rows = outcome
+        .deliveries
+        .iter()
+        .map(|d| d.to_row(&columns))
+        .collect();
+    print_rows(
+        format,
+        &headers::<Delivery>(&columns),
+        rows,
+        &outcome.deliveries,
+    )?;
+    for u in &outcome.unsupported {
+        eprintln!("Not followed past {}: {}", u.exchange, u.reason);
+    }
+    if outcome.deliveries.is_empty() {
+        eprintln!("Unroutable, the message would be dropped");
+    }
+    Ok(())
+}
+
+fn diff<M: ManagementClient>(
+    client: &M,
+    format: OutputFormat,
+    config: &AppConfig,
+    against: &str,
+    vhost: Option<&str>,
+) -> CommandResult {
+    let reference = match config.profiles.get(against) {
+        Some(profile) => topology::of_profile(profile)?,
+        None if Path::new(against).is_file() => topology::of_file(Path::new(against))?,
+        None => return Err(format!("{} is neither a profile nor a file", against).into()),
+    };
+    let changes = topology::compare(&reference, &topology::fetch(client)?, vhost);
+    let columns = Change::default_columns();
+    let rows = changes.iter().map(|c| c.to_row(&columns)).collect();
+    print_rows(format, &headers::<Change>(&columns), rows, &changes)?;
+    let count = |s| changes.iter().filter(|c| c.status == s).count();
+    eprintln!(
+        "{} added, {} removed, {} changed compared with {}",
+        count(Status::Added),
+        count(Status::Removed),
+        count(Status::Changed),
+        against
+    );
+    Ok(())
+}
+
+/// Asks for confirmation on the terminal. Anything
+/// but an explicit yes is a no.
+fn confirm(prompt: &str) -> io::Result<bool> {
+    if !termion::is_tty(&io::stdin()) {
+        return Ok(false);
+    }
+    eprint!("{} [y/N] ", prompt);
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+/// Asks for `name` to be typed out on the terminal,
+/// never confirming when stdin is not one.
+fn confirm_typed(prompt: &str, name: &str) -> io::Result<bool> {
+    if !termion::is_tty(&io::stdin()) {
+        return Ok(false);
+    }
+    eprint!("{} Type {} to confirm: ", prompt, name);
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(answer.trim_end_matches(['\r', '\n']) == name)
+}
+
+/// Asks for confirmation of `prompt`, with `name` typed out
+/// when `typed`. `--yes` skips the question unless the name
+/// has to be typed, which `--confirm` with the name does instead.
+fn ask(
+    what: &str,
+    prompt: &str,
+    name: &str,
+    typed: bool,
+    (yes, confirm_name): (bool, Option<&str>),
+) -> CommandResult {
+    let confirmed = match confirm_name {
+        Some(n) => n == name,
+        None if typed => confirm_typed(prompt, name)?,
+        None => yes || confirm(prompt)?,
+    };
+    match (confirmed, typed) {
+        (true, _) => Ok(()),
+        (false, true) => Err(format!(
+            "{} not confirmed, type {} or pass it with --confirm",
+            what, name
+        )
+        .into()),
+        (false, false) => {
+            Err(format!("{} not confirmed, pass --yes to skip the prompt", what).into())
+        }
+    }
+}
+
+/// Runs a headless command against the API, printing
+/// its result on stdout.
+pub fn run<M: ManagementClient>(
+    client: &M,
+    cmd: Command,
+    format: OutputFormat,
+    config: &AppConfig,
+) -> CommandResult {
+    match cmd {
+        Command::Queues {
+            action: ListAction::List,
+        } => {
+            let queues = client.get_queues_info()?;
+            // Trends need depths seen over time,
+            // which a single listing does not have.
+            let columns: &Vec<_> = &config
+                .queue_columns
+                .iter()
+                .filter(|c| QueueInfo::columns()[c.field].key != "trend")
+                .cloned()
+                .collect();
+            let rows = queues.iter().map(|q| q.to_row(columns)).collect();
+            print_rows(format, &headers::<QueueInfo>(columns), rows, &queues)
+        }
+        Command::Exchanges {
+            action: ListAction::List,
+        } => {
+            let exchanges = client.get_exchange_overview()?;
+            let columns = &config.exchange_columns;
+            let rows = exchanges.iter().map(|e| e.to_row(columns)).collect();
+            print_rows(format, &headers::<ExchangeInfo>(columns), rows, &exchanges)
+        }
+        Command::Publish {
+            vhost,
+            exchange,
+            routing_key,
+            file,
+            payload,
+        } => {
+            config.safety.check(Action::Publish)?;
+            let messages = match (file, payload) {
+                (Some(f), _) => bulk::load_messages(&f, &routing_key)?,
+                (None, p) => {
+                    let payload = match p {
+                        Some(p) => p,
+                        None => {
+                            let mut s = String::new();
+                            io::stdin().read_to_string(&mut s)?;
+                            s
+                        }
+                    };
+                    vec![PayloadPost::default()
+                        .routing_key(routing_key)
+                        .payload(payload)]
+                }
+            };
+            publish(client, format, config, &vhost, &exchange, messages)
+        }
+        Command::Route {
+            vhost,
+            exchange,
+            routing_key,
+            headers,
+        } => {
+            let mut message = Message {
+                routing_key,
+                headers: Default::default(),
+            };
+            for h in &headers {
+                message.headers.extend(routing::parse_headers(h)?);
+            }
+            route(client, format, &vhost, &exchange, &message)
+        }
+        Command::Get {
+            queue,
+            vhost,
+            count,
+            ack,
+        } => {
+            let ackmode = if ack {
+                config.safety.check(Action::Consume)?;
+                Ackmode::AckRequeueFalse
+            } else {
+                Ackmode::AckRequeueTrue
+            };
+            let body = MQMessageGetBody::default().count(count).ackmode(ackmode);
+            let messages = client.get_messages(&queue, &vhost, &body)?;
+            let rows = messages
+                .iter()
+                .map(|m| {
+                    vec![
+                        m.exchange.clone(),
+                        m.routing_key.clone(),
+                        m.redelivered.to_string(),
+                        m.payload_bytes.to_string(),
+                        m.payload.clone(),
+                    ]
+                })
+                .collect();
+            print_rows(
+                format,
+                &["Exchange", "Routing key", "Redelivered", "Bytes", "Payload"],
+                rows,
+                &messages,
+            )
+        }
+        Command::Purge {
+            queue,
+            vhost,
+            yes,
+            confirm,
+        } => {
+            config.safety.check(Action::Purge)?;
+            let ready = match client.get_queue(&queue, &vhost) {
+                Ok(info) => info.ready,
+                Err(e) if e.status() == Some(StatusCode::NOT_FOUND) => {
+                    return Err(format!("no queue {} on vhost {}", queue, vhost).into());
+                }
+                Err(e) => return Err(e.into()),
+            };
+            let typed = config.safety.needs_typed_confirmation(ready);
+            if typed || !yes {
+                let cluster = client.get_overview()?.cluster_name;
+                let prompt = format!(
+                    "Purge {} ready messages of {} on vhost {} of cluster {}?",
+                    ready, queue, vhost, cluster
+                );
+                ask("purge", &prompt, &queue, typed, (yes, confirm.as_deref()))?;
+            }
+            match client.purge_queue(&queue, &vhost)? {
+                Effect::DryRun => eprintln!("Dry run: would purge {} on vhost {}", queue, vhost),
+                Effect::Applied => eprintln!("Purged {} on vhost {}", queue, vhost),
+            }
+            Ok(())
+        }
+        Command::Definitions { action } => match action {
+            DefinitionsAction::Export { vhost, file } => {
+                export(client, vhost.as_deref(), file.as_deref())
+            }
+            DefinitionsAction::Import {
+                file,
+                vhost,
+                yes,
+                confirm,
+            } => import(
+                client,
+                format,
+                config,
+                &file,
+                vhost.as_deref(),
+                (yes, confirm.as_deref()),
+            ),
+        },
+        Command::Diff { against, vhost } => {
+            diff(client, format, config, &against, vhost.as_deref())
+        }
+    }
+}
@@ -1,10 +1,245 @@
+use crate::{
+    alerts::{parse_duration, AlertConfig, Rule, Severity},
+    audit,
+    models::{ExchangeInfo, QueueInfo},
+    safety::Safety,
+    Column, Rowable,
+};
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+pub const DEFAULT_ADDR: &str = "http://localhost:15672";
+pub const DEFAULT_USER: &str = "guest";
+pub const DEFAULT_PASS: &str = "guest";
+
 #[derive(Debug, Clone)]
 pub struct AppConfig {
     pub update_rate: u64,
+    /// Messages per second sent by bulk publishes,
+    /// 0 meaning unlimited.
+    pub publish_rate: u32,
+    pub queue_columns: Vec<Column>,
+    pub exchange_columns: Vec<Column>,
+    pub alerts: AlertConfig,
+    /// Where mutating calls are recorded.
+    pub audit_log: Option<PathBuf>,
+    pub profiles: HashMap<String, Profile>,
+    /// Set from the profile and the command line, but
+    /// for the typed confirmation threshold.
+    pub safety: Safety,
+    /// Whether mutating calls are only shown, set
+    /// from the command line.
+    pub dry_run: bool,
+}
+
+/// A named connection, picked with `--profile`:
+/// `[profiles.prod]` with its `addr`, `user`, `pass`
+/// whether it is `read_only` and whether it is a
+/// `production` broker.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    pub addr: Option<String>,
+    pub user: Option<String>,
+    pub pass: Option<String>,
+    pub read_only: bool,
+    pub production: bool,
 }
 
 impl Default for AppConfig {
     fn default() -> Self {
-        Self { update_rate: 2_000 }
+        Self {
+            update_rate: 2_000,
+            publish_rate: 50,
+            queue_columns: QueueInfo::default_columns(),
+            exchange_columns: ExchangeInfo::default_columns(),
+            alerts: AlertConfig::default(),
+            audit_log: audit::default_path(),
+            profiles: HashMap::new(),
+            safety: Safety::default(),
+            dry_run: false,
+        }
+    }
+}
+
+/// The config file as written by the user. Anything
+/// left out keeps its default.
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    update_rate: Option<u64>,
+    publish_rate: Option<u32>,
+    queues: TableFile,
+    exchanges: TableFile,
+    alerts: AlertsFile,
+    audit_log: Option<PathBuf>,
+    profiles: HashMap<String, Profile>,
+    confirm_above: Option<u64>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct TableFile {
+    columns: Option<Vec<ColumnFile>>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct AlertsFile {
+    desktop: bool,
+    rules: Vec<RuleFile>,
+}
+
+/// An alert rule, on the queues matching `queue`
+/// or on the overview when left out:
+/// `{ name = "backlog", queue = "orders.*", when = "ready > 10000", for = "2m" }`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleFile {
+    name: String,
+    queue: Option<String>,
+    vhost: Option<String>,
+    when: String,
+    #[serde(rename = "for")]
+    for_: Option<String>,
+    #[serde(default)]
+    severity: Severity,
+    command: Option<String>,
+}
+
+fn resolve_alerts(file: AlertsFile) -> Result<AlertConfig, String> {
+    // Events refer to their rule by name.
+    for (i, r) in file.rules.iter().enumerate() {
+        if file.rules[..i].iter().any(|o| o.name == r.name) {
+            return Err(format!("rule {:?} is defined twice", r.name));
+        }
+    }
+    let rules = file
+        .rules
+        .into_iter()
+        .map(|r| {
+            let for_secs = r.for_.as_deref().map_or(Ok(0), parse_duration);
+            let rule = for_secs.and_then(|for_secs| {
+                Rule::new(
+                    r.name.clone(),
+                    r.queue.as_deref(),
+                    r.vhost,
+                    &r.when,
+                    for_secs,
+                )
+            });
+            let mut rule = rule.map_err(|e| format!("rule {:?}: {}", r.name, e))?;
+            rule.severity = r.severity;
+            rule.command = r.command;
+            Ok(rule)
+        })
+        .collect::<Result<_, String>>()?;
+    Ok(AlertConfig {
+        rules,
+        desktop: file.desktop,
+    })
+}
+
+/// A column, either by name alone or with a width:
+/// `"ready"` or `{ field = "ready", width = 8 }`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ColumnFile {
+    Name(String),
+    Full { field: String, width: Option<u16> },
+}
+
+fn resolve<T: Rowable>(columns: Option<Vec<ColumnFile>>) -> Result<Vec<Column>, String> {
+    let Some(columns) = columns else {
+        return Ok(T::default_columns());
+    };
+    if columns.is_empty() {
+        return Err("at least one column is needed".to_string());
+    }
+    let columns: Vec<Column> = columns
+        .into_iter()
+        .map(|c| {
+            let (name, width) = match c {
+                ColumnFile::Name(name) => (name, None),
+                ColumnFile::Full { field, width } => (field, width),
+            };
+            let field = T::columns()
+                .iter()
+                .position(|d| d.key == name)
+                .ok_or_else(|| {
+                    let known: Vec<_> = T::columns().iter().map(|d| d.key).collect();
+                    format!(
+                        "unknown column {:?}, expected one of {}",
+                        name,
+                        known.join(", ")
+                    )
+                })?;
+            Ok(Column {
+                field,
+                width: width.unwrap_or(T::columns()[field].width),
+            })
+        })
+        .collect::<Result<_, String>>()?;
+    for (i, c) in columns.iter().enumerate() {
+        if columns[..i].iter().any(|o| o.field == c.field) {
+            return Err(format!(
+                "column {:?} is listed twice",
+                T::columns()[c.field].key
+            ));
+        }
+    }
+    Ok(columns)
+}
+
+/// Default location of the config file,
+/// `rabbitui/config.toml` in the user config dir.
+pub fn default_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|d| d.join("rabbitui").join("config.toml"))
+}
+
+impl AppConfig {
+    /// Loads the config file at `path`, or at the default
+    /// location when none is given. Only an explicit path
+    /// has to exist.
+    pub fn load(path: Option<&Path>) -> Result<Self, Box<dyn Error>> {
+        let path = match path {
+            Some(p) => p.to_path_buf(),
+            None => match default_path() {
+                Some(p) if p.exists() => p,
+                _ => return Ok(Self::default()),
+            },
+        };
+        let contents =
+            fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let file: ConfigFile =
+            toml::from_str(&contents).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::from_file(file).map_err(|e| format!("{}: {}", path.display(), e).into())
+    }
+
+    fn from_file(file: ConfigFile) -> Result<Self, String> {
+        let default = Self::default();
+        Ok(Self {
+            update_rate: file.update_rate.unwrap_or(default.update_rate),
+            publish_rate: file.publish_rate.unwrap_or(default.publish_rate),
+            queue_columns: resolve::<QueueInfo>(file.queues.columns)
+                .map_err(|e| format!("[queues] {}", e))?,
+            exchange_columns: resolve::<ExchangeInfo>(file.exchanges.columns)
+                .map_err(|e| format!("[exchanges] {}", e))?,
+            alerts: resolve_alerts(file.alerts).map_err(|e| format!("[alerts] {}", e))?,
+            audit_log: file.audit_log.or(default.audit_log),
+            profiles: file.profiles,
+            safety: match file.confirm_above {
+                Some(n) => default.safety.confirm_above(n),
+                None => default.safety,
+            },
+            dry_run: false,
+        })
     }
 }
@@ -0,0 +1,580 @@
+use crate::{models::column, ColumnDef, Identifiable, Rowable, SortValue, Sortable};
+
+use std::{
+    collections::BTreeMap,
+    fs::{OpenOptions, Permissions},
+    io::{self, Write},
+    os::unix::fs::{OpenOptionsExt, PermissionsExt},
+    path::Path,
+};
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// Kinds of objects of a definitions file, in the
+/// order they depend on each other.
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    Vhost,
+    User,
+    Permission,
+    Policy,
+    Exchange,
+    Queue,
+    Binding,
+}
+
+impl Kind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Vhost => "vhost",
+            Kind::User => "user",
+            Kind::Permission => "permission",
+            Kind::Policy => "policy",
+            Kind::Exchange => "exchange",
+            Kind::Queue => "queue",
+            Kind::Binding => "binding",
+        }
+    }
+
+    /// Whether objects of this kind live in a vhost,
+    /// and so are part of a vhost export.
+    pub fn in_vhost(self) -> bool {
+        matches!(
+            self,
+            Kind::Policy | Kind::Exchange | Kind::Queue | Kind::Binding
+        )
+    }
+}
+
+const SECTIONS: [(&str, Kind); 7] = [
+    ("vhosts", Kind::Vhost),
+    ("users", Kind::User),
+    ("permissions", Kind::Permission),
+    ("policies", Kind::Policy),
+    ("exchanges", Kind::Exchange),
+    ("queues", Kind::Queue),
+    ("bindings", Kind::Binding),
+];
+
+/// Fields left out of items, never to be shown.
+const SECRET_FIELDS: [&str; 1] = ["password_hash"];
+
+/// An object of a definitions file, identified by its
+/// kind, vhost and name. Its other fields are flattened,
+/// nested ones being keyed by their dotted path such as
+/// `arguments.x-max-length`.
+#[derive(Clone, Debug)]
+pub struct Item {
+    pub kind: Kind,
+    pub vhost: String,
+    pub name: String,
+    pub fields: BTreeMap<String, Value>,
+}
+
+impl Item {
+    fn key(&self) -> (Kind, String, String) {
+        (self.kind, self.vhost.clone(), self.name.clone())
+    }
+}
+
+fn flatten(prefix: &str, value: &Value, out: &mut BTreeMap<String, Value>) {
+    match value {
+        Value::Object(o) if !o.is_empty() => {
+            for (k, v) in o {
+                let path = match prefix {
+                    "" => k.clone(),
+                    p => format!("{}.{}", p, k),
+                };
+                flatten(&path, v, out);
+            }
+        }
+        v => {
+            out.insert(prefix.to_string(), v.clone());
+        }
+    }
+}
+
+fn text(obj: &Value, field: &str) -> String {
+    obj.get(field)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Objects of definitions exported from `vhost`, whose
+/// objects carry no vhost of their own, or from the
+/// whole broker.
+pub fn items(defs: &Value, vhost: Option<&str>) -> Vec<Item> {
+    let mut items = Vec::new();
+    for (section, kind) in SECTIONS {
+        let Some(objects) = defs.get(section).and_then(Value::as_array) else {
+            continue;
+        };
+        for obj in objects.iter().filter(|o| o.is_object()) {
+            let mut identity = vec!["name", "vhost"];
+            let in_vhost = match obj.get("vhost").and_then(Value::as_str) {
+                Some(v) => v.to_string(),
+                None => vhost.unwrap_or_default().to_string(),
+            };
+            let (vhost, name) = match kind {
+                Kind::Vhost => (text(obj, "name"), text(obj, "name")),
+                Kind::User => (String::new(), text(obj, "name")),
+                Kind::Permission => {
+                    identity.push("user");
+                    (in_vhost, text(obj, "user"))
+                }
+                Kind::Binding => {
+                    // Bindings differing only by their arguments,
+                    // as those of headers exchanges, are distinct.
+                    identity.extend([
+                        "source",
+                        "destination",
+                        "destination_type",
+                        "routing_key",
+                        "arguments",
+                    ]);
+                    let source = match text(obj, "source") {
+                        s if s.is_empty() => "(default)".to_string(),
+                        s => s,
+                    };
+                    let mut name = format!(
+                        "{} -> {} {} [{}]",
+                        source,
+                        text(obj, "destination_type"),
+                        text(obj, "destination"),
+                        text(obj, "routing_key")
+                    );
+                    // Keys come out sorted, so equal arguments
+                    // always read the same.
+                    match obj.get("arguments") {
+                        Some(Value::Object(a)) if a.is_empty() => {}
+                        Some(Value::Null) | None => {}
+                        Some(a) => {
+                            name.push(' ');
+                            name.push_str(&a.to_string());
+                        }
+                    }
+                    (in_vhost, name)
+                }
+                _ => (in_vhost, text(obj, "name")),
+            };
+            let mut fields = BTreeMap::new();
+            for (k, v) in obj.as_object().into_iter().flatten() {
+                if !identity.contains(&k.as_str()) && !SECRET_FIELDS.contains(&k.as_str()) {
+                    flatten(k, v, &mut fields);
+                }
+            }
+            items.push(Item {
+                kind,
+                vhost,
+                name,
+                fields,
+            });
+        }
+    }
+    items
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Added,
+    Removed,
+    Changed,
+}
+
+impl Status {
+    pub fn name(self) -> &'static str {
+        match self {
+            Status::Added => "added",
+            Status::Removed => "removed",
+            Status::Changed => "changed",
+        }
+    }
+}
+
+/// A field with different values on both sides,
+/// `None` where it is missing.
+#[derive(Serialize, Clone, Debug)]
+pub struct FieldChange {
+    pub field: String,
+    pub from: Option<Value>,
+    pub to: Option<Value>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Change {
+    pub status: Status,
+    pub kind: Kind,
+    pub vhost: String,
+    pub name: String,
+    /// Differing fields of a changed object.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldChange>,
+}
+
+/// What changes between `from` and `to`, object by object.
+/// Objects that are the same on both sides are left out.
+pub fn diff(from: &[Item], to: &[Item]) -> Vec<Change> {
+    let from: BTreeMap<_, _> = from.iter().map(|i| (i.key(), i)).collect();
+    let to: BTreeMap<_, _> = to.iter().map(|i| (i.key(), i)).collect();
+    let mut keys: Vec<_> = from.keys().chain(to.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    let mut changes = Vec::new();
+    for key in keys {
+        let (status, fields) = match (from.get(key), to.get(key)) {
+            (None, Some(_)) => (Status::Added, Vec::new()),
+            (Some(_), None) => (Status::Removed, Vec::new()),
+            (Some(a), Some(b)) => {
+                let mut names: Vec<_> = a.fields.keys().chain(b.fields.keys()).collect();
+                names.sort();
+                names.dedup();
+                let fields: Vec<_> = names
+                    .into_iter()
+                    .filter(|n| a.fields.get(*n) != b.fields.get(*n))
+                    .map(|n| FieldChange {
+                        field: n.clone(),
+                        from: a.fields.get(n).cloned(),
+                        to: b.fields.get(n).cloned(),
+                    })
+                    .collect();
+                if fields.is_empty() {
+                    continue;
+                }
+                (Status::Changed, fields)
+            }
+            (None, None) => continue,
+        };
+        let (kind, vhost, name) = key.clone();
+        changes.push(Change {
+            status,
+            kind,
+            vhost,
+            name,
+            fields,
+        });
+    }
+    changes
+}
+
+/// What of `defs` an import into `vhost` takes: the objects
+/// living in a vhost, but those of other vhosts. The objects
+/// of a vhost export carry no vhost and are all taken. An
+/// import into the whole broker takes everything.
+pub fn scoped(defs: &Value, vhost: Option<&str>) -> Value {
+    let Some(v) = vhost else {
+        return defs.clone();
+    };
+    let mut out = Map::new();
+    for (section, kind) in SECTIONS {
+        let Some(objects) = defs.get(section).and_then(Value::as_array) else {
+            continue;
+        };
+        if !kind.in_vhost() {
+            continue;
+        }
+        let taken = objects
+            .iter()
+            .filter(|o| {
+                o.is_object()
+                    && o.get("vhost")
+                        .and_then(Value::as_str)
+                        .is_none_or(|ov| ov == v)
+            })
+            .cloned()
+            .collect();
+        out.insert(section.to_string(), Value::Array(taken));
+    }
+    Value::Object(out)
+}
+
+/// What importing `incoming` into `vhost`, or into the
+/// whole broker, would create or find different from
+/// `current`, along with the number of objects already
+/// there as they are. Only the `scoped` part of `incoming`
+/// counts, which is what has to be posted. Imports never
+/// delete anything.
+pub fn import_preview(
+    current: &Value,
+    incoming: &Value,
+    vhost: Option<&str>,
+) -> (Vec<Change>, usize) {
+    let current = items(current, vhost);
+    let incoming = items(&scoped(incoming, vhost), vhost);
+    let changes: Vec<_> = diff(&current, &incoming)
+        .into_iter()
+        .filter(|c| c.status != Status::Removed)
+        .collect();
+    let unchanged = incoming.len() - changes.len();
+    (changes, unchanged)
+}
+
+/// Writes `defs` to `path`, readable by its owner
+/// only as they may hold password hashes.
+pub fn write_file(path: &Path, defs: &Value) -> io::Result<()> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    // The mode only applies to new files.
+    file.set_permissions(Permissions::from_mode(0o600))?;
+    file.write_all(serde_json::to_string_pretty(defs)?.as_bytes())
+}
+
+fn show(value: &Option<Value>) -> String {
+    match value {
+        None => "-".to_string(),
+        Some(Value::String(s)) => s.clone(),
+        Some(v) => v.to_string(),
+    }
+}
+
+impl FieldChange {
+    pub fn describe(&self) -> String {
+        format!("{}: {} -> {}", self.field, show(&self.from), show(&self.to))
+    }
+}
+
+const CHANGE_COLUMNS: [ColumnDef; 5] = [
+    column("status", "Change", 8, true),
+    column("kind", "Kind", 10, true),
+    column("vhost", "Vhost", 10, true),
+    column("name", "Name", 32, true),
+    column("fields", "Fields", 40, true),
+];
+
+impl Rowable for Change {
+    fn columns() -> &'static [ColumnDef] {
+        &CHANGE_COLUMNS
+    }
+
+    fn cell(&self, field: usize) -> String {
+        match CHANGE_COLUMNS[field].key {
+            "status" => self.status.name().to_string(),
+            "kind" => self.kind.name().to_string(),
+            "vhost" => self.vhost.clone(),
+            "name" => self.name.clone(),
+            "fields" => self
+                .fields
+                .iter()
+                .map(FieldChange::describe)
+                .collect::<Vec<_>>()
+                .join("; "),
+            _ => String::new(),
+        }
+    }
+}
+
+impl Sortable for Change {
+    fn sort_value(&self, field: usize) -> SortValue {
+        match CHANGE_COLUMNS[field].key {
+            "status" => SortValue::Number(self.status as u8 as f64),
+            "kind" => SortValue::Number(self.kind as u8 as f64),
+            _ => SortValue::Text(self.cell(field).to_lowercase()),
+        }
+    }
+}
+
+impl Identifiable for Change {
+    type Id = (Kind, String, String);
+
+    fn id(&self) -> Self::Id {
+        (self.kind, self.vhost.clone(), self.name.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{import_preview, items, scoped, write_file, Change, Kind, Status};
+
+    use std::{env, fs, os::unix::fs::PermissionsExt, process};
+
+    use serde_json::{json, Value};
+
+    fn queue(vhost: Option<&str>, name: &str, max_length: u64) -> Value {
+        let mut q = json!({
+            "name": name,
+            "durable": true,
+            "auto_delete": false,
+            "arguments": {"x-max-length": max_length},
+        });
+        if let Some(v) = vhost {
+            q["vhost"] = json!(v);
+        }
+        q
+    }
+
+    fn binding(vhost: &str, key: &str, arguments: Value) -> Value {
+        json!({
+            "source": "events",
+            "vhost": vhost,
+            "destination": "orders",
+            "destination_type": "queue",
+            "routing_key": key,
+            "arguments": arguments,
+        })
+    }
+
+    /// A whole broker export, with a vhost, a user and
+    /// queues in two vhosts.
+    fn broker() -> Value {
+        json!({
+            "vhosts": [{"name": "/"}, {"name": "prod"}],
+            "users": [{"name": "ops", "password_hash": "abc", "tags": ["administrator"]}],
+            "permissions": [{"user": "ops", "vhost": "/", "configure": ".*"}],
+            "queues": [queue(Some("/"), "orders", 1000), queue(Some("prod"), "billing", 10)],
+        })
+    }
+
+    fn summary(changes: &[Change]) -> Vec<(Status, Kind, &str, &str)> {
+        changes
+            .iter()
+            .map(|c| (c.status, c.kind, c.vhost.as_str(), c.name.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn objects_of_vhost_exports_take_the_given_vhost() {
+        let defs = json!({"queues": [queue(None, "orders", 1000)]});
+        let items = items(&defs, Some("staging"));
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].vhost, "staging");
+        assert_eq!(items[0].name, "orders");
+        assert_eq!(items[0].fields["arguments.x-max-length"], 1000);
+        assert!(!items[0].fields.contains_key("name"));
+    }
+
+    #[test]
+    fn objects_of_broker_exports_keep_their_vhost() {
+        let items = items(&broker(), Some("staging"));
+        let queues: Vec<_> = items
+            .iter()
+            .filter(|i| i.kind == Kind::Queue)
+            .map(|i| (i.vhost.as_str(), i.name.as_str()))
+            .collect();
+        assert_eq!(queues, [("/", "orders"), ("prod", "billing")]);
+    }
+
+    #[test]
+    fn scoping_to_a_vhost_keeps_its_objects_only() {
+        let defs = scoped(&broker(), Some("/"));
+        assert_eq!(defs, json!({"queues": [queue(Some("/"), "orders", 1000)]}));
+        assert_eq!(scoped(&broker(), None), broker());
+    }
+
+    #[test]
+    fn scoping_a_vhost_export_keeps_everything_in_it() {
+        let defs = json!({
+            "queues": [queue(None, "orders", 1000)],
+            "bindings": [{"source": "events", "destination": "orders", "destination_type": "queue", "routing_key": "#"}],
+            "policies": [{"name": "ha", "pattern": ".*", "definition": {}}],
+        });
+        assert_eq!(scoped(&defs, Some("staging")), defs);
+    }
+
+    #[test]
+    fn vhost_and_broker_exports_preview_the_same_into_a_vhost() {
+        let current = json!({"queues": [queue(None, "orders", 1000)]});
+        let vhost_export = json!({"queues": [queue(None, "orders", 2000)]});
+        let (from_vhost, unchanged) = import_preview(&current, &vhost_export, Some("/"));
+        assert_eq!(unchanged, 0);
+        let mut broker = broker();
+        broker["queues"][0] = queue(Some("/"), "orders", 2000);
+        let (from_broker, unchanged) = import_preview(&current, &broker, Some("/"));
+        assert_eq!(unchanged, 0);
+        for changes in [&from_vhost, &from_broker] {
+            assert_eq!(
+                summary(changes),
+                [(Status::Changed, Kind::Queue, "/", "orders")]
+            );
+            assert_eq!(
+                changes[0].fields[0].describe(),
+                "arguments.x-max-length: 1000 -> 2000"
+            );
+        }
+    }
+
+    #[test]
+    fn password_hashes_are_never_compared_nor_shown() {
+        let current = broker();
+        let mut incoming = broker();
+        incoming["users"][0]["password_hash"] = json!("xyz");
+        let (changes, unchanged) = import_preview(&current, &incoming, None);
+        assert!(changes.is_empty());
+        assert_eq!(unchanged, 6);
+        assert!(items(&current, None)
+            .iter()
+            .all(|i| !i.fields.contains_key("password_hash")));
+        incoming["users"][0]["tags"] = json!([]);
+        let (changes, _) = import_preview(&current, &incoming, None);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].fields[0].field, "tags");
+    }
+
+    #[test]
+    fn bindings_are_keyed_by_their_arguments() {
+        let any = json!({"x-match": "any", "region": "eu"});
+        let all = json!({"region": "eu", "x-match": "all"});
+        let current = json!({"bindings": [binding("/", "", any.clone())]});
+        let incoming = json!({"bindings": [binding("/", "", any), binding("/", "", all)]});
+        let (changes, unchanged) = import_preview(&current, &incoming, Some("/"));
+        assert_eq!(unchanged, 1);
+        assert_eq!(
+            summary(&changes),
+            [(
+                Status::Added,
+                Kind::Binding,
+                "/",
+                r#"events -> queue orders [] {"region":"eu","x-match":"all"}"#
+            )]
+        );
+        // No arguments, whether empty or missing, add nothing to the name.
+        let bare = items(&json!({"bindings": [binding("/", "k", json!({}))]}), None);
+        assert_eq!(bare[0].name, "events -> queue orders [k]");
+        assert!(bare[0].fields.is_empty());
+    }
+
+    #[test]
+    fn previews_count_what_is_already_there() {
+        let current = json!({"queues": [
+            queue(None, "same", 1),
+            queue(None, "changed", 1),
+            queue(None, "only_current", 1),
+        ]});
+        let incoming = json!({"queues": [
+            queue(None, "same", 1),
+            queue(None, "changed", 2),
+            queue(None, "new", 1),
+        ]});
+        let (changes, unchanged) = import_preview(&current, &incoming, Some("/"));
+        assert_eq!(unchanged, 1);
+        // Imports never delete, so nothing is removed.
+        assert_eq!(
+            summary(&changes),
+            [
+                (Status::Changed, Kind::Queue, "/", "changed"),
+                (Status::Added, Kind::Queue, "/", "new"),
+            ]
+        );
+    }
+
+    #[test]
+    fn exports_are_readable_by_their_owner_only() {
+        let dir = env::temp_dir().join(format!("rabbitui-definitions-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("definitions.json");
+        fs::write(&path, "old").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        write_file(&path, &broker()).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        let written: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(written, broker());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,132 @@
+use crate::{
+    client::{definitions_endpoint, get_endpoint, publish_endpoint, purge_endpoint, ApiError},
+    models::{
+        Effect, ExchangeBindings, ExchangeInfo, HistoryWindow, MQMessage, MQMessageGetBody,
+        Overview, PayloadPost, Policy, PublishResponse, QueueInfo,
+    },
+    widgets::notif::Notifier,
+    ManagementClient,
+};
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// Wraps a client so that calls changing the broker
+/// state are only shown as the request they would send.
+/// Reads still go to the broker.
+pub struct DryRunClient<M: ManagementClient> {
+    inner: M,
+    addr: String,
+    notifier: Notifier,
+}
+
+impl<M: ManagementClient> DryRunClient<M> {
+    pub fn new(inner: M, addr: &str, notifier: Notifier) -> Self {
+        Self {
+            inner,
+            addr: addr.to_string(),
+            notifier,
+        }
+    }
+
+    fn show<S: Serialize>(&self, method: &str, endpoint: &str, body: Option<&S>) {
+        let mut request = format!("{} {}{}", method, self.addr, endpoint);
+        if let Some(json) = body.and_then(|b| serde_json::to_string(b).ok()) {
+            request.push(' ');
+            request.push_str(&json);
+        }
+        self.notifier.info(format!("Dry run: {}", request));
+    }
+}
+
+impl<M: ManagementClient> ManagementClient for DryRunClient<M> {
+    fn get_exchange_overview(&self) -> Result<Vec<ExchangeInfo>, ApiError> {
+        self.inner.get_exchange_overview()
+    }
+
+    fn get_exchange_bindings(
+        &self,
+        exch: &ExchangeInfo,
+    ) -> Result<Vec<ExchangeBindings>, ApiError> {
+        self.inner.get_exchange_bindings(exch)
+    }
+
+    fn get_overview(&self) -> Result<Overview, ApiError> {
+        self.inner.get_overview()
+    }
+
+    fn get_overview_history(&self, window: HistoryWindow) -> Result<Overview, ApiError> {
+        self.inner.get_overview_history(window)
+    }
+
+    fn get_queues_info(&self) -> Result<Vec<QueueInfo>, ApiError> {
+        self.inner.get_queues_info()
+    }
+
+    fn get_queue(&self, queue_name: &str, vhost: &str) -> Result<QueueInfo, ApiError> {
+        self.inner.get_queue(queue_name, vhost)
+    }
+
+    fn get_queue_history(
+        &self,
+        queue_name: &str,
+        vhost: &str,
+        window: HistoryWindow,
+    ) -> Result<QueueInfo, ApiError> {
+        self.inner.get_queue_history(queue_name, vhost, window)
+    }
+
+    fn publish(
+        &self,
+        vhost: &str,
+        exchange: &str,
+        body: &PayloadPost,
+    ) -> Result<PublishResponse, ApiError> {
+        self.show("POST", &publish_endpoint(vhost, exchange), Some(body));
+        // Nothing was sent, so nothing was routed.
+        Ok(PublishResponse {
+            routed: false,
+            effect: Effect::DryRun,
+        })
+    }
+
+    fn get_messages(
+        &self,
+        queue_name: &str,
+        vhost: &str,
+        body: &MQMessageGetBody,
+    ) -> Result<Vec<MQMessage>, ApiError> {
+        // Messages left in the queue are only read.
+        if body.requeues() {
+            return self.inner.get_messages(queue_name, vhost, body);
+        }
+        self.show("POST", &get_endpoint(queue_name, vhost), Some(body));
+        Ok(Vec::new())
+    }
+
+    fn ping(&self) -> Result<(), ApiError> {
+        self.inner.ping()
+    }
+
+    fn purge_queue(&self, queue_name: &str, vhost: &str) -> Result<Effect, ApiError> {
+        self.show::<()>("DELETE", &purge_endpoint(queue_name, vhost), None);
+        Ok(Effect::DryRun)
+    }
+
+    fn get_bindings(&self) -> Result<Vec<ExchangeBindings>, ApiError> {
+        self.inner.get_bindings()
+    }
+
+    fn get_policies(&self) -> Result<Vec<Policy>, ApiError> {
+        self.inner.get_policies()
+    }
+
+    fn get_definitions(&self, vhost: Option<&str>) -> Result<Value, ApiError> {
+        self.inner.get_definitions(vhost)
+    }
+
+    fn post_definitions(&self, vhost: Option<&str>, defs: &Value) -> Result<Effect, ApiError> {
+        self.show("POST", &definitions_endpoint(vhost), Some(defs));
+        Ok(Effect::DryRun)
+    }
+}
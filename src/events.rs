@@ -2,7 +2,8 @@
 // Taken from
 // https://github.com/fdehau/tui-rs/blob/master/examples/util/event.rs
 use std::{
-    io,
+    io::Read,
+    os::unix::io::AsRawFd,
     sync::{
         atomic::{AtomicBool, Ordering},
         mpsc, Arc,
@@ -11,22 +12,42 @@ use std::{
     time::Duration,
 };
 
-use termion::{event::Key, input::TermRead};
+use termion::{
+    event::{Event as TermEvent, Key, MouseEvent},
+    input::TermRead,
+};
 
 pub enum Event<I> {
     Input(I),
+    Mouse(MouseEvent),
     Tick,
 }
 
+/// How long the input thread waits for the tty before
+/// checking again whether it has been paused.
+const INPUT_POLL: Duration = Duration::from_millis(50);
+
 /// A small event handler that wrap termion input and tick events. Each event
 /// type is handled in its own thread and returned to a common `Receiver`
 pub struct Events {
     rx: mpsc::Receiver<Event<Key>>,
     input_handle: thread::JoinHandle<()>,
     ignore_exit_key: Arc<AtomicBool>,
+    input_paused: Arc<AtomicBool>,
     tick_handle: thread::JoinHandle<()>,
 }
 
+/// Waits up to `timeout` for `fd` to have bytes to read.
+fn input_ready(fd: i32, timeout: Duration) -> bool {
+    let mut pfd = libc::pollfd {
+        fd,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    // SAFETY: pfd is a single valid pollfd living for the whole call.
+    unsafe { libc::poll(&mut pfd, 1, timeout.as_millis() as i32) > 0 }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct Config {
     pub exit_key: Key,
@@ -50,18 +71,46 @@ impl Events {
     pub fn with_config(config: Config) -> Events {
         let (tx, rx) = mpsc::channel();
         let ignore_exit_key = Arc::new(AtomicBool::new(false));
+        let input_paused = Arc::new(AtomicBool::new(false));
         let input_handle = {
             let tx = tx.clone();
             let ignore_exit_key = ignore_exit_key.clone();
+            let input_paused = input_paused.clone();
             thread::spawn(move || {
-                let stdin = io::stdin();
-                for evt in stdin.keys() {
-                    if let Ok(key) = evt {
-                        if let Err(err) = tx.send(Event::Input(key)) {
+                // The tty is polled rather than read in a blocking
+                // loop, so that a paused thread never steals the
+                // keys meant for an external program.
+                let mut tty = match termion::get_tty() {
+                    Ok(t) => t,
+                    Err(err) => {
+                        eprintln!("{}", err);
+                        return;
+                    }
+                };
+                let mut buf = [0; 64];
+                loop {
+                    if input_paused.load(Ordering::Relaxed) {
+                        thread::sleep(INPUT_POLL);
+                        continue;
+                    }
+                    if !input_ready(tty.as_raw_fd(), INPUT_POLL) {
+                        continue;
+                    }
+                    let n = match tty.read(&mut buf) {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => n,
+                    };
+                    for event in (&buf[..n]).events().flatten() {
+                        let (event, exit) = match event {
+                            TermEvent::Key(key) => (Event::Input(key), key == config.exit_key),
+                            TermEvent::Mouse(m) => (Event::Mouse(m), false),
+                            TermEvent::Unsupported(_) => continue,
+                        };
+                        if let Err(err) = tx.send(event) {
                             eprintln!("{}", err);
                             return;
                         }
-                        if !ignore_exit_key.load(Ordering::Relaxed) && key == config.exit_key {
+                        if exit && !ignore_exit_key.load(Ordering::Relaxed) {
                             return;
                         }
                     }
@@ -79,6 +128,7 @@ impl Events {
         Events {
             rx,
             ignore_exit_key,
+            input_paused,
             input_handle,
             tick_handle,
         }
@@ -95,4 +145,13 @@ impl Events {
     pub fn enable_exit_key(&mut self) {
         self.ignore_exit_key.store(false, Ordering::Relaxed);
     }
+
+    /// Stops reading keys until `resume_input` is called.
+    pub fn pause_input(&self) {
+        self.input_paused.store(true, Ordering::Relaxed);
+    }
+
+    pub fn resume_input(&self) {
+        self.input_paused.store(false, Ordering::Relaxed);
+    }
 }
@@ -0,0 +1,160 @@
+use regex::{Regex, RegexBuilder};
+
+/// How a search query is matched against table cells.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FilterMode {
+    /// Case-insensitive match anywhere in a cell.
+    Substring,
+    /// Case-insensitive shell pattern matching a whole
+    /// cell, `*` for any run of chars and `?` for one.
+    Glob,
+    /// Regular expression matching anywhere in a cell.
+    Regex,
+}
+
+impl FilterMode {
+    pub fn next(self) -> Self {
+        match self {
+            FilterMode::Substring => FilterMode::Glob,
+            FilterMode::Glob => FilterMode::Regex,
+            FilterMode::Regex => FilterMode::Substring,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FilterMode::Substring => "substring",
+            FilterMode::Glob => "glob",
+            FilterMode::Regex => "regex",
+        }
+    }
+}
+
+fn glob_to_regex(glob: &str) -> String {
+    let mut re = String::from("^");
+    for c in glob.chars() {
+        match c {
+            '*' => re.push_str(".*"),
+            '?' => re.push('.'),
+            c => re.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    re.push('$');
+    re
+}
+
+/// A compiled search query, matched against
+/// the cells of table rows.
+#[derive(Clone, Debug)]
+pub struct Filter {
+    re: Regex,
+}
+
+impl Filter {
+    pub fn new(query: &str, mode: FilterMode) -> Result<Self, regex::Error> {
+        let (pattern, insensitive) = match mode {
+            FilterMode::Substring => (regex::escape(query), true),
+            FilterMode::Glob => (glob_to_regex(query), true),
+            FilterMode::Regex => (query.to_string(), false),
+        };
+        let re = RegexBuilder::new(&pattern)
+            .case_insensitive(insensitive)
+            .build()?;
+        Ok(Self { re })
+    }
+
+    /// Byte range of the first match in `text`.
+    pub fn find(&self, text: &str) -> Option<(usize, usize)> {
+        self.re.find(text).map(|m| (m.start(), m.end()))
+    }
+
+    /// A row matches when any of its cells does.
+    pub fn matches(&self, cells: &[String]) -> bool {
+        cells.iter().any(|c| self.re.is_match(c))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{glob_to_regex, Filter, FilterMode};
+
+    fn matches(query: &str, mode: FilterMode, cell: &str) -> bool {
+        Filter::new(query, mode)
+            .unwrap()
+            .matches(&[cell.to_string()])
+    }
+
+    #[test]
+    fn modes_cycle() {
+        let mut mode = FilterMode::Substring;
+        for expected in [FilterMode::Glob, FilterMode::Regex, FilterMode::Substring] {
+            mode = mode.next();
+            assert_eq!(mode, expected);
+        }
+    }
+
+    #[test]
+    fn globs_translate_wildcards_and_escape_the_rest() {
+        assert_eq!(glob_to_regex("a*b?"), "^a.*b.$");
+        assert_eq!(glob_to_regex("a.b+(c)"), r"^a\.b\+\(c\)$");
+    }
+
+    #[test]
+    fn globs_match_whole_cells() {
+        assert!(matches("order*", FilterMode::Glob, "orders.dlq"));
+        assert!(!matches("order*", FilterMode::Glob, "my.orders"));
+        assert!(matches("q?", FilterMode::Glob, "q1"));
+        assert!(!matches("q?", FilterMode::Glob, "q12"));
+        assert!(matches("*", FilterMode::Glob, ""));
+    }
+
+    #[test]
+    fn globs_take_metacharacters_literally() {
+        assert!(matches("a.b", FilterMode::Glob, "a.b"));
+        assert!(!matches("a.b", FilterMode::Glob, "axb"));
+        assert!(matches("[x]", FilterMode::Glob, "[x]"));
+        assert!(!matches("[x]", FilterMode::Glob, "x"));
+    }
+
+    #[test]
+    fn substrings_match_anywhere_literally() {
+        assert!(matches("der", FilterMode::Substring, "orders"));
+        assert!(matches("a.b", FilterMode::Substring, "xa.by"));
+        assert!(!matches("a.b", FilterMode::Substring, "axb"));
+        assert!(matches("(", FilterMode::Substring, "f(x)"));
+    }
+
+    #[test]
+    fn only_regexes_are_case_sensitive() {
+        assert!(matches("ORD", FilterMode::Substring, "orders"));
+        assert!(matches("ORD*", FilterMode::Glob, "orders"));
+        assert!(!matches("ORD", FilterMode::Regex, "orders"));
+        assert!(matches("(?i)ORD", FilterMode::Regex, "orders"));
+    }
+
+    #[test]
+    fn regexes_match_anywhere() {
+        assert!(matches(r"\.dlq$", FilterMode::Regex, "orders.dlq"));
+        assert!(!matches(r"^dlq", FilterMode::Regex, "orders.dlq"));
+    }
+
+    #[test]
+    fn invalid_regexes_are_errors() {
+        assert!(Filter::new("(", FilterMode::Regex).is_err());
+        assert!(Filter::new("(", FilterMode::Glob).is_ok());
+    }
+
+    #[test]
+    fn find_gives_the_byte_range_of_the_match() {
+        let filter = Filter::new("DER", FilterMode::Substring).unwrap();
+        assert_eq!(filter.find("orders"), Some((2, 5)));
+        assert_eq!(filter.find("queue"), None);
+    }
+
+    #[test]
+    fn rows_match_on_any_cell() {
+        let filter = Filter::new("prod", FilterMode::Substring).unwrap();
+        assert!(filter.matches(&["orders".to_string(), "prod".to_string()]));
+        assert!(!filter.matches(&["orders".to_string(), "/".to_string()]));
+    }
+}
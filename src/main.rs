@@ -1,30 +1,63 @@
+mod alerts;
+mod audit;
+mod bulk;
 mod cli;
 mod client;
+mod clip;
+mod commands;
 mod config;
+mod definitions;
+mod dry_run;
 mod events;
+mod filter;
 mod models;
+mod routing;
+mod safety;
+mod series;
+mod topology;
 mod views;
 mod widgets;
 
-use cli::Cli;
-use client::Client;
-use config::AppConfig;
+use alerts::AlertEngine;
+use audit::{AuditLog, AuditedClient};
+use cli::{Cli, OutputFormat};
+use client::{ApiError, Client};
+use config::{AppConfig, Profile, DEFAULT_ADDR, DEFAULT_PASS, DEFAULT_USER};
+use dry_run::DryRunClient;
 use events::{Event, Events};
-use models::{ExchangeBindings, ExchangeInfo, MQMessage, Overview, QueueInfo};
-use views::{exchange::ExchangePane, overview::OverviewPane, queues::QueuesPane, StatefulPane};
+use filter::Filter;
+use models::{
+    Effect, ExchangeBindings, ExchangeInfo, HistoryWindow, MQMessage, MQMessageGetBody, Overview,
+    PayloadPost, Policy, PublishResponse, QueueInfo,
+};
+use safety::ReadOnlyClient;
+use views::{
+    alerts::AlertsPane, audit::AuditPane, definitions::DefinitionsPane, exchange::ExchangePane,
+    notifications::NotificationsPane, overview::OverviewPane, queues::QueuesPane,
+    topology::TopologyPane, StatefulPane,
+};
+use widgets::{
+    chart::now,
+    editor::edit_external,
+    notif::{self, Notification, Notifier, Toasts},
+};
 
 use std::{
+    cmp::Ordering,
     error::Error,
     io,
-    io::Stdout,
+    io::{Stdout, Write},
+    path::PathBuf,
+    process,
     sync::{mpsc, Arc},
     thread,
     time::Duration,
 };
 
 use clap::Parser;
+use serde_json::Value;
 use termion::{
-    event::Key,
+    event::{Key, MouseEvent},
     input::MouseTerminal,
     raw::{IntoRawMode, RawTerminal},
     screen::AlternateScreen,
@@ -32,7 +65,7 @@ use termion::{
 use tui::{
     backend::{Backend, TermionBackend},
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     text::{Span, Spans, Text},
     widgets::{Block, Borders, Paragraph, TableState, Tabs, Wrap},
     Frame, Terminal,
@@ -46,24 +79,128 @@ const ASCII: &str = r#"
                                       
 "#;
 
-type TBackend = TermionBackend<AlternateScreen<MouseTerminal<RawTerminal<Stdout>>>>;
+// Mouse reporting sequences toggled by `MouseTerminal`, which
+// termion keeps private.
+const MOUSE_ON: &str = "\x1b[?1000h\x1b[?1002h\x1b[?1015h\x1b[?1006h";
+const MOUSE_OFF: &str = "\x1b[?1006l\x1b[?1015l\x1b[?1002l\x1b[?1000l";
+
+// Raw mode is held apart from the backend writer so that it
+// can be suspended while an external program owns the tty.
+type TBackend = TermionBackend<AlternateScreen<MouseTerminal<Stdout>>>;
 
 /// data access trait for the RabbitMQ
 /// Management API. Implemented by any
 /// struct used for the app data backend.
 pub trait ManagementClient: Send + Sync {
-    fn get_exchange_overview(&self) -> Vec<ExchangeInfo>;
-    fn get_exchange_bindings(&self, exch: &ExchangeInfo) -> Vec<ExchangeBindings>;
-    fn get_overview(&self) -> Overview;
-    fn get_queues_info(&self) -> Vec<QueueInfo>;
-    fn post_queue_payload(&self, queue_name: String, vhost: &str, payload: String);
-    fn pop_queue_item(&self, queue_name: &str, vhost: &str) -> Option<MQMessage>;
-    fn ping(&self) -> Result<(), ()>;
-    fn purge_queue(&self, queue_name: &str, vhost: &str);
+    fn get_exchange_overview(&self) -> Result<Vec<ExchangeInfo>, ApiError>;
+    fn get_exchange_bindings(&self, exch: &ExchangeInfo)
+        -> Result<Vec<ExchangeBindings>, ApiError>;
+    fn get_overview(&self) -> Result<Overview, ApiError>;
+    /// The overview along with samples covering `window`.
+    fn get_overview_history(&self, window: HistoryWindow) -> Result<Overview, ApiError>;
+    fn get_queues_info(&self) -> Result<Vec<QueueInfo>, ApiError>;
+    /// A single queue, as it is now.
+    fn get_queue(&self, queue_name: &str, vhost: &str) -> Result<QueueInfo, ApiError>;
+    /// A single queue along with samples covering `window`.
+    fn get_queue_history(
+        &self,
+        queue_name: &str,
+        vhost: &str,
+        window: HistoryWindow,
+    ) -> Result<QueueInfo, ApiError>;
+    fn publish(
+        &self,
+        vhost: &str,
+        exchange: &str,
+        body: &PayloadPost,
+    ) -> Result<PublishResponse, ApiError>;
+    fn get_messages(
+        &self,
+        queue_name: &str,
+        vhost: &str,
+        body: &MQMessageGetBody,
+    ) -> Result<Vec<MQMessage>, ApiError>;
+    fn ping(&self) -> Result<(), ApiError>;
+    fn purge_queue(&self, queue_name: &str, vhost: &str) -> Result<Effect, ApiError>;
+    /// Bindings of every vhost, the default exchange's included.
+    fn get_bindings(&self) -> Result<Vec<ExchangeBindings>, ApiError>;
+    fn get_policies(&self) -> Result<Vec<Policy>, ApiError>;
+    /// Definitions of one vhost, or of the whole broker.
+    fn get_definitions(&self, vhost: Option<&str>) -> Result<Value, ApiError>;
+    /// Imports definitions into one vhost, or into the whole
+    /// broker, creating whatever does not exist yet.
+    fn post_definitions(&self, vhost: Option<&str>, defs: &Value) -> Result<Effect, ApiError>;
+
+    /// Fetches the message at the head of a queue,
+    /// leaving it queued.
+    fn pop_queue_item(&self, queue_name: &str, vhost: &str) -> Result<Option<MQMessage>, ApiError> {
+        let body = MQMessageGetBody::default();
+        Ok(self
+            .get_messages(queue_name, vhost, &body)?
+            .into_iter()
+            .next())
+    }
+
+    /// Publishes a plain payload straight to a queue through
+    /// the default exchange.
+    fn post_queue_payload(
+        &self,
+        queue_name: String,
+        vhost: &str,
+        payload: String,
+    ) -> Result<PublishResponse, ApiError> {
+        let body = PayloadPost::default()
+            .routing_key(queue_name)
+            .payload(payload);
+        self.publish(vhost, "", &body)
+    }
+}
+
+/// A field rows can be shown with.
+pub struct ColumnDef {
+    /// Name of the column in the config file.
+    pub key: &'static str,
+    pub title: &'static str,
+    /// Default share of the table width, in percent.
+    pub width: u16,
+    /// Whether the column is shown by default.
+    pub shown: bool,
+}
+
+/// A column picked for display: an index into
+/// `Rowable::columns` and its width in percent.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Column {
+    pub field: usize,
+    pub width: u16,
 }
 
 pub trait Rowable {
-    fn to_row(&self) -> Vec<String>;
+    /// Every column the rows can be shown with.
+    fn columns() -> &'static [ColumnDef];
+
+    /// Text of the cell for the column at `field`
+    /// in `Self::columns()`.
+    fn cell(&self, field: usize) -> String;
+
+    fn to_row(&self, columns: &[Column]) -> Vec<String> {
+        columns.iter().map(|c| self.cell(c.field)).collect()
+    }
+
+    fn default_columns() -> Vec<Column>
+    where
+        Self: Sized,
+    {
+        Self::columns()
+            .iter()
+            .enumerate()
+            .filter(|(_, d)| d.shown)
+            .map(|(field, d)| Column {
+                field,
+                width: d.width,
+            })
+            .collect()
+    }
 }
 
 pub struct TabsState<'a, const L: usize> {
@@ -107,36 +244,111 @@ impl<T> DataContainer<T> {
     }
 }
 
+/// Identity of an entry that survives data refreshes,
+/// so selections can follow the entity rather than its index.
+pub trait Identifiable {
+    type Id: PartialEq;
+
+    fn id(&self) -> Self::Id;
+}
+
+/// Value a row is ordered by in a given column.
+#[derive(PartialEq, PartialOrd, Debug)]
+pub enum SortValue {
+    Number(f64),
+    Text(String),
+}
+
+/// Rows that can be ordered by any of their columns.
+pub trait Sortable {
+    /// Value of the column at `field` in `Rowable::columns`.
+    fn sort_value(&self, field: usize) -> SortValue;
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SortOrder {
+    /// Sorted field, kept when columns are rearranged.
+    pub field: usize,
+    pub descending: bool,
+}
+
 /// Stateful container for tabular data. Manages
 /// state such as currently selected row, etc.
+///
+/// Rows hidden by the filter stay in `data`; `view`
+/// holds the indices of the rows currently shown, and
+/// the selection is an index into `view`.
 pub struct Datatable<T> {
     data: DataContainer<T>,
     state: TableState,
+    view: Vec<usize>,
+    filter: Option<Filter>,
+    sort: Option<SortOrder>,
+    columns: Vec<Column>,
 }
 
 impl<T> Default for Datatable<T> {
     fn default() -> Self {
-        Self {
-            data: DataContainer {
-                entries: Vec::new(),
-            },
-            state: TableState::default(),
-        }
+        Self::new(Vec::new())
     }
 }
 
 impl<T> Datatable<T> {
     fn new(data: Vec<T>) -> Self {
         Self {
+            view: (0..data.len()).collect(),
             data: DataContainer { entries: data },
             state: TableState::default(),
+            filter: None,
+            sort: None,
+            columns: Vec::new(),
         }
     }
 
+    /// Rows currently shown, in display order.
+    pub fn rows(&self) -> impl Iterator<Item = &T> {
+        self.view.iter().map(move |&i| &self.data.entries[i])
+    }
+
+    pub fn len(&self) -> usize {
+        self.view.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.view.is_empty()
+    }
+
+    pub fn total(&self) -> usize {
+        self.data.entries.len()
+    }
+
+    pub fn selected(&self) -> Option<&T> {
+        self.state
+            .selected()
+            .and_then(|i| self.view.get(i))
+            .map(|&i| &self.data.entries[i])
+    }
+
+    pub fn filter(&self) -> Option<&Filter> {
+        self.filter.as_ref()
+    }
+
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    /// Widths of the shown columns, in percent.
+    pub fn widths(&self) -> Vec<u16> {
+        self.columns.iter().map(|c| c.width).collect()
+    }
+
     pub fn next(&mut self) {
+        if self.is_empty() {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
-                if i >= self.data.entries.len() - 1 {
+                if i >= self.view.len() - 1 {
                     0
                 } else {
                     i + 1
@@ -148,10 +360,13 @@ impl<T> Datatable<T> {
     }
 
     pub fn previous(&mut self) {
+        if self.is_empty() {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
                 if i == 0 {
-                    self.data.entries.len() - 1
+                    self.view.len() - 1
                 } else {
                     i - 1
                 }
@@ -162,6 +377,158 @@ impl<T> Datatable<T> {
     }
 }
 
+impl<T: Rowable> Datatable<T> {
+    pub fn row(&self, entry: &T) -> Vec<String> {
+        entry.to_row(&self.columns)
+    }
+
+    /// Titles of the shown columns, the sorted one
+    /// marked with its direction.
+    pub fn headers(&self) -> Vec<String> {
+        self.columns
+            .iter()
+            .map(|c| {
+                let title = T::columns()[c.field].title;
+                match self.sort {
+                    Some(s) if s.field == c.field && s.descending => format!("{} ▼", title),
+                    Some(s) if s.field == c.field => format!("{} ▲", title),
+                    _ => title.to_string(),
+                }
+            })
+            .collect()
+    }
+}
+
+impl<T: Rowable + Identifiable + Sortable> Datatable<T> {
+    pub fn set_columns(&mut self, columns: Vec<Column>) {
+        self.columns = columns;
+        self.reapply();
+    }
+
+    /// Replaces the entries, keeping the filter, the sort
+    /// and the selection on the same entity when it still exists.
+    pub fn update(&mut self, data: Vec<T>) {
+        let selected = self.selected().map(|e| e.id());
+        self.data.set(data);
+        self.refresh(selected);
+    }
+
+    /// Changes the entries in place, keeping the filter, the
+    /// sort and the selection as `update` does.
+    pub fn update_with(&mut self, f: impl FnOnce(&mut Vec<T>)) {
+        let selected = self.selected().map(|e| e.id());
+        f(&mut self.data.entries);
+        self.refresh(selected);
+    }
+
+    /// Looks an entity up among all entries,
+    /// including the ones hidden by the filter.
+    pub fn find(&self, id: &T::Id) -> Option<&T> {
+        self.data.entries.iter().find(|e| e.id() == *id)
+    }
+
+    pub fn set_filter(&mut self, filter: Option<Filter>) {
+        self.filter = filter;
+        self.reapply();
+    }
+
+    /// Sorts by the shown column at `column`, ascending at
+    /// first and toggling the direction when already sorted by it.
+    pub fn sort_by(&mut self, column: usize) {
+        let Some(field) = self.columns.get(column).map(|c| c.field) else {
+            return;
+        };
+        self.sort = match self.sort {
+            Some(s) if s.field == field => Some(SortOrder {
+                field,
+                descending: !s.descending,
+            }),
+            _ => Some(SortOrder {
+                field,
+                descending: false,
+            }),
+        };
+        self.reapply();
+    }
+
+    /// Moves the sort to the next shown column,
+    /// keeping the direction.
+    pub fn cycle_sort(&mut self) {
+        if self.columns.is_empty() {
+            return;
+        }
+        let pos = self
+            .sort
+            .and_then(|s| self.columns.iter().position(|c| c.field == s.field));
+        let next = pos.map_or(0, |p| (p + 1) % self.columns.len());
+        self.sort = Some(SortOrder {
+            field: self.columns[next].field,
+            descending: self.sort.is_some_and(|s| s.descending),
+        });
+        self.reapply();
+    }
+
+    pub fn toggle_sort_order(&mut self) {
+        let Some(first) = self.columns.first() else {
+            return;
+        };
+        self.sort = Some(match self.sort {
+            Some(s) => SortOrder {
+                descending: !s.descending,
+                ..s
+            },
+            None => SortOrder {
+                field: first.field,
+                descending: true,
+            },
+        });
+        self.reapply();
+    }
+
+    fn reapply(&mut self) {
+        let selected = self.selected().map(|e| e.id());
+        self.refresh(selected);
+    }
+
+    fn refresh(&mut self, selected: Option<T::Id>) {
+        let entries = &self.data.entries;
+        self.view = match &self.filter {
+            Some(f) => (0..entries.len())
+                .filter(|&i| f.matches(&entries[i].to_row(&self.columns)))
+                .collect(),
+            None => (0..entries.len()).collect(),
+        };
+        if let Some(sort) = self.sort {
+            let mut keyed: Vec<_> = self
+                .view
+                .iter()
+                .map(|&i| (entries[i].sort_value(sort.field), i))
+                .collect();
+            keyed.sort_by(|a, b| {
+                let ord = a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal);
+                if sort.descending {
+                    ord.reverse()
+                } else {
+                    ord
+                }
+            });
+            self.view = keyed.into_iter().map(|(_, i)| i).collect();
+        }
+        let pos = selected.and_then(|id| {
+            self.view
+                .iter()
+                .position(|&i| self.data.entries[i].id() == id)
+        });
+        let pos = match (pos, self.state.selected()) {
+            (Some(p), _) => Some(p),
+            (None, _) if self.view.is_empty() => None,
+            (None, Some(i)) => Some(i.min(self.view.len() - 1)),
+            (None, None) => None,
+        };
+        self.state.select(pos);
+    }
+}
+
 /// The manager gives us a way to structure the relationship
 /// between our tabs and panes. Serves as a middleman between
 /// app and panes.
@@ -238,42 +605,123 @@ struct App<'a, B>
 where
     B: Backend,
 {
-    manager: TabsManager<'a, B, 3>,
+    manager: TabsManager<'a, B, 8>,
+    // Shown above whichever tab is open.
+    toasts: Toasts,
+    // Badged in the header, so that nobody wonders
+    // why nothing can be changed.
+    read_only: bool,
+    dry_run: bool,
 }
 
 impl<'a, B> App<'a, B>
 where
     B: Backend + 'a,
 {
-    pub fn new<M: ManagementClient + 'static>(client: Arc<M>, config: AppConfig) -> Self {
+    pub fn new<M: ManagementClient + 'static>(
+        client: Arc<M>,
+        config: AppConfig,
+        audit_log: Option<PathBuf>,
+        notifier: Notifier,
+        toasts: Toasts,
+        history_rx: mpsc::Receiver<Notification>,
+    ) -> Self {
         let thread_client = Arc::clone(&client);
         let (overview_tx, overview_rx) = mpsc::channel();
         let (exchange_tx, exchange_rx) = mpsc::channel();
         let (queue_tx, queue_rx) = mpsc::channel();
+        let (alert_tx, alert_rx) = mpsc::channel();
+        let thread_notifier = notifier.clone();
+        let update_rate = config.update_rate;
+        let has_rules = !config.alerts.rules.is_empty();
+        let mut engine = AlertEngine::new(config.alerts.clone());
         // Create data thread. Responsible for gathering new data points
-        // and sending to existing receivers.
-        thread::spawn(move || loop {
-            let overview_data = thread_client.get_overview();
-            if overview_tx.send(overview_data).is_err() {
-                break;
-            }
-            let exchange_data = thread_client.get_exchange_overview();
-            if exchange_tx.send(exchange_data).is_err() {
-                break;
-            }
-            let queue_data = thread_client.get_queues_info();
-            if queue_tx.send(queue_data).is_err() {
-                break;
+        // and sending to existing receivers. Alert rules are checked
+        // against every refresh before it is sent.
+        thread::spawn(move || {
+            // Whether the last refresh failed, so that an
+            // unreachable API is only notified once.
+            let mut failing = false;
+            loop {
+                let data = thread_client.get_overview().and_then(|o| {
+                    let e = thread_client.get_exchange_overview()?;
+                    let q = thread_client.get_queues_info()?;
+                    Ok((o, e, q))
+                });
+                let (overview_data, exchange_data, queue_data) = match data {
+                    Ok(data) => data,
+                    Err(e) => {
+                        if !failing {
+                            thread_notifier.error(format!("Refresh failed: {}", e));
+                            failing = true;
+                        }
+                        thread::sleep(Duration::from_millis(update_rate));
+                        continue;
+                    }
+                };
+                if failing {
+                    thread_notifier.success("Refreshing again");
+                    failing = false;
+                }
+                let mut alerts = engine.evaluate_overview(&overview_data, now());
+                alerts.extend(engine.evaluate_queues(&queue_data, now()));
+                if overview_tx.send(overview_data).is_err()
+                    || exchange_tx.send(exchange_data).is_err()
+                    || queue_tx.send(queue_data).is_err()
+                {
+                    break;
+                }
+                for alert in alerts {
+                    engine.dispatch(&alert);
+                    thread_notifier.post(alert.level(), alert.message());
+                    if alert_tx.send(alert).is_err() {
+                        return;
+                    }
+                }
+                thread::sleep(Duration::from_millis(update_rate));
             }
-            thread::sleep(Duration::from_millis(config.update_rate));
         });
         Self {
+            toasts,
+            read_only: config.safety.is_read_only(),
+            dry_run: config.dry_run,
             manager: TabsManager::new(
-                ["Overview", "Exchanges", "Queues"],
+                [
+                    "Overview",
+                    "Exchanges",
+                    "Topology",
+                    "Queues",
+                    "Definitions",
+                    "Alerts",
+                    "Notifications",
+                    "Audit",
+                ],
                 [
                     Box::new(OverviewPane::new(Arc::clone(&client), overview_rx)),
-                    Box::new(ExchangePane::<M>::new(Arc::clone(&client), exchange_rx)),
-                    Box::new(QueuesPane::<'a, M>::new(Arc::clone(&client), queue_rx)),
+                    Box::new(ExchangePane::<M>::new(
+                        Arc::clone(&client),
+                        exchange_rx,
+                        &config,
+                        notifier.clone(),
+                    )),
+                    Box::new(TopologyPane::<M>::new(
+                        Arc::clone(&client),
+                        notifier.clone(),
+                    )),
+                    Box::new(QueuesPane::<'a, M>::new(
+                        Arc::clone(&client),
+                        queue_rx,
+                        &config,
+                        notifier.clone(),
+                    )),
+                    Box::new(DefinitionsPane::<'a, M>::new(
+                        Arc::clone(&client),
+                        &config,
+                        notifier,
+                    )),
+                    Box::new(AlertsPane::new(alert_rx, has_rules)),
+                    Box::new(NotificationsPane::new(history_rx)),
+                    Box::new(AuditPane::new(audit_log)),
                 ],
             ),
         }
@@ -297,6 +745,7 @@ where
         self.draw_header(f, chunks[0]);
         self.draw_tabs(f, chunks[1]);
         self.manager.pane().draw(f, chunks[2]);
+        self.toasts.draw(f, chunks[2]);
     }
 
     fn draw_header(&mut self, f: &mut Frame<B>, area: Rect) {
@@ -324,6 +773,34 @@ where
             .split(chunks[3]);
         f.render_widget(pg_title, chunks[0]);
         f.render_widget(p, meta_chunks[1]);
+        let mut badges = Vec::new();
+        if self.dry_run {
+            badges.push(Span::styled(
+                " DRY RUN ",
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        }
+        if self.read_only {
+            if !badges.is_empty() {
+                badges.push(Span::raw(" "));
+            }
+            badges.push(Span::styled(
+                " READ-ONLY ",
+                Style::default()
+                    .fg(Color::White)
+                    .bg(Color::Red)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        }
+        if !badges.is_empty() {
+            let p = Paragraph::new(Spans::from(badges))
+                .alignment(Alignment::Right)
+                .block(Block::default());
+            f.render_widget(p, meta_chunks[0]);
+        }
     }
 
     fn draw_tabs(&self, f: &mut Frame<B>, area: Rect) {
@@ -345,6 +822,10 @@ where
     /// regardless of active view. Any other keys are passed off
     /// to the tab manager.
     fn handle_key(&mut self, key: Key) {
+        if self.captures_input() {
+            self.manager.pane().handle_key(key);
+            return;
+        }
         match key {
             Key::Char('l') => {
                 self.manager.next();
@@ -358,46 +839,217 @@ where
         }
     }
 
+    fn handle_mouse(&mut self, event: MouseEvent) {
+        self.manager.pane().handle_mouse(event);
+    }
+
+    fn captures_input(&mut self) -> bool {
+        self.manager.pane().captures_input()
+    }
+
+    fn external_edit(&mut self) -> Option<String> {
+        self.manager.pane().external_edit()
+    }
+
+    fn external_edit_done(&mut self, result: io::Result<String>) {
+        self.manager.pane().external_edit_done(result);
+    }
+
     /// Handles tick updates. Most cases are just passed
     /// to the tab manager to determine if individual panes
     /// need updated.
     fn update(&mut self) {
+        self.toasts.update();
         self.manager.update();
     }
 }
 
+/// Hands the terminal over to an external program for
+/// the duration of `f`, then restores the TUI.
+fn suspend<T>(
+    terminal: &mut Terminal<TBackend>,
+    raw: &RawTerminal<Stdout>,
+    events: &Events,
+    f: impl FnOnce() -> T,
+) -> io::Result<T> {
+    events.pause_input();
+    let out = terminal.backend_mut();
+    write!(
+        out,
+        "{}{}{}",
+        MOUSE_OFF,
+        termion::screen::ToMainScreen,
+        termion::cursor::Show
+    )?;
+    Write::flush(out)?;
+    raw.suspend_raw_mode()?;
+    let res = f();
+    raw.activate_raw_mode()?;
+    let out = terminal.backend_mut();
+    write!(out, "{}{}", termion::screen::ToAlternateScreen, MOUSE_ON)?;
+    Write::flush(out)?;
+    terminal.hide_cursor()?;
+    terminal.clear()?;
+    events.resume_input();
+    Ok(res)
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
     let cli = Cli::parse();
-    let c = Client::new(&cli.addr, &cli.user, Some(cli.pass));
-    if let Err(_) = c.ping() {
-        println!("Unable to ping RabbitMQ API.");
-        println!("Check that the service is running and that creds are correct.");
+    let mut config = match AppConfig::load(cli.config.as_deref()) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("Invalid config: {}", e);
+            process::exit(1);
+        }
+    };
+    if let Some(rate) = cli.publish_rate {
+        config.publish_rate = rate;
+    }
+    let profile = match &cli.profile {
+        Some(name) => match config.profiles.get(name) {
+            Some(p) => p.clone(),
+            None => {
+                eprintln!("Unknown profile {}", name);
+                process::exit(1);
+            }
+        },
+        None => Profile::default(),
+    };
+    config.safety = config.safety.production(profile.production);
+    if cli.read_only {
+        config.safety = config
+            .safety
+            .read_only("rabbitui was started with --read-only");
+    } else if let (true, Some(name)) = (profile.read_only, &cli.profile) {
+        config.safety = config
+            .safety
+            .read_only(format!("profile {} is read-only", name));
+    }
+    let addr = cli
+        .addr
+        .or(profile.addr)
+        .unwrap_or_else(|| DEFAULT_ADDR.to_string());
+    let user = cli
+        .user
+        .or(profile.user)
+        .unwrap_or_else(|| DEFAULT_USER.to_string());
+    let pass = cli
+        .pass
+        .or(profile.pass)
+        .unwrap_or_else(|| DEFAULT_PASS.to_string());
+    let c = Client::new(&addr, &user, Some(pass));
+    if c.ping().is_err() {
+        eprintln!("Unable to ping RabbitMQ API.");
+        eprintln!("Check that the service is running and that creds are correct.");
+        process::exit(1);
+    }
+    let audit_path = cli.audit_log.or(config.audit_log.clone());
+    let log = match audit_path.as_deref().map(AuditLog::open) {
+        Some(Ok(log)) => Some(log),
+        failed => {
+            // Nothing may change the broker unrecorded.
+            let why = match (failed, &audit_path) {
+                (Some(Err(e)), Some(path)) => {
+                    format!("audit log {} cannot be opened: {}", path.display(), e)
+                }
+                _ => "no audit log location, set one with --audit-log".to_string(),
+            };
+            eprintln!("Warning: {}, starting read-only.", why);
+            config.safety = config.safety.read_only(why);
+            None
+        }
+    };
+    let (notifier, toasts, history_rx) = notif::channel();
+    // Every mutating call, from the UI or a command,
+    // goes through the audit log.
+    let c = AuditedClient::new(c, log, &addr, &user, notifier.clone());
+    let c = ReadOnlyClient::new(c, config.safety.clone());
+    let notifications = (notifier.clone(), toasts, history_rx);
+    if cli.dry_run {
+        config.dry_run = true;
+        // Outside of the audit log, as nothing is sent.
+        let c = DryRunClient::new(c, &addr, notifier);
+        return launch(
+            c,
+            cli.command,
+            cli.output,
+            config,
+            audit_path,
+            notifications,
+        );
+    }
+    launch(
+        c,
+        cli.command,
+        cli.output,
+        config,
+        audit_path,
+        notifications,
+    )
+}
+
+/// Runs the command given on the command line,
+/// or the UI when there is none.
+fn launch<M: ManagementClient + 'static>(
+    client: M,
+    command: Option<cli::Command>,
+    output: OutputFormat,
+    config: AppConfig,
+    audit_path: Option<PathBuf>,
+    (notifier, toasts, history_rx): (Notifier, Toasts, mpsc::Receiver<Notification>),
+) -> Result<(), Box<dyn Error>> {
+    if let Some(cmd) = command {
+        let res = commands::run(&client, cmd, output, &config);
+        // Commands print their own results, what was
+        // notified along the way are dry runs and
+        // audit log failures.
+        for n in history_rx.try_iter() {
+            eprintln!("{}", n.msg);
+        }
+        if let Err(e) = res {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        }
         return Ok(());
     }
-    // TODO allow override this.
-    let config = AppConfig::default();
-    let mut app = App::<TBackend>::new::<Client>(Arc::new(c), config);
+    let mut app = App::<TBackend>::new(
+        Arc::new(client),
+        config,
+        audit_path,
+        notifier,
+        toasts,
+        history_rx,
+    );
     // TODO support different backend for non-MacOs.
     // Just need to swap out Termion based upon some config or compile setting.
-    let stdout = io::stdout().into_raw_mode()?;
-    let stdout = MouseTerminal::from(stdout);
+    let raw = io::stdout().into_raw_mode()?;
+    let stdout = MouseTerminal::from(io::stdout());
     let stdout = AlternateScreen::from(stdout);
     let backend = TermionBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
-    let events = Events::new();
+    let mut events = Events::new();
+    // quitting is decided below, panes taking text
+    // input may need the exit key.
+    events.disable_exit_key();
 
     loop {
         terminal.draw(|f| app.draw(f))?;
 
         match events.next()? {
             Event::Input(key) => match key {
-                Key::Char('q') => {
+                Key::Char('q') if !app.captures_input() => {
                     break;
                 }
                 _ => {
                     app.handle_key(key);
+                    if let Some(text) = app.external_edit() {
+                        let res = suspend(&mut terminal, &raw, &events, || edit_external(&text))?;
+                        app.external_edit_done(res);
+                    }
                 }
             },
+            Event::Mouse(m) => app.handle_mouse(m),
             Event::Tick => {
                 app.update();
             }
@@ -405,3 +1057,122 @@ fn main() -> Result<(), Box<dyn Error>> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Datatable, Rowable};
+    use crate::{
+        filter::{Filter, FilterMode},
+        models::QueueInfo,
+    };
+
+    use serde_json::json;
+
+    // The ready column, among the shown ones.
+    const READY: usize = 3;
+
+    fn queue(vhost: &str, name: &str, ready: u64) -> QueueInfo {
+        serde_json::from_value(json!({
+            "name": name,
+            "type": "classic",
+            "state": "running",
+            "messages_ready": ready,
+            "messages_unacknowledged": 0,
+            "messages": ready,
+            "vhost": vhost,
+        }))
+        .unwrap()
+    }
+
+    fn table(queues: Vec<QueueInfo>) -> Datatable<QueueInfo> {
+        let mut table = Datatable::new(queues);
+        table.set_columns(QueueInfo::default_columns());
+        table
+    }
+
+    fn select(table: &mut Datatable<QueueInfo>, vhost: &str, name: &str) {
+        let pos = table
+            .rows()
+            .position(|q| q.vhost == vhost && q.name == name)
+            .unwrap();
+        table.state.select(Some(pos));
+    }
+
+    fn selected(table: &Datatable<QueueInfo>) -> Option<(&str, &str)> {
+        table
+            .selected()
+            .map(|q| (q.vhost.as_str(), q.name.as_str()))
+    }
+
+    fn names(table: &Datatable<QueueInfo>) -> Vec<&str> {
+        table.rows().map(|q| q.name.as_str()).collect()
+    }
+
+    #[test]
+    fn selection_follows_the_queue_across_sorts() {
+        let mut table = table(vec![
+            queue("/", "a", 3),
+            queue("/", "b", 1),
+            queue("prod", "c", 2),
+        ]);
+        select(&mut table, "/", "b");
+        table.sort_by(READY);
+        assert_eq!(names(&table), ["b", "c", "a"]);
+        assert_eq!(selected(&table), Some(("/", "b")));
+        table.sort_by(READY);
+        assert_eq!(names(&table), ["a", "c", "b"]);
+        assert_eq!(selected(&table), Some(("/", "b")));
+    }
+
+    #[test]
+    fn selection_tells_vhosts_apart() {
+        let mut table = table(vec![queue("/", "orders", 1), queue("prod", "orders", 2)]);
+        table.sort_by(READY);
+        select(&mut table, "prod", "orders");
+        // The prod queue drains below the other one.
+        table.update(vec![queue("prod", "orders", 0), queue("/", "orders", 1)]);
+        assert_eq!(table.state.selected(), Some(0));
+        assert_eq!(selected(&table), Some(("prod", "orders")));
+    }
+
+    #[test]
+    fn selection_follows_the_queue_across_filters() {
+        let mut table = table(vec![
+            queue("/", "audit", 0),
+            queue("/", "orders", 0),
+            queue("/", "orders.dlq", 0),
+        ]);
+        select(&mut table, "/", "orders.dlq");
+        table.set_filter(Some(Filter::new("orders", FilterMode::Substring).unwrap()));
+        assert_eq!(names(&table), ["orders", "orders.dlq"]);
+        assert_eq!(selected(&table), Some(("/", "orders.dlq")));
+        table.set_filter(None);
+        assert_eq!(selected(&table), Some(("/", "orders.dlq")));
+    }
+
+    #[test]
+    fn selection_stays_in_place_when_the_queue_is_filtered_out() {
+        let mut table = table(vec![queue("/", "audit", 0), queue("/", "orders", 0)]);
+        select(&mut table, "/", "orders");
+        table.set_filter(Some(Filter::new("audit", FilterMode::Substring).unwrap()));
+        assert_eq!(selected(&table), Some(("/", "audit")));
+        table.set_filter(Some(Filter::new("nothing", FilterMode::Substring).unwrap()));
+        assert_eq!(selected(&table), None);
+    }
+
+    #[test]
+    fn selection_moves_to_a_neighbour_when_the_queue_disappears() {
+        let mut table = table(vec![
+            queue("/", "a", 0),
+            queue("/", "b", 0),
+            queue("/", "c", 0),
+        ]);
+        select(&mut table, "/", "c");
+        table.update(vec![queue("/", "a", 0), queue("/", "b", 0)]);
+        assert_eq!(selected(&table), Some(("/", "b")));
+        assert!(table.find(&("/".to_string(), "c".to_string())).is_none());
+        table.update(Vec::new());
+        assert_eq!(selected(&table), None);
+        assert_eq!(table.state.selected(), None);
+    }
+}
@@ -1,8 +1,7 @@
-use crate::{client::Ackmode, Rowable};
+use crate::{client::Ackmode, ColumnDef, Identifiable, Rowable, SortValue, Sortable};
 
-use std::collections::HashMap;
-
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
 
 trait ToRate {
     fn to_rate(&self) -> String;
@@ -14,13 +13,42 @@ impl ToRate for String {
     }
 }
 
+pub(crate) fn human_bytes(n: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = n as f64;
+    let mut unit = 0;
+    while size >= 1024. && unit < UNITS.len() - 1 {
+        size /= 1024.;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", n)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+pub(crate) const fn column(
+    key: &'static str,
+    title: &'static str,
+    width: u16,
+    shown: bool,
+) -> ColumnDef {
+    ColumnDef {
+        key,
+        title,
+        width,
+        shown,
+    }
+}
+
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "lowercase")]
 pub enum MQEncoding {
     Auto,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct ExchangeInfo {
     pub auto_delete: bool,
     pub durable: bool,
@@ -32,9 +60,14 @@ pub struct ExchangeInfo {
     pub message_stats: ExchangeMsgStats,
     pub user_who_performed_action: String,
     pub vhost: String,
+    #[serde(default)]
+    pub arguments: Map<String, Value>,
+    /// Name of the policy applying to the exchange.
+    #[serde(default)]
+    pub policy: Option<String>,
 }
 
-#[derive(Deserialize, Debug, Default)]
+#[derive(Deserialize, Serialize, Debug, Default)]
 #[serde(default)]
 pub struct ExchangeMsgStats {
     #[serde(alias = "publish_in_details")]
@@ -43,26 +76,53 @@ pub struct ExchangeMsgStats {
     pub out_rate: RateContainer,
 }
 
-impl ExchangeInfo {
-    pub fn headers<'a>() -> [&'a str; 4] {
-        ["Name", "Type", "Rate In", "Rate Out"]
+const EXCHANGE_COLUMNS: [ColumnDef; 8] = [
+    column("name", "Name", 40, true),
+    column("type", "Type", 30, true),
+    column("rate_in", "Rate In", 15, true),
+    column("rate_out", "Rate Out", 15, true),
+    column("vhost", "Vhost", 15, false),
+    column("durable", "Durable", 10, false),
+    column("auto_delete", "Auto delete", 10, false),
+    column("internal", "Internal", 10, false),
+];
+
+impl Rowable for ExchangeInfo {
+    fn columns() -> &'static [ColumnDef] {
+        &EXCHANGE_COLUMNS
+    }
+
+    fn cell(&self, field: usize) -> String {
+        match EXCHANGE_COLUMNS[field].key {
+            "name" if self.name.is_empty() => "(AMQP DEFAULT)".to_owned(),
+            "name" => self.name.clone(),
+            "type" => self.t.clone(),
+            "rate_in" => self.message_stats.in_rate.rate.to_string().to_rate(),
+            "rate_out" => self.message_stats.out_rate.rate.to_string().to_rate(),
+            "vhost" => self.vhost.clone(),
+            "durable" => self.durable.to_string(),
+            "auto_delete" => self.auto_delete.to_string(),
+            "internal" => self.internal.to_string(),
+            _ => String::new(),
+        }
     }
 }
 
-impl Rowable for ExchangeInfo {
-    fn to_row(&self) -> Vec<String> {
-        let nice_name = if self.name.is_empty() {
-            "(AMQP DEFAULT)".to_owned()
-        } else {
-            self.name.clone()
-        };
+impl Sortable for ExchangeInfo {
+    fn sort_value(&self, field: usize) -> SortValue {
+        match EXCHANGE_COLUMNS[field].key {
+            "rate_in" => SortValue::Number(self.message_stats.in_rate.rate),
+            "rate_out" => SortValue::Number(self.message_stats.out_rate.rate),
+            _ => SortValue::Text(self.cell(field).to_lowercase()),
+        }
+    }
+}
+
+impl Identifiable for ExchangeInfo {
+    type Id = (String, String);
 
-        vec![
-            nice_name,
-            self.t.clone(),
-            self.message_stats.in_rate.rate.to_string().to_rate(),
-            self.message_stats.out_rate.rate.to_string().to_rate(),
-        ]
+    fn id(&self) -> Self::Id {
+        (self.vhost.clone(), self.name.clone())
     }
 }
 
@@ -77,48 +137,189 @@ pub struct ExchangeBindings {
     pub routing_key: String,
     #[serde(alias = "properties_key")]
     pub prop_key: String,
+    #[serde(default)]
+    pub arguments: Map<String, Value>,
 }
 
-impl ExchangeBindings {
-    pub fn headers<'a>() -> [&'a str; 2] {
-        ["To", "Routing key"]
-    }
-}
+const BINDING_COLUMNS: [ColumnDef; 2] = [
+    column("destination", "To", 50, true),
+    column("routing_key", "Routing key", 30, true),
+];
 
 impl Rowable for ExchangeBindings {
-    fn to_row(&self) -> Vec<String> {
-        vec![self.dest.clone(), self.routing_key.clone()]
+    fn columns() -> &'static [ColumnDef] {
+        &BINDING_COLUMNS
+    }
+
+    fn cell(&self, field: usize) -> String {
+        match BINDING_COLUMNS[field].key {
+            "destination" => self.dest.clone(),
+            "routing_key" => self.routing_key.clone(),
+            _ => String::new(),
+        }
     }
 }
 
 #[derive(Deserialize, Debug)]
+pub struct Policy {
+    pub vhost: String,
+    pub name: String,
+    pub pattern: String,
+    #[serde(rename = "apply-to")]
+    pub apply_to: String,
+    pub definition: Map<String, Value>,
+    pub priority: i64,
+}
+
+#[derive(Deserialize, Debug, Default)]
 pub struct Overview {
     pub queue_totals: OverviewQueueTotals,
+    // Sent as an empty list until some
+    // traffic has been seen.
+    #[serde(default, deserialize_with = "empty_list_as_default")]
     pub message_stats: OverviewMessageRates,
+    #[serde(default)]
+    pub object_totals: ObjectTotals,
+    #[serde(default)]
+    pub cluster_name: String,
+    #[serde(default)]
+    pub rabbitmq_version: String,
+    #[serde(default)]
+    pub erlang_version: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Default)]
 pub struct OverviewQueueTotals {
     pub messages: f64,
     pub messages_ready: f64,
     #[serde(alias = "messages_unacknowledged")]
     pub messages_unacked: f64,
+    #[serde(default)]
+    pub messages_details: RateContainer,
+    #[serde(default)]
+    pub messages_ready_details: RateContainer,
+    #[serde(default, alias = "messages_unacknowledged_details")]
+    pub messages_unacked_details: RateContainer,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
 pub struct OverviewMessageRates {
     pub disk_reads: f64,
     pub disk_reads_details: RateContainer,
     pub disk_writes: f64,
     pub disk_writes_details: RateContainer,
+    pub publish_details: RateContainer,
+    pub publish_in_details: RateContainer,
+    pub publish_out_details: RateContainer,
+    pub confirm_details: RateContainer,
+    pub deliver_get_details: RateContainer,
+    pub ack_details: RateContainer,
+    pub redeliver_details: RateContainer,
+    pub return_unroutable_details: RateContainer,
+    pub drop_unroutable_details: RateContainer,
 }
 
 #[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct ObjectTotals {
+    pub connections: u64,
+    pub channels: u64,
+    pub consumers: u64,
+    pub queues: u64,
+    pub exchanges: u64,
+}
+
+/// The API sends `[]` instead of an object when there
+/// is nothing to report yet.
+fn empty_list_as_default<'de, D, T>(d: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    match Value::deserialize(d)? {
+        Value::Array(a) if a.is_empty() => Ok(T::default()),
+        v => T::deserialize(v).map_err(de::Error::custom),
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
 pub struct RateContainer {
     pub rate: f64,
+    /// Past values, only sent when history is requested.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub samples: Vec<Sample>,
 }
 
-#[derive(Deserialize, Debug)]
+/// A past value of a length or of a counter, `timestamp`
+/// being in milliseconds since the epoch.
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct Sample {
+    pub sample: f64,
+    pub timestamp: u64,
+}
+
+/// How far back charts go, and so how much
+/// history is asked from the API.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum HistoryWindow {
+    OneMinute,
+    #[default]
+    TenMinutes,
+    OneHour,
+    EightHours,
+}
+
+impl HistoryWindow {
+    pub fn next(self) -> Self {
+        match self {
+            HistoryWindow::OneMinute => HistoryWindow::TenMinutes,
+            HistoryWindow::TenMinutes => HistoryWindow::OneHour,
+            HistoryWindow::OneHour => HistoryWindow::EightHours,
+            HistoryWindow::EightHours => HistoryWindow::OneMinute,
+        }
+    }
+
+    pub fn secs(self) -> u64 {
+        match self {
+            HistoryWindow::OneMinute => 60,
+            HistoryWindow::TenMinutes => 600,
+            HistoryWindow::OneHour => 3_600,
+            HistoryWindow::EightHours => 28_800,
+        }
+    }
+
+    /// Seconds between samples, for about a
+    /// hundred points per window.
+    pub fn incr(self) -> u64 {
+        match self {
+            HistoryWindow::OneMinute => 5,
+            HistoryWindow::TenMinutes => 5,
+            HistoryWindow::OneHour => 30,
+            HistoryWindow::EightHours => 300,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            HistoryWindow::OneMinute => "1m",
+            HistoryWindow::TenMinutes => "10m",
+            HistoryWindow::OneHour => "1h",
+            HistoryWindow::EightHours => "8h",
+        }
+    }
+
+    /// Query string asking for this window of history.
+    pub fn query(self) -> String {
+        format!(
+            "lengths_age={age}&lengths_incr={incr}&msg_rates_age={age}&msg_rates_incr={incr}",
+            age = self.secs(),
+            incr = self.incr()
+        )
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct QueueInfo {
     pub name: String,
     #[serde(alias = "type")]
@@ -132,10 +333,36 @@ pub struct QueueInfo {
     pub unacked: u64,
     #[serde(alias = "messages")]
     pub total: u64,
+    #[serde(default)]
+    pub messages_ready_details: RateContainer,
+    #[serde(default)]
+    pub messages_unacknowledged_details: RateContainer,
     pub vhost: String,
+    #[serde(default)]
+    pub consumers: u64,
+    #[serde(default)]
+    pub node: String,
+    #[serde(default)]
+    pub durable: bool,
+    #[serde(default)]
+    pub auto_delete: bool,
+    #[serde(default)]
+    pub arguments: Map<String, Value>,
+    #[serde(default)]
+    pub memory: u64,
+    #[serde(default)]
+    pub policy: Option<String>,
+    /// Definition of the policies applying to the queue,
+    /// operator policies included.
+    #[serde(default, deserialize_with = "empty_list_as_default")]
+    pub effective_policy_definition: Map<String, Value>,
+    /// When the queue last saw activity, absent
+    /// while it is in use.
+    #[serde(default)]
+    pub idle_since: Option<String>,
 }
 
-#[derive(Deserialize, Debug, Default)]
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
 #[serde(default)]
 pub struct QueueMsgStats {
     pub publish: u64,
@@ -146,45 +373,93 @@ pub struct QueueMsgStats {
     pub ack_details: RateContainer,
 }
 
-impl QueueInfo {
-    pub fn headers<'a>() -> [&'a str; 9] {
-        [
-            "Name",
-            "Type",
-            "State",
-            "Ready",
-            "Unacked",
-            "Total",
-            "Incoming",
-            "Deliver / Get",
-            "Ack",
-        ]
+const QUEUE_COLUMNS: [ColumnDef; 17] = [
+    column("name", "Name", 20, true),
+    column("type", "Type", 8, true),
+    column("state", "State", 8, true),
+    column("ready", "Ready", 8, true),
+    column("unacked", "Unacked", 8, true),
+    column("total", "Total", 8, true),
+    // Drawn by the queues tab from the depths it
+    // has seen, empty anywhere else.
+    column("trend", "Trend", 10, true),
+    column("incoming", "Incoming", 10, true),
+    column("deliver_get", "Deliver / Get", 10, true),
+    column("ack", "Ack", 10, true),
+    column("vhost", "Vhost", 10, false),
+    column("consumers", "Consumers", 8, false),
+    column("node", "Node", 15, false),
+    column("durable", "Durable", 8, false),
+    column("arguments", "Arguments", 20, false),
+    column("memory", "Memory", 10, false),
+    column("policy", "Policy", 10, false),
+];
+
+impl Rowable for QueueInfo {
+    fn columns() -> &'static [ColumnDef] {
+        &QUEUE_COLUMNS
+    }
+
+    fn cell(&self, field: usize) -> String {
+        let stats = &self.message_stats;
+        match QUEUE_COLUMNS[field].key {
+            "name" => self.name.clone(),
+            "type" => self.t.clone(),
+            "state" => self.state.clone(),
+            "ready" => self.ready.to_string(),
+            "unacked" => self.unacked.to_string(),
+            "total" => self.total.to_string(),
+            "incoming" => stats.publish_details.rate.to_string().to_rate(),
+            "deliver_get" => stats.deliver_get_details.rate.to_string().to_rate(),
+            "ack" => stats.ack_details.rate.to_string().to_rate(),
+            "vhost" => self.vhost.clone(),
+            "consumers" => self.consumers.to_string(),
+            "node" => self.node.clone(),
+            "durable" => self.durable.to_string(),
+            "arguments" => self
+                .arguments
+                .iter()
+                .map(|(k, v)| match v {
+                    Value::String(s) => format!("{}={}", k, s),
+                    v => format!("{}={}", k, v),
+                })
+                .collect::<Vec<_>>()
+                .join(", "),
+            "memory" => human_bytes(self.memory),
+            "policy" => self.policy.clone().unwrap_or_default(),
+            _ => String::new(),
+        }
     }
 }
 
-impl Rowable for QueueInfo {
-    fn to_row(&self) -> Vec<String> {
-        vec![
-            self.name.clone(),
-            self.t.clone(),
-            self.state.clone(),
-            self.ready.to_string(),
-            self.unacked.to_string(),
-            self.total.to_string(),
-            self.message_stats.ack_details.rate.to_string().to_rate(),
-            self.message_stats
-                .deliver_get_details
-                .rate
-                .to_string()
-                .to_rate(),
-            self.message_stats.ack_details.rate.to_string().to_rate(),
-        ]
+impl Sortable for QueueInfo {
+    fn sort_value(&self, field: usize) -> SortValue {
+        let stats = &self.message_stats;
+        match QUEUE_COLUMNS[field].key {
+            "ready" => SortValue::Number(self.ready as f64),
+            "unacked" => SortValue::Number(self.unacked as f64),
+            "total" => SortValue::Number(self.total as f64),
+            "incoming" => SortValue::Number(stats.publish_details.rate),
+            "deliver_get" => SortValue::Number(stats.deliver_get_details.rate),
+            "ack" => SortValue::Number(stats.ack_details.rate),
+            "consumers" => SortValue::Number(self.consumers as f64),
+            "memory" => SortValue::Number(self.memory as f64),
+            _ => SortValue::Text(self.cell(field).to_lowercase()),
+        }
     }
 }
 
-#[derive(Serialize, Debug)]
+impl Identifiable for QueueInfo {
+    type Id = (String, String);
+
+    fn id(&self) -> Self::Id {
+        (self.vhost.clone(), self.name.clone())
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
 pub struct PayloadPost {
-    pub properties: HashMap<String, String>,
+    pub properties: Map<String, Value>,
     pub routing_key: String,
     pub payload: String,
     #[serde(rename = "payload_encoding")]
@@ -194,7 +469,7 @@ pub struct PayloadPost {
 impl Default for PayloadPost {
     fn default() -> Self {
         Self {
-            properties: HashMap::new(),
+            properties: Map::new(),
             routing_key: "".to_string(),
             payload: "".to_string(),
             encoding: "string".to_string(),
@@ -212,6 +487,35 @@ impl PayloadPost {
         self.payload = payload;
         self
     }
+
+    pub fn properties(mut self, properties: Map<String, Value>) -> Self {
+        self.properties = properties;
+        self
+    }
+
+    pub fn encoding(mut self, encoding: String) -> Self {
+        self.encoding = encoding;
+        self
+    }
+}
+
+/// What a call changing the broker did.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Effect {
+    #[default]
+    Applied,
+    /// Only shown as the request it would send.
+    DryRun,
+}
+
+/// Response of the publish endpoint. `routed` is false
+/// when the broker accepted the message but no queue
+/// was bound to receive it.
+#[derive(Deserialize, Debug)]
+pub struct PublishResponse {
+    pub routed: bool,
+    #[serde(skip)]
+    pub effect: Effect,
 }
 
 #[derive(Serialize, Debug)]
@@ -221,6 +525,23 @@ pub struct MQMessageGetBody {
     encoding: MQEncoding,
 }
 
+impl MQMessageGetBody {
+    pub fn count(mut self, count: u64) -> Self {
+        self.count = count;
+        self
+    }
+
+    pub fn ackmode(mut self, ackmode: Ackmode) -> Self {
+        self.ackmode = ackmode;
+        self
+    }
+
+    /// Whether the messages got stay in the queue.
+    pub fn requeues(&self) -> bool {
+        self.ackmode.requeues()
+    }
+}
+
 impl Default for MQMessageGetBody {
     fn default() -> Self {
         Self {
@@ -231,11 +552,25 @@ impl Default for MQMessageGetBody {
     }
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 pub struct MQMessage {
     pub payload_bytes: u64,
     pub redelivered: bool,
     pub exchange: String,
     pub routing_key: String,
     pub payload: String,
+    /// Either "string" or "base64" for payloads
+    /// that are not valid UTF-8.
+    #[serde(default)]
+    pub payload_encoding: String,
+    // the API sends an empty array rather than
+    // an object when a message has no properties.
+    #[serde(default)]
+    pub properties: Value,
+}
+
+impl MQMessage {
+    pub fn content_type(&self) -> Option<&str> {
+        self.properties.get("content_type")?.as_str()
+    }
 }
@@ -0,0 +1,423 @@
+use crate::{
+    models::{column, ExchangeBindings, ExchangeInfo, Policy},
+    ColumnDef, Rowable,
+};
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// What routing looks at in a message.
+pub struct Message {
+    pub routing_key: String,
+    pub headers: Map<String, Value>,
+}
+
+/// Headers written as `key=value` pairs separated by commas.
+pub fn parse_headers(text: &str) -> Result<Map<String, Value>, String> {
+    let mut headers = Map::new();
+    for pair in text.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        match pair.split_once('=') {
+            Some((k, v)) if !k.trim().is_empty() => {
+                headers.insert(k.trim().to_string(), Value::String(v.trim().to_string()));
+            }
+            _ => return Err(format!("{} is not a key=value header", pair)),
+        }
+    }
+    Ok(headers)
+}
+
+/// A queue the message reaches, along with the exchanges
+/// it goes through, the one published to first.
+#[derive(Serialize, Clone, Debug)]
+pub struct Delivery {
+    pub queue: String,
+    pub via: Vec<String>,
+}
+
+/// An exchange on the way whose routing is not known
+/// here, such as a plugin's exchange type.
+#[derive(Serialize, Clone, Debug)]
+pub struct Unsupported {
+    pub exchange: String,
+    pub reason: String,
+}
+
+#[derive(Serialize, Default, Debug)]
+pub struct Outcome {
+    pub deliveries: Vec<Delivery>,
+    pub unsupported: Vec<Unsupported>,
+}
+
+fn exchange_name(name: &str) -> String {
+    match name {
+        "" => "(default)".to_string(),
+        n => n.to_string(),
+    }
+}
+
+fn words_match(pattern: &[&str], key: &[&str]) -> bool {
+    match (pattern.split_first(), key.split_first()) {
+        (None, None) => true,
+        (Some((&"#", rest)), _) => {
+            words_match(rest, key) || (!key.is_empty() && words_match(pattern, &key[1..]))
+        }
+        (Some((&"*", rest)), Some((_, key_rest))) => words_match(rest, key_rest),
+        (Some((p, rest)), Some((k, key_rest))) => p == k && words_match(rest, key_rest),
+        _ => false,
+    }
+}
+
+/// Whether a topic binding key matches a routing key, `*`
+/// standing for exactly one dot separated word and `#`
+/// for any number of them.
+pub fn topic_matches(pattern: &str, routing_key: &str) -> bool {
+    let pattern: Vec<_> = pattern.split('.').collect();
+    let key: Vec<_> = routing_key.split('.').collect();
+    words_match(&pattern, &key)
+}
+
+fn same_value(expected: &Value, actual: &Value) -> bool {
+    match (expected, actual) {
+        // Headers typed in are text, arguments may not be.
+        (e, Value::String(a)) if !e.is_string() => {
+            serde_json::from_str::<Value>(a).is_ok_and(|a| *e == a)
+        }
+        (e, a) => e == a,
+    }
+}
+
+/// Whether headers match the arguments of a headers binding,
+/// all of them or any of them as `x-match` says. Arguments
+/// starting with `x-` only count with `all-with-x` and
+/// `any-with-x`.
+pub fn headers_match(arguments: &Map<String, Value>, headers: &Map<String, Value>) -> bool {
+    let x_match = arguments
+        .get("x-match")
+        .and_then(Value::as_str)
+        .unwrap_or("all");
+    let with_x = x_match.ends_with("-with-x");
+    let mut expected = arguments
+        .iter()
+        .filter(|(k, _)| *k != "x-match" && (with_x || !k.starts_with("x-")));
+    let matches = |(k, v): (&String, &Value)| headers.get(k).is_some_and(|h| same_value(v, h));
+    if x_match.starts_with("any") {
+        expected.any(matches)
+    } else {
+        expected.all(matches)
+    }
+}
+
+/// Whether a binding of an exchange of type `kind` takes
+/// the message, None for types not simulated here.
+fn binding_matches(kind: &str, binding: &ExchangeBindings, message: &Message) -> Option<bool> {
+    match kind {
+        "direct" => Some(binding.routing_key == message.routing_key),
+        "fanout" => Some(true),
+        "topic" => Some(topic_matches(&binding.routing_key, &message.routing_key)),
+        "headers" => Some(headers_match(&binding.arguments, &message.headers)),
+        _ => None,
+    }
+}
+
+struct Router<'a> {
+    vhost: &'a str,
+    message: &'a Message,
+    exchanges: &'a [ExchangeInfo],
+    bindings: &'a [ExchangeBindings],
+    policies: &'a [Policy],
+    visited: Vec<String>,
+    outcome: Outcome,
+}
+
+impl<'a> Router<'a> {
+    /// The alternate exchange of `info`, from its arguments
+    /// or else from its policy, as the broker gives
+    /// arguments precedence.
+    fn alternate(&self, info: &'a ExchangeInfo) -> Option<&'a str> {
+        let key = "alternate-exchange";
+        let (vhost, policies) = (self.vhost, self.policies);
+        let from_policy = || {
+            let name = info.policy.as_deref()?;
+            policies
+                .iter()
+                .find(|p| p.vhost == vhost && p.name == name)?
+                .definition
+                .get(key)
+        };
+        info.arguments
+            .get(key)
+            .or_else(from_policy)
+            .and_then(Value::as_str)
+    }
+
+    fn route(&mut self, exchange: &str, via: &mut Vec<String>) {
+        // An exchange routes a message once, however
+        // many ways lead to it.
+        if self.visited.iter().any(|e| e == exchange) {
+            return;
+        }
+        self.visited.push(exchange.to_string());
+        let info = self
+            .exchanges
+            .iter()
+            .find(|e| e.vhost == self.vhost && e.name == exchange);
+        let Some(info) = info else {
+            self.outcome.unsupported.push(Unsupported {
+                exchange: exchange_name(exchange),
+                reason: "no such exchange".to_string(),
+            });
+            return;
+        };
+        let mut matching = Vec::new();
+        for b in self
+            .bindings
+            .iter()
+            .filter(|b| b.vhost == self.vhost && b.source == exchange)
+        {
+            match binding_matches(&info.t, b, self.message) {
+                Some(true) => matching.push(b),
+                Some(false) => {}
+                None => {
+                    self.outcome.unsupported.push(Unsupported {
+                        exchange: exchange_name(exchange),
+                        reason: format!("{} exchanges are not simulated", info.t),
+                    });
+                    return;
+                }
+            }
+        }
+        via.push(exchange_name(exchange));
+        for b in &matching {
+            if b.dest_type == "exchange" {
+                self.route(&b.dest, via);
+            } else if !self.outcome.deliveries.iter().any(|d| d.queue == b.dest) {
+                self.outcome.deliveries.push(Delivery {
+                    queue: b.dest.clone(),
+                    via: via.clone(),
+                });
+            }
+        }
+        // Messages nothing matched go to the
+        // alternate exchange, if there is one.
+        if matching.is_empty() {
+            if let Some(alternate) = self.alternate(info) {
+                self.route(alternate, via);
+            }
+        }
+        via.pop();
+    }
+}
+
+/// Which queues of `vhost` a message published to `exchange`
+/// would reach, following exchange to exchange bindings and
+/// alternate exchanges, set in arguments or `policies`.
+pub fn route(
+    vhost: &str,
+    exchange: &str,
+    message: &Message,
+    exchanges: &[ExchangeInfo],
+    bindings: &[ExchangeBindings],
+    policies: &[Policy],
+) -> Outcome {
+    let mut router = Router {
+        vhost,
+        message,
+        exchanges,
+        bindings,
+        policies,
+        visited: Vec::new(),
+        outcome: Outcome::default(),
+    };
+    router.route(exchange, &mut Vec::new());
+    router.outcome
+}
+
+const DELIVERY_COLUMNS: [ColumnDef; 2] = [
+    column("queue", "Queue", 30, true),
+    column("via", "Via", 70, true),
+];
+
+impl Rowable for Delivery {
+    fn columns() -> &'static [ColumnDef] {
+        &DELIVERY_COLUMNS
+    }
+
+    fn cell(&self, field: usize) -> String {
+        match DELIVERY_COLUMNS[field].key {
+            "queue" => self.queue.clone(),
+            "via" => self.via.join(" → "),
+            _ => String::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{headers_match, route, topic_matches, Message};
+
+    use serde_json::{json, Map, Value};
+
+    fn map(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(m) => m,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn star_is_exactly_one_word() {
+        assert!(topic_matches("a.*", "a.b"));
+        assert!(!topic_matches("a.*", "a"));
+        assert!(!topic_matches("a.*", "a.b.c"));
+        assert!(topic_matches("*.b.*", "a.b.c"));
+    }
+
+    #[test]
+    fn hash_is_any_number_of_words() {
+        assert!(topic_matches("a.#", "a"));
+        assert!(topic_matches("a.#", "a.b.c"));
+        assert!(topic_matches("#.c", "c"));
+        assert!(topic_matches("a.#.c", "a.c"));
+        assert!(topic_matches("a.#.c", "a.b.b.c"));
+        assert!(!topic_matches("a.#.c", "a.b.d"));
+        assert!(topic_matches("#", ""));
+        assert!(topic_matches("#", "a.b"));
+    }
+
+    #[test]
+    fn plain_words_match_exactly() {
+        assert!(topic_matches("a.b", "a.b"));
+        assert!(!topic_matches("a.b", "a.bc"));
+        assert!(!topic_matches("a", "a.b"));
+    }
+
+    #[test]
+    fn headers_match_all_by_default() {
+        let arguments = map(json!({"a": "1", "b": "2"}));
+        assert!(headers_match(
+            &arguments,
+            &map(json!({"a": "1", "b": "2", "c": "3"}))
+        ));
+        assert!(!headers_match(&arguments, &map(json!({"a": "1"}))));
+        let all = map(json!({"x-match": "all", "a": "1", "b": "2"}));
+        assert!(!headers_match(&all, &map(json!({"a": "1", "b": "3"}))));
+    }
+
+    #[test]
+    fn headers_match_any() {
+        let arguments = map(json!({"x-match": "any", "a": "1", "b": "2"}));
+        assert!(headers_match(&arguments, &map(json!({"b": "2"}))));
+        assert!(!headers_match(
+            &arguments,
+            &map(json!({"a": "2", "c": "1"}))
+        ));
+    }
+
+    #[test]
+    fn x_arguments_only_count_with_x() {
+        let all = map(json!({"x-match": "all", "a": "1", "x-b": "2"}));
+        assert!(headers_match(&all, &map(json!({"a": "1"}))));
+        let all_with_x = map(json!({"x-match": "all-with-x", "a": "1", "x-b": "2"}));
+        assert!(!headers_match(&all_with_x, &map(json!({"a": "1"}))));
+        assert!(headers_match(
+            &all_with_x,
+            &map(json!({"a": "1", "x-b": "2"}))
+        ));
+        let any = map(json!({"x-match": "any", "x-b": "2"}));
+        assert!(!headers_match(&any, &map(json!({"x-b": "2"}))));
+        let any_with_x = map(json!({"x-match": "any-with-x", "x-b": "2"}));
+        assert!(headers_match(&any_with_x, &map(json!({"x-b": "2"}))));
+    }
+
+    #[test]
+    fn typed_arguments_match_headers_typed_in() {
+        let arguments = map(json!({"n": 1, "flag": true}));
+        assert!(headers_match(
+            &arguments,
+            &map(json!({"n": "1", "flag": "true"}))
+        ));
+        assert!(!headers_match(
+            &arguments,
+            &map(json!({"n": "2", "flag": "true"}))
+        ));
+    }
+
+    #[test]
+    fn unrouted_messages_go_to_the_policy_alternate_exchange() {
+        let exchange = |name: &str, t: &str, arguments: Value, policy: Value| {
+            json!({
+                "auto_delete": false,
+                "durable": true,
+                "internal": false,
+                "name": name,
+                "type": t,
+                "user_who_performed_action": "guest",
+                "vhost": "/",
+                "arguments": arguments,
+                "policy": policy,
+            })
+        };
+        let exchanges = serde_json::from_value::<Vec<_>>(json!([
+            exchange("orders", "direct", json!({}), json!("ae")),
+            exchange(
+                "args",
+                "direct",
+                json!({"alternate-exchange": "kept"}),
+                json!("ae")
+            ),
+            exchange("unrouted", "fanout", json!({}), Value::Null),
+            exchange("kept", "fanout", json!({}), Value::Null),
+        ]))
+        .unwrap();
+        let binding = |source: &str, dest: &str| {
+            json!({
+                "source": source,
+                "vhost": "/",
+                "destination": dest,
+                "destination_type": "queue",
+                "routing_key": "",
+                "properties_key": "~",
+            })
+        };
+        let bindings = serde_json::from_value::<Vec<_>>(json!([
+            binding("unrouted", "lost"),
+            binding("kept", "kept"),
+        ]))
+        .unwrap();
+        let policies = serde_json::from_value::<Vec<_>>(json!([{
+            "vhost": "/",
+            "name": "ae",
+            "pattern": ".*",
+            "apply-to": "exchanges",
+            "definition": {"alternate-exchange": "unrouted"},
+            "priority": 0,
+        }]))
+        .unwrap();
+        let message = Message {
+            routing_key: "nowhere".to_string(),
+            headers: Map::new(),
+        };
+        let queues = |exchange| {
+            route("/", exchange, &message, &exchanges, &bindings, &policies)
+                .deliveries
+                .into_iter()
+                .map(|d| (d.queue, d.via))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            queues("orders"),
+            [(
+                "lost".to_string(),
+                vec!["orders".to_string(), "unrouted".to_string()]
+            )]
+        );
+        // Arguments take precedence over policies.
+        assert_eq!(
+            queues("args"),
+            [(
+                "kept".to_string(),
+                vec!["args".to_string(), "kept".to_string()]
+            )]
+        );
+    }
+}
@@ -0,0 +1,203 @@
+use crate::{
+    client::ApiError,
+    models::{
+        Effect, ExchangeBindings, ExchangeInfo, HistoryWindow, MQMessage, MQMessageGetBody,
+        Overview, PayloadPost, Policy, PublishResponse, QueueInfo,
+    },
+    ManagementClient,
+};
+
+use serde_json::Value;
+
+/// Calls that change the state of the broker. Panes
+/// and commands check theirs before making them, to
+/// explain up front why one is disabled.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Action {
+    Publish,
+    Purge,
+    /// Getting messages without requeuing them.
+    Consume,
+    /// Declaring what a definitions file holds.
+    Import,
+}
+
+impl Action {
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Publish => "Publishing",
+            Action::Purge => "Purging",
+            Action::Consume => "Consuming messages",
+            Action::Import => "Importing definitions",
+        }
+    }
+}
+
+/// Ready messages above which purging a queue
+/// needs its name typed, by default.
+const CONFIRM_ABOVE: u64 = 10_000;
+
+/// Which mutating actions are allowed for the
+/// session, and how carefully they are confirmed.
+#[derive(Clone, Debug)]
+pub struct Safety {
+    // Why the session is read-only, if it is.
+    read_only: Option<String>,
+    production: bool,
+    confirm_above: u64,
+}
+
+impl Default for Safety {
+    fn default() -> Self {
+        Self {
+            read_only: None,
+            production: false,
+            confirm_above: CONFIRM_ABOVE,
+        }
+    }
+}
+
+impl Safety {
+    /// Disables every mutating action, `reason` being
+    /// given back whenever one is refused.
+    pub fn read_only(mut self, reason: impl Into<String>) -> Self {
+        self.read_only = Some(reason.into());
+        self
+    }
+
+    /// Marks the broker as a production one, where every
+    /// destructive action needs its target name typed.
+    pub fn production(mut self, production: bool) -> Self {
+        self.production = production;
+        self
+    }
+
+    pub fn confirm_above(mut self, messages: u64) -> Self {
+        self.confirm_above = messages;
+        self
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.is_some()
+    }
+
+    /// Whether `action` may be taken, explaining
+    /// why when it may not.
+    pub fn check(&self, action: Action) -> Result<(), String> {
+        match &self.read_only {
+            Some(reason) => Err(format!("{} is disabled, {}", action.name(), reason)),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether destroying `messages` messages needs the
+    /// name of their queue typed rather than a yes.
+    pub fn needs_typed_confirmation(&self, messages: u64) -> bool {
+        self.production || messages > self.confirm_above
+    }
+}
+
+/// Wraps a client so that, in a read-only session, calls
+/// changing the broker state fail without being sent,
+/// whatever pane or command makes them. Reads, and gets
+/// requeuing the messages, go straight through.
+pub struct ReadOnlyClient<M: ManagementClient> {
+    inner: M,
+    safety: Safety,
+}
+
+impl<M: ManagementClient> ReadOnlyClient<M> {
+    pub fn new(inner: M, safety: Safety) -> Self {
+        Self { inner, safety }
+    }
+
+    fn check(&self, action: Action) -> Result<(), ApiError> {
+        self.safety.check(action).map_err(ApiError::ReadOnly)
+    }
+}
+
+impl<M: ManagementClient> ManagementClient for ReadOnlyClient<M> {
+    fn get_exchange_overview(&self) -> Result<Vec<ExchangeInfo>, ApiError> {
+        self.inner.get_exchange_overview()
+    }
+
+    fn get_exchange_bindings(
+        &self,
+        exch: &ExchangeInfo,
+    ) -> Result<Vec<ExchangeBindings>, ApiError> {
+        self.inner.get_exchange_bindings(exch)
+    }
+
+    fn get_overview(&self) -> Result<Overview, ApiError> {
+        self.inner.get_overview()
+    }
+
+    fn get_overview_history(&self, window: HistoryWindow) -> Result<Overview, ApiError> {
+        self.inner.get_overview_history(window)
+    }
+
+    fn get_queues_info(&self) -> Result<Vec<QueueInfo>, ApiError> {
+        self.inner.get_queues_info()
+    }
+
+    fn get_queue(&self, queue_name: &str, vhost: &str) -> Result<QueueInfo, ApiError> {
+        self.inner.get_queue(queue_name, vhost)
+    }
+
+    fn get_queue_history(
+        &self,
+        queue_name: &str,
+        vhost: &str,
+        window: HistoryWindow,
+    ) -> Result<QueueInfo, ApiError> {
+        self.inner.get_queue_history(queue_name, vhost, window)
+    }
+
+    fn publish(
+        &self,
+        vhost: &str,
+        exchange: &str,
+        body: &PayloadPost,
+    ) -> Result<PublishResponse, ApiError> {
+        self.check(Action::Publish)?;
+        self.inner.publish(vhost, exchange, body)
+    }
+
+    fn get_messages(
+        &self,
+        queue_name: &str,
+        vhost: &str,
+        body: &MQMessageGetBody,
+    ) -> Result<Vec<MQMessage>, ApiError> {
+        if !body.requeues() {
+            self.check(Action::Consume)?;
+        }
+        self.inner.get_messages(queue_name, vhost, body)
+    }
+
+    fn ping(&self) -> Result<(), ApiError> {
+        self.inner.ping()
+    }
+
+    fn purge_queue(&self, queue_name: &str, vhost: &str) -> Result<Effect, ApiError> {
+        self.check(Action::Purge)?;
+        self.inner.purge_queue(queue_name, vhost)
+    }
+
+    fn get_bindings(&self) -> Result<Vec<ExchangeBindings>, ApiError> {
+        self.inner.get_bindings()
+    }
+
+    fn get_policies(&self) -> Result<Vec<Policy>, ApiError> {
+        self.inner.get_policies()
+    }
+
+    fn get_definitions(&self, vhost: Option<&str>) -> Result<Value, ApiError> {
+        self.inner.get_definitions(vhost)
+    }
+
+    fn post_definitions(&self, vhost: Option<&str>, defs: &Value) -> Result<Effect, ApiError> {
+        self.check(Action::Import)?;
+        self.inner.post_definitions(vhost, defs)
+    }
+}
@@ -0,0 +1,223 @@
+use std::collections::VecDeque;
+
+/// Fixed-capacity series of (seconds since the epoch, value)
+/// points, oldest first. Pushing drops the oldest point once
+/// full, and the min and max are kept up to date as points
+/// come and go instead of being searched for.
+pub struct TimeSeries {
+    points: VecDeque<(f64, f64)>,
+    capacity: usize,
+    // Sequence number of the front point, so that
+    // extremes know when they leave the series.
+    head: u64,
+    // Candidate extremes, (sequence number, value). The
+    // front is the current extreme, and later ones take
+    // over as the points before them are dropped.
+    maxima: VecDeque<(u64, f64)>,
+    minima: VecDeque<(u64, f64)>,
+}
+
+impl TimeSeries {
+    pub fn with_capacity(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            points: VecDeque::with_capacity(capacity),
+            capacity,
+            head: 0,
+            maxima: VecDeque::new(),
+            minima: VecDeque::new(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Appends a point, `t` being no earlier
+    /// than the last one.
+    pub fn push(&mut self, t: f64, value: f64) {
+        if self.points.len() == self.capacity {
+            self.pop_front();
+        }
+        let seq = self.head + self.points.len() as u64;
+        self.points.push_back((t, value));
+        while self.maxima.back().is_some_and(|m| m.1 <= value) {
+            self.maxima.pop_back();
+        }
+        self.maxima.push_back((seq, value));
+        while self.minima.back().is_some_and(|m| m.1 >= value) {
+            self.minima.pop_back();
+        }
+        self.minima.push_back((seq, value));
+    }
+
+    fn pop_front(&mut self) {
+        if self.points.pop_front().is_none() {
+            return;
+        }
+        if self.maxima.front().is_some_and(|m| m.0 == self.head) {
+            self.maxima.pop_front();
+        }
+        if self.minima.front().is_some_and(|m| m.0 == self.head) {
+            self.minima.pop_front();
+        }
+        self.head += 1;
+    }
+
+    /// Drops the points older than `t`.
+    pub fn retain_since(&mut self, t: f64) {
+        while self.points.front().is_some_and(|p| p.0 < t) {
+            self.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+        self.maxima.clear();
+        self.minima.clear();
+        self.head = 0;
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        self.maxima.front().map(|m| m.1)
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        self.minima.front().map(|m| m.1)
+    }
+
+    pub fn first(&self) -> Option<(f64, f64)> {
+        self.points.front().copied()
+    }
+
+    pub fn last(&self) -> Option<(f64, f64)> {
+        self.points.back().copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (f64, f64)> + '_ {
+        self.points.iter().copied()
+    }
+
+    /// The points reduced to at most `n`, keeping the
+    /// highest value of each run of points so that peaks
+    /// survive. Ready to feed a `tui` dataset.
+    pub fn downsample(&self, n: usize) -> Vec<(f64, f64)> {
+        let n = n.max(1);
+        if self.points.len() <= n {
+            return self.iter().collect();
+        }
+        let bucket = self.points.len().div_ceil(n);
+        let (front, back) = self.points.as_slices();
+        let mut out = Vec::with_capacity(n);
+        let mut current: Option<(f64, f64)> = None;
+        for (i, &(t, v)) in front.iter().chain(back).enumerate() {
+            current = match current {
+                Some(c) if c.1 >= v => Some(c),
+                _ => Some((t, v)),
+            };
+            if (i + 1) % bucket == 0 {
+                out.extend(current.take());
+            }
+        }
+        out.extend(current);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TimeSeries;
+
+    fn values(s: &TimeSeries) -> Vec<f64> {
+        s.iter().map(|p| p.1).collect()
+    }
+
+    #[test]
+    fn drops_the_oldest_point_once_full() {
+        let mut s = TimeSeries::with_capacity(3);
+        for (t, v) in [(1., 10.), (2., 20.), (3., 30.), (4., 40.)] {
+            s.push(t, v);
+        }
+        assert_eq!(values(&s), [20., 30., 40.]);
+        assert_eq!(s.first(), Some((2., 20.)));
+        assert_eq!(s.last(), Some((4., 40.)));
+    }
+
+    #[test]
+    fn holds_at_least_one_point() {
+        let mut s = TimeSeries::with_capacity(0);
+        s.push(1., 1.);
+        s.push(2., 2.);
+        assert_eq!(s.capacity(), 1);
+        assert_eq!(values(&s), [2.]);
+    }
+
+    #[test]
+    fn extremes_follow_the_points_in_the_series() {
+        let mut s = TimeSeries::with_capacity(4);
+        let mut seed = 7u64;
+        let mut pushed = Vec::new();
+        for t in 0..200 {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            let v = ((seed >> 33) % 50) as f64;
+            s.push(t as f64, v);
+            pushed.push(v);
+            let window = &pushed[pushed.len().saturating_sub(4)..];
+            let max = window.iter().cloned().fold(f64::MIN, f64::max);
+            let min = window.iter().cloned().fold(f64::MAX, f64::min);
+            assert_eq!(s.max(), Some(max), "max after {:?}", window);
+            assert_eq!(s.min(), Some(min), "min after {:?}", window);
+        }
+    }
+
+    #[test]
+    fn extremes_survive_equal_values_leaving() {
+        let mut s = TimeSeries::with_capacity(2);
+        s.push(1., 5.);
+        s.push(2., 5.);
+        s.push(3., 1.);
+        assert_eq!(s.max(), Some(5.));
+        assert_eq!(s.min(), Some(1.));
+        s.push(4., 3.);
+        assert_eq!(s.max(), Some(3.));
+    }
+
+    #[test]
+    fn retain_since_drops_older_points_and_their_extremes() {
+        let mut s = TimeSeries::with_capacity(10);
+        for (t, v) in [(1., 9.), (2., 1.), (3., 4.), (4., 6.)] {
+            s.push(t, v);
+        }
+        s.retain_since(3.);
+        assert_eq!(values(&s), [4., 6.]);
+        assert_eq!(s.max(), Some(6.));
+        assert_eq!(s.min(), Some(4.));
+        s.retain_since(10.);
+        assert_eq!(s.first(), None);
+        assert_eq!(s.max(), None);
+        assert_eq!(s.min(), None);
+    }
+
+    #[test]
+    fn clear_empties_the_series() {
+        let mut s = TimeSeries::with_capacity(3);
+        s.push(1., 1.);
+        s.push(2., 2.);
+        s.clear();
+        assert_eq!(s.last(), None);
+        assert_eq!(s.max(), None);
+        s.push(3., 3.);
+        assert_eq!(values(&s), [3.]);
+        assert_eq!(s.min(), Some(3.));
+    }
+
+    #[test]
+    fn downsample_keeps_the_peak_of_each_bucket() {
+        let mut s = TimeSeries::with_capacity(10);
+        for (t, v) in [1., 8., 2., 3., 1., 9., 4.].into_iter().enumerate() {
+            s.push(t as f64, v);
+        }
+        assert_eq!(s.downsample(10).len(), 7);
+        assert_eq!(s.downsample(3), [(1., 8.), (5., 9.), (6., 4.)]);
+    }
+}
@@ -0,0 +1,754 @@
+use crate::{
+    client::{ApiError, Client},
+    config::Profile,
+    definitions::{self, Change, Item, Kind},
+    models::{ExchangeBindings, QueueInfo},
+    ManagementClient,
+};
+
+use std::{collections::HashSet, error::Error, fs, path::Path};
+
+use serde_json::{json, Value};
+
+/// Fields compared for each kind, nested ones included,
+/// leaving out what only some brokers or exports carry.
+/// Bindings have none, their arguments being part of
+/// what identifies them.
+fn compared_fields(kind: Kind) -> &'static [&'static str] {
+    match kind {
+        Kind::Policy => &["pattern", "apply-to", "definition", "priority"],
+        Kind::Exchange => &["type", "durable", "auto_delete", "internal", "arguments"],
+        Kind::Queue => &["durable", "auto_delete", "arguments"],
+        _ => &[],
+    }
+}
+
+/// Exchanges, queues, bindings and policies of the broker
+/// behind `client`, shaped as a definitions file. The default
+/// and `amq.` exchanges, and the bindings of the default
+/// exchange, exist on every broker and are left out as
+/// exports leave them out.
+pub fn fetch<M: ManagementClient>(client: &M) -> Result<Value, ApiError> {
+    let exchanges: Vec<_> = client
+        .get_exchange_overview()?
+        .into_iter()
+        .filter(|e| !e.name.is_empty() && !e.name.starts_with("amq."))
+        .map(|e| {
+            json!({
+                "name": e.name,
+                "vhost": e.vhost,
+                "type": e.t,
+                "durable": e.durable,
+                "auto_delete": e.auto_delete,
+                "internal": e.internal,
+                "arguments": e.arguments,
+            })
+        })
+        .collect();
+    let queues: Vec<_> = client
+        .get_queues_info()?
+        .into_iter()
+        .map(|q| {
+            json!({
+                "name": q.name,
+                "vhost": q.vhost,
+                "durable": q.durable,
+                "auto_delete": q.auto_delete,
+                "arguments": q.arguments,
+            })
+        })
+        .collect();
+    let bindings: Vec<_> = client
+        .get_bindings()?
+        .into_iter()
+        .filter(|b| !b.source.is_empty())
+        .map(|b| {
+            json!({
+                "source": b.source,
+                "vhost": b.vhost,
+                "destination": b.dest,
+                "destination_type": b.dest_type,
+                "routing_key": b.routing_key,
+                "arguments": b.arguments,
+            })
+        })
+        .collect();
+    let policies: Vec<_> = client
+        .get_policies()?
+        .into_iter()
+        .map(|p| {
+            json!({
+                "vhost": p.vhost,
+                "name": p.name,
+                "pattern": p.pattern,
+                "apply-to": p.apply_to,
+                "definition": p.definition,
+                "priority": p.priority,
+            })
+        })
+        .collect();
+    Ok(json!({
+        "exchanges": exchanges,
+        "queues": queues,
+        "bindings": bindings,
+        "policies": policies,
+    }))
+}
+
+/// The topology of the broker of another profile.
+pub fn of_profile(profile: &Profile) -> Result<Value, ApiError> {
+    fetch(&Client::from_profile(profile))
+}
+
+/// The topology held by a definitions file.
+pub fn of_file(path: &Path) -> Result<Value, Box<dyn Error>> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let defs = serde_json::from_str(&contents).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(defs)
+}
+
+fn topology_items(defs: &Value, vhost: Option<&str>) -> Vec<Item> {
+    let mut items = definitions::items(defs, vhost);
+    items.retain(|i| i.kind.in_vhost() && vhost.is_none_or(|v| i.vhost == v));
+    for item in &mut items {
+        let compared = compared_fields(item.kind);
+        item.fields.retain(|name, value| {
+            // Empty arguments are the same as none.
+            let empty = matches!(value, Value::Object(o) if o.is_empty());
+            let known = compared.iter().any(|f| {
+                name == f
+                    || name
+                        .strip_prefix(f)
+                        .is_some_and(|rest| rest.starts_with('.'))
+            });
+            known && !empty
+        });
+    }
+    items
+}
+
+/// What differs in `current` from `reference`, within `vhost`
+/// when given. Objects only in `current` are added, those
+/// only in `reference` removed.
+pub fn compare(reference: &Value, current: &Value, vhost: Option<&str>) -> Vec<Change> {
+    definitions::diff(
+        &topology_items(reference, vhost),
+        &topology_items(current, vhost),
+    )
+}
+
+/// An exchange or a queue, within a vhost.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Node {
+    Exchange(String),
+    Queue(String),
+}
+
+impl Node {
+    pub fn name(&self) -> &str {
+        match self {
+            Node::Exchange(n) if n.is_empty() => "(default)",
+            Node::Exchange(n) | Node::Queue(n) => n,
+        }
+    }
+}
+
+/// How messages go from one node to the next.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Link {
+    /// A binding, with its arguments for headers exchanges.
+    Binding {
+        routing_key: String,
+        arguments: String,
+    },
+    /// Dead-lettering set for the queue, the routing
+    /// key being the message's own when absent.
+    DeadLetter { routing_key: Option<String> },
+}
+
+impl Link {
+    pub fn describe(&self) -> String {
+        match self {
+            Link::Binding {
+                routing_key,
+                arguments,
+            } if arguments.is_empty() => format!("[{}]", routing_key),
+            Link::Binding {
+                routing_key,
+                arguments,
+            } => format!("[{}] {}", routing_key, arguments),
+            Link::DeadLetter {
+                routing_key: Some(k),
+            } => format!("dead-letter [{}]", k),
+            Link::DeadLetter { routing_key: None } => "dead-letter".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Edge {
+    pub from: Node,
+    pub to: Node,
+    pub link: Link,
+}
+
+/// A dead-lettering setting of `queue`, from its arguments
+/// or else from the policies applying to it, as the broker
+/// gives arguments precedence.
+fn dead_letter<'a>(queue: &'a QueueInfo, key: &str) -> Option<&'a str> {
+    queue
+        .arguments
+        .get(&format!("x-{}", key))
+        .or_else(|| queue.effective_policy_definition.get(key))
+        .and_then(Value::as_str)
+}
+
+/// Links between the exchanges and queues of `vhost`: its
+/// bindings, and dead-lettering set by queue arguments or
+/// policies. The bindings of the default exchange, one per
+/// queue, are left out. Dead-lettering through it with a
+/// routing key goes straight to the queue of that name.
+pub fn edges(vhost: &str, bindings: &[ExchangeBindings], queues: &[QueueInfo]) -> Vec<Edge> {
+    let mut edges: Vec<_> = bindings
+        .iter()
+        .filter(|b| b.vhost == vhost && !b.source.is_empty())
+        .map(|b| Edge {
+            from: Node::Exchange(b.source.clone()),
+            to: match b.dest_type.as_str() {
+                "exchange" => Node::Exchange(b.dest.clone()),
+                _ => Node::Queue(b.dest.clone()),
+            },
+            link: Link::Binding {
+                routing_key: b.routing_key.clone(),
+                arguments: b
+                    .arguments
+                    .iter()
+                    .map(|(k, v)| match v {
+                        Value::String(s) => format!("{}={}", k, s),
+                        v => format!("{}={}", k, v),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(","),
+            },
+        })
+        .collect();
+    for q in queues.iter().filter(|q| q.vhost == vhost) {
+        let Some(dlx) = dead_letter(q, "dead-letter-exchange") else {
+            continue;
+        };
+        let routing_key = dead_letter(q, "dead-letter-routing-key").map(str::to_string);
+        let to = match (dlx, &routing_key) {
+            ("", Some(k)) => Node::Queue(k.clone()),
+            _ => Node::Exchange(dlx.to_string()),
+        };
+        edges.push(Edge {
+            from: Node::Queue(q.name.clone()),
+            to,
+            link: Link::DeadLetter { routing_key },
+        });
+    }
+    edges.sort_by(|a, b| (&a.from, &a.link, &a.to).cmp(&(&b.from, &b.link, &b.to)));
+    edges
+}
+
+/// A line of the routing tree: a node, reached from the
+/// line `parent` through `link`, drawn after `prefix`.
+#[derive(Clone, Debug)]
+pub struct TreeLine {
+    pub prefix: String,
+    pub parent: Option<usize>,
+    pub link: Option<Link>,
+    pub node: Node,
+    /// Whether the node is already on the way here,
+    /// messages going round in circles.
+    pub cycle: bool,
+    /// Whether what follows the node is drawn
+    /// further up, and left out here.
+    pub repeat: bool,
+}
+
+/// Depth first walk of the edges, drawing what follows
+/// a node once however many ways lead to it.
+struct Walk<'a> {
+    edges: &'a [Edge],
+    path: Vec<Node>,
+    // Nodes whose children are drawn already.
+    expanded: HashSet<Node>,
+    lines: Vec<TreeLine>,
+}
+
+impl Walk<'_> {
+    fn visit(&mut self, mut line: TreeLine, children_prefix: &str) {
+        let node = line.node.clone();
+        let children: Vec<_> = self.edges.iter().filter(|e| e.from == node).collect();
+        line.repeat = !line.cycle && !children.is_empty() && self.expanded.contains(&node);
+        let stop = line.cycle || line.repeat;
+        self.lines.push(line);
+        if stop {
+            return;
+        }
+        self.expanded.insert(node.clone());
+        let parent = self.lines.len() - 1;
+        self.path.push(node);
+        for (i, e) in children.iter().enumerate() {
+            let last = i == children.len() - 1;
+            let (branch, indent) = if last {
+                ("└─ ", "   ")
+            } else {
+                ("├─ ", "│  ")
+            };
+            let child = TreeLine {
+                prefix: format!("{}{}", children_prefix, branch),
+                parent: Some(parent),
+                link: Some(e.link.clone()),
+                node: e.to.clone(),
+                cycle: self.path.contains(&e.to),
+                repeat: false,
+            };
+            self.visit(child, &format!("{}{}", children_prefix, indent));
+        }
+        self.path.pop();
+    }
+}
+
+/// Every path messages can take through `edges`, as a tree
+/// rooted at the exchanges no other exchange routes to.
+/// Exchanges only reachable from a cycle become roots too.
+/// What follows a node is drawn the first time it is
+/// reached only, later lines being marked `repeat`.
+pub fn tree(edges: &[Edge]) -> Vec<TreeLine> {
+    let mut sources: Vec<_> = edges
+        .iter()
+        .filter(|e| matches!(e.from, Node::Exchange(_)))
+        .map(|e| e.from.clone())
+        .collect();
+    sources.sort();
+    sources.dedup();
+    let routed_to = |n: &Node| {
+        edges
+            .iter()
+            .any(|e| &e.to == n && matches!(e.from, Node::Exchange(_)))
+    };
+    let (mut roots, mut rest): (Vec<_>, Vec<_>) = sources.into_iter().partition(|n| !routed_to(n));
+    let mut walk = Walk {
+        edges,
+        path: Vec::new(),
+        expanded: HashSet::new(),
+        lines: Vec::new(),
+    };
+    loop {
+        for root in roots {
+            let line = TreeLine {
+                prefix: String::new(),
+                parent: None,
+                link: None,
+                node: root,
+                cycle: false,
+                repeat: false,
+            };
+            walk.visit(line, "");
+        }
+        rest.retain(|n| !walk.expanded.contains(n));
+        if rest.is_empty() {
+            return walk.lines;
+        }
+        roots = vec![rest.remove(0)];
+    }
+}
+
+/// Indexes of the lines on the way from
+/// the root to the line at `index`.
+pub fn path(lines: &[TreeLine], index: usize) -> Vec<usize> {
+    let mut path = Vec::new();
+    let mut next = Some(index);
+    while let Some(i) = next.filter(|i| *i < lines.len()) {
+        path.push(i);
+        next = lines[i].parent;
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compare, edges, path, tree, Link, Node, TreeLine};
+    use crate::{
+        definitions::{Change, Kind, Status},
+        models::{ExchangeBindings, QueueInfo},
+    };
+
+    use serde_json::{json, Value};
+
+    fn exchange(name: &str, t: &str) -> Value {
+        json!({"name": name, "vhost": "/", "type": t, "durable": true, "auto_delete": false, "internal": false, "arguments": {}})
+    }
+
+    fn queue(vhost: &str, name: &str, arguments: Value) -> Value {
+        json!({"name": name, "vhost": vhost, "durable": true, "auto_delete": false, "arguments": arguments})
+    }
+
+    fn binding(key: &str, arguments: Value) -> Value {
+        json!({
+            "source": "events",
+            "vhost": "/",
+            "destination": "orders",
+            "destination_type": "queue",
+            "routing_key": key,
+            "arguments": arguments,
+        })
+    }
+
+    fn policy(name: &str, definition: Value) -> Value {
+        json!({"vhost": "/", "name": name, "pattern": "^orders$", "apply-to": "queues", "definition": definition, "priority": 0})
+    }
+
+    fn summary(changes: &[Change]) -> Vec<(Status, Kind, &str)> {
+        changes
+            .iter()
+            .map(|c| (c.status, c.kind, c.name.as_str()))
+            .collect()
+    }
+
+    fn fields(change: &Change) -> Vec<String> {
+        change.fields.iter().map(|f| f.describe()).collect()
+    }
+
+    #[test]
+    fn same_topologies_have_no_changes() {
+        let defs = json!({
+            "exchanges": [exchange("events", "topic")],
+            "queues": [queue("/", "orders", json!({}))],
+            "bindings": [binding("order.*", json!({}))],
+            "policies": [policy("ha", json!({"max-length": 5000}))],
+        });
+        assert!(compare(&defs, &defs, None).is_empty());
+    }
+
+    #[test]
+    fn objects_only_on_one_side_are_added_or_removed() {
+        let reference = json!({
+            "exchanges": [exchange("old", "direct")],
+            "queues": [queue("/", "old", json!({}))],
+            "bindings": [binding("old", json!({}))],
+            "policies": [policy("old", json!({}))],
+        });
+        let current = json!({
+            "exchanges": [exchange("new", "direct")],
+            "queues": [queue("/", "new", json!({}))],
+            "bindings": [binding("new", json!({}))],
+            "policies": [policy("new", json!({}))],
+        });
+        assert_eq!(
+            summary(&compare(&reference, &current, None)),
+            [
+                (Status::Added, Kind::Policy, "new"),
+                (Status::Removed, Kind::Policy, "old"),
+                (Status::Added, Kind::Exchange, "new"),
+                (Status::Removed, Kind::Exchange, "old"),
+                (Status::Added, Kind::Queue, "new"),
+                (Status::Removed, Kind::Queue, "old"),
+                (Status::Added, Kind::Binding, "events -> queue orders [new]"),
+                (
+                    Status::Removed,
+                    Kind::Binding,
+                    "events -> queue orders [old]"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn changed_objects_list_their_fields() {
+        let reference = json!({
+            "exchanges": [exchange("events", "topic")],
+            "queues": [queue("/", "orders", json!({"x-max-length": 1000}))],
+            "policies": [policy("ha", json!({"max-length": 5000}))],
+        });
+        let current = json!({
+            "exchanges": [exchange("events", "fanout")],
+            "queues": [queue("/", "orders", json!({"x-max-length": 2000}))],
+            "policies": [policy("ha", json!({"max-length": 5000, "overflow": "reject-publish"}))],
+        });
+        let changes = compare(&reference, &current, None);
+        assert_eq!(
+            summary(&changes),
+            [
+                (Status::Changed, Kind::Policy, "ha"),
+                (Status::Changed, Kind::Exchange, "events"),
+                (Status::Changed, Kind::Queue, "orders"),
+            ]
+        );
+        assert_eq!(
+            fields(&changes[0]),
+            ["definition.overflow: - -> reject-publish"]
+        );
+        assert_eq!(fields(&changes[1]), ["type: topic -> fanout"]);
+        assert_eq!(
+            fields(&changes[2]),
+            ["arguments.x-max-length: 1000 -> 2000"]
+        );
+    }
+
+    #[test]
+    fn bindings_differing_by_arguments_are_distinct() {
+        let reference =
+            json!({"bindings": [binding("", json!({"x-match": "any", "region": "eu"}))]});
+        let current = json!({"bindings": [binding("", json!({"x-match": "all", "region": "eu"}))]});
+        assert_eq!(
+            summary(&compare(&reference, &current, None)),
+            [
+                (
+                    Status::Added,
+                    Kind::Binding,
+                    r#"events -> queue orders [] {"region":"eu","x-match":"all"}"#
+                ),
+                (
+                    Status::Removed,
+                    Kind::Binding,
+                    r#"events -> queue orders [] {"region":"eu","x-match":"any"}"#
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn fields_some_brokers_leave_out_are_not_compared() {
+        let mut with_extras = queue("/", "orders", json!({}));
+        with_extras["type"] = json!("quorum");
+        let reference = json!({"queues": [queue("/", "orders", json!({}))]});
+        let current = json!({"queues": [with_extras]});
+        assert!(compare(&reference, &current, None).is_empty());
+        // Empty arguments are the same as none.
+        let bare = json!({"queues": [{"name": "orders", "vhost": "/", "durable": true, "auto_delete": false}]});
+        assert!(compare(&reference, &bare, None).is_empty());
+    }
+
+    #[test]
+    fn comparisons_stay_within_the_vhost() {
+        let reference = json!({
+            "vhosts": [{"name": "/"}],
+            "users": [{"name": "ops"}],
+            "queues": [queue("/", "orders", json!({})), queue("prod", "billing", json!({}))],
+        });
+        let current = json!({"queues": [queue("/", "orders", json!({}))]});
+        assert!(compare(&reference, &current, Some("/")).is_empty());
+        assert_eq!(
+            summary(&compare(&reference, &current, None)),
+            [(Status::Removed, Kind::Queue, "billing")]
+        );
+    }
+
+    fn bound(
+        vhost: &str,
+        source: &str,
+        dest: &str,
+        dest_type: &str,
+        key: &str,
+    ) -> ExchangeBindings {
+        serde_json::from_value(json!({
+            "source": source,
+            "vhost": vhost,
+            "destination": dest,
+            "destination_type": dest_type,
+            "routing_key": key,
+            "properties_key": key,
+        }))
+        .unwrap()
+    }
+
+    fn queue_info(name: &str, arguments: Value, policy: Value) -> QueueInfo {
+        serde_json::from_value(json!({
+            "name": name,
+            "type": "classic",
+            "state": "running",
+            "messages_ready": 0,
+            "messages_unacknowledged": 0,
+            "messages": 0,
+            "vhost": "/",
+            "arguments": arguments,
+            "effective_policy_definition": policy,
+        }))
+        .unwrap()
+    }
+
+    /// The lines of the tree as drawn, markers included.
+    fn drawn(lines: &[TreeLine]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|l| {
+                let link = l.link.as_ref().map(|k| format!("{} ", k.describe()));
+                let mark = match (l.cycle, l.repeat) {
+                    (true, _) => " (cycle)",
+                    (_, true) => " (see above)",
+                    _ => "",
+                };
+                format!(
+                    "{}{}{}{}",
+                    l.prefix,
+                    link.unwrap_or_default(),
+                    l.node.name(),
+                    mark
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn default_exchange_and_other_vhosts_are_left_out() {
+        let bindings = [
+            bound("/", "", "orders", "queue", "orders"),
+            bound("/", "events", "orders", "queue", "order.*"),
+            bound("prod", "events", "billing", "queue", "#"),
+        ];
+        let edges = edges("/", &bindings, &[]);
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].from, Node::Exchange("events".to_string()));
+        assert_eq!(edges[0].to, Node::Queue("orders".to_string()));
+    }
+
+    #[test]
+    fn dead_lettering_comes_from_arguments_before_policies() {
+        let queues = [
+            queue_info(
+                "by_arguments",
+                json!({"x-dead-letter-exchange": "dlx", "x-dead-letter-routing-key": "dead"}),
+                json!({"dead-letter-exchange": "ignored"}),
+            ),
+            queue_info(
+                "by_policy",
+                json!({}),
+                json!({"dead-letter-exchange": "audit"}),
+            ),
+            queue_info(
+                "to_a_queue",
+                json!({"x-dead-letter-exchange": ""}),
+                json!({"dead-letter-routing-key": "parking"}),
+            ),
+            queue_info("none", json!({}), json!({})),
+        ];
+        let found: Vec<_> = edges("/", &[], &queues)
+            .into_iter()
+            .map(|e| (e.from, e.to, e.link))
+            .collect();
+        let q = |n: &str| Node::Queue(n.to_string());
+        let x = |n: &str| Node::Exchange(n.to_string());
+        assert_eq!(
+            found,
+            [
+                (
+                    q("by_arguments"),
+                    x("dlx"),
+                    Link::DeadLetter {
+                        routing_key: Some("dead".to_string())
+                    }
+                ),
+                (
+                    q("by_policy"),
+                    x("audit"),
+                    Link::DeadLetter { routing_key: None }
+                ),
+                (
+                    q("to_a_queue"),
+                    q("parking"),
+                    Link::DeadLetter {
+                        routing_key: Some("parking".to_string())
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn dead_letter_exchanges_are_roots_too() {
+        let bindings = [
+            bound("/", "events", "orders", "queue", "order.*"),
+            bound("/", "dlx", "orders.dlq", "queue", ""),
+        ];
+        let queues = [queue_info(
+            "orders",
+            json!({}),
+            json!({"dead-letter-exchange": "dlx"}),
+        )];
+        // Only exchanges routing to it keep it from being one.
+        assert_eq!(
+            drawn(&tree(&edges("/", &bindings, &queues))),
+            [
+                "dlx",
+                "└─ [] orders.dlq",
+                "events",
+                "└─ [order.*] orders",
+                "   └─ dead-letter dlx (see above)",
+            ]
+        );
+    }
+
+    #[test]
+    fn what_follows_a_node_is_drawn_once() {
+        let bindings = [
+            bound("/", "events", "dlx", "exchange", "a"),
+            bound("/", "events", "dlx", "exchange", "b"),
+            bound("/", "events", "orders.dlq", "queue", "c"),
+            bound("/", "dlx", "orders.dlq", "queue", ""),
+        ];
+        assert_eq!(
+            drawn(&tree(&edges("/", &bindings, &[]))),
+            [
+                "events",
+                "├─ [a] dlx",
+                "│  └─ [] orders.dlq",
+                "├─ [b] dlx (see above)",
+                "└─ [c] orders.dlq",
+            ]
+        );
+    }
+
+    #[test]
+    fn cycles_are_marked_and_not_followed() {
+        let bindings = [
+            bound("/", "events", "audit", "exchange", "#"),
+            bound("/", "audit", "events", "exchange", ""),
+            bound("/", "audit", "log", "queue", ""),
+        ];
+        // No exchange is a root, the first one becomes one.
+        assert_eq!(
+            drawn(&tree(&edges("/", &bindings, &[]))),
+            [
+                "audit",
+                "├─ [] events",
+                "│  └─ [#] audit (cycle)",
+                "└─ [] log",
+            ]
+        );
+    }
+
+    #[test]
+    fn exchanges_only_reached_from_cycles_become_roots() {
+        let bindings = [
+            bound("/", "a", "b", "exchange", ""),
+            bound("/", "b", "a", "exchange", ""),
+            bound("/", "c", "d", "exchange", ""),
+            bound("/", "d", "c", "exchange", ""),
+        ];
+        let lines = tree(&edges("/", &bindings, &[]));
+        let roots: Vec<_> = lines
+            .iter()
+            .filter(|l| l.parent.is_none())
+            .map(|l| l.node.name())
+            .collect();
+        assert_eq!(roots, ["a", "c"]);
+    }
+
+    #[test]
+    fn paths_go_from_the_root_to_the_line() {
+        let bindings = [
+            bound("/", "events", "dlx", "exchange", "a"),
+            bound("/", "dlx", "orders.dlq", "queue", ""),
+            bound("/", "other", "orders", "queue", ""),
+        ];
+        let lines = tree(&edges("/", &bindings, &[]));
+        assert_eq!(path(&lines, 2), [0, 1, 2]);
+        assert_eq!(path(&lines, 4), [3, 4]);
+        assert_eq!(path(&lines, 0), [0]);
+        assert!(path(&lines, lines.len()).is_empty());
+    }
+}
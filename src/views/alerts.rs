@@ -0,0 +1,145 @@
+use super::{column_widths, header_column, Drawable, StatefulPane, HIGHLIGHT_SYMBOL};
+use crate::{
+    alerts::{AlertEvent, AlertState},
+    widgets::help::Help,
+    Datatable, Identifiable, Rowable,
+};
+
+use std::sync::mpsc;
+
+use termion::event::{Key, MouseButton, MouseEvent};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Cell, Row, Table},
+    Frame,
+};
+
+const HELP: &str = "The Alerts tab lists the alert rules \
+of the config file that fired, newest first. Rules are \
+checked on every refresh, a rule firing once its condition \
+held for its duration and resolving when it stops holding.
+
+Keys:
+  - h: previous tab
+  - l: next tab
+  - k: previous row
+  - j: next row
+  - s: sort by the next column, S: toggle ascending/descending
+  - click on a header: sort by that column, again to reverse
+  - x: clear the resolved alerts
+  - ?: close the help menu";
+
+pub struct AlertsPane {
+    table: Datatable<AlertEvent>,
+    // Latest event of each (rule, subject), newest first.
+    alerts: Vec<AlertEvent>,
+    // Where the table was last drawn, to map
+    // header clicks to columns.
+    table_area: Rect,
+    data_chan: mpsc::Receiver<AlertEvent>,
+    has_rules: bool,
+    should_show_help: bool,
+}
+
+impl AlertsPane {
+    pub fn new(data_chan: mpsc::Receiver<AlertEvent>, has_rules: bool) -> Self {
+        let mut table = Datatable::default();
+        table.set_columns(AlertEvent::default_columns());
+        Self {
+            table,
+            alerts: Vec::new(),
+            table_area: Rect::default(),
+            data_chan,
+            has_rules,
+            should_show_help: false,
+        }
+    }
+}
+
+impl<B: Backend> Drawable<B> for AlertsPane {
+    fn draw(&mut self, f: &mut Frame<B>, area: Rect) {
+        let rects = Layout::default()
+            .constraints([Constraint::Min(0)].as_ref())
+            .margin(1)
+            .split(area);
+        let header_cells = self
+            .table
+            .headers()
+            .into_iter()
+            .map(|h| Cell::from(h).style(Style::default().fg(Color::Green)));
+        let header = Row::new(header_cells).height(1).bottom_margin(1);
+        let has_selection = self.table.state.selected().is_some();
+        let widths = column_widths(&self.table.widths(), rects[0], has_selection);
+        let rows = self.table.rows().map(|a| {
+            let style = match a.state {
+                AlertState::Firing => Style::default().fg(a.severity.color()),
+                AlertState::Resolved => Style::default().fg(Color::DarkGray),
+            };
+            Row::new(self.table.row(a)).style(style).bottom_margin(1)
+        });
+        let title = match (self.has_rules, self.alerts.is_empty()) {
+            (false, _) => "Alerts (no rules configured)",
+            (true, true) => "Alerts (none fired)",
+            (true, false) => "Alerts",
+        };
+        let t = Table::new(rows)
+            .header(header)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol(HIGHLIGHT_SYMBOL)
+            .widths(&widths);
+        self.table_area = rects[0];
+        f.render_stateful_widget(t, rects[0], &mut self.table.state);
+        if self.should_show_help {
+            let help = Help::new(HELP);
+            help.draw(f, area);
+        }
+    }
+}
+
+impl<B: Backend> StatefulPane<B> for AlertsPane {
+    fn handle_key(&mut self, key: Key) {
+        match key {
+            Key::Char('j') => self.table.next(),
+            Key::Char('k') => self.table.previous(),
+            Key::Char('s') => self.table.cycle_sort(),
+            Key::Char('S') => self.table.toggle_sort_order(),
+            Key::Char('x') => {
+                self.alerts.retain(|a| a.state == AlertState::Firing);
+                self.table.update(self.alerts.clone());
+            }
+            Key::Char('?') => {
+                self.should_show_help = !self.should_show_help;
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_mouse(&mut self, event: MouseEvent) {
+        if self.should_show_help {
+            return;
+        }
+        if let MouseEvent::Press(MouseButton::Left, x, y) = event {
+            let has_selection = self.table.state.selected().is_some();
+            let widths = column_widths(&self.table.widths(), self.table_area, has_selection);
+            let (x, y) = (x.saturating_sub(1), y.saturating_sub(1));
+            if let Some(c) = header_column(self.table_area, &widths, has_selection, x, y) {
+                self.table.sort_by(c);
+            }
+        }
+    }
+
+    fn update(&mut self) {
+        let events: Vec<AlertEvent> = self.data_chan.try_iter().collect();
+        if events.is_empty() {
+            return;
+        }
+        for e in events {
+            self.alerts.retain(|a| a.id() != e.id());
+            self.alerts.insert(0, e);
+        }
+        self.table.update(self.alerts.clone());
+    }
+}
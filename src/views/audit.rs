@@ -0,0 +1,184 @@
+use super::{column_widths, header_column, Drawable, StatefulPane, HIGHLIGHT_SYMBOL};
+use crate::{
+    audit::{read_log, AuditEntry},
+    widgets::{
+        help::Help,
+        search::{highlight_cell, SearchBar},
+    },
+    Datatable, Rowable,
+};
+
+use std::{fs, path::PathBuf};
+
+use termion::event::{Key, MouseButton, MouseEvent};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Cell, Row, Table},
+    Frame,
+};
+
+const HELP: &str = "The Audit tab shows the audit log, where \
+every publish, purge and get made through rabbitui is recorded, \
+newest first. It follows the file as new entries are written, \
+from this session or others.
+
+Keys:
+  - h: previous tab
+  - l: next tab
+  - k: previous row
+  - j: next row
+  - s: sort by the next column, S: toggle ascending/descending
+  - click on a header: sort by that column, again to reverse
+  - /: filter rows, tab switches between substring, glob \
+and regex, return keeps the filter, esc clears it
+  - ?: close the help menu";
+
+pub struct AuditPane {
+    table: Datatable<AuditEntry>,
+    path: Option<PathBuf>,
+    // Size of the log when last read, to
+    // only read it again once it grew.
+    read_len: Option<u64>,
+    // Why the log could not be read, if it could not.
+    error: Option<String>,
+    search: SearchBar,
+    // Where the table was last drawn, to map
+    // header clicks to columns.
+    table_area: Rect,
+    should_show_help: bool,
+}
+
+impl AuditPane {
+    pub fn new(path: Option<PathBuf>) -> Self {
+        let mut table = Datatable::default();
+        table.set_columns(AuditEntry::default_columns());
+        let error = path.is_none().then(|| "no audit log location".to_string());
+        Self {
+            table,
+            path,
+            read_len: None,
+            error,
+            search: SearchBar::default(),
+            table_area: Rect::default(),
+            should_show_help: false,
+        }
+    }
+}
+
+impl<B: Backend> Drawable<B> for AuditPane {
+    fn draw(&mut self, f: &mut Frame<B>, area: Rect) {
+        let search_height = if self.search.is_visible() { 1 } else { 0 };
+        let rects = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(search_height)].as_ref())
+            .margin(1)
+            .split(area);
+        let header_cells = self
+            .table
+            .headers()
+            .into_iter()
+            .map(|h| Cell::from(h).style(Style::default().fg(Color::Green)));
+        let header = Row::new(header_cells).height(1).bottom_margin(1);
+        let has_selection = self.table.state.selected().is_some();
+        let widths = column_widths(&self.table.widths(), rects[0], has_selection);
+        let filter = self.table.filter();
+        let rows = self.table.rows().map(|e| {
+            let cells = self
+                .table
+                .row(e)
+                .into_iter()
+                .map(|c| highlight_cell(c, filter));
+            let color = match e.result.as_str() {
+                "ok" => Color::Reset,
+                _ => Color::Red,
+            };
+            Row::new(cells).style(Style::default().fg(color))
+        });
+        let title = match (&self.error, &self.path) {
+            (Some(e), _) => format!("Audit - {}", e),
+            (None, Some(path)) => format!("Audit - {}", path.display()),
+            (None, None) => "Audit".to_string(),
+        };
+        let t = Table::new(rows)
+            .header(header)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol(HIGHLIGHT_SYMBOL)
+            .widths(&widths);
+        self.table_area = rects[0];
+        f.render_stateful_widget(t, rects[0], &mut self.table.state);
+        if self.search.is_visible() {
+            self.search
+                .draw(f, rects[1], self.table.len(), self.table.total());
+        }
+        if self.should_show_help {
+            let help = Help::new(HELP);
+            help.draw(f, area);
+        }
+    }
+}
+
+impl<B: Backend> StatefulPane<B> for AuditPane {
+    fn handle_key(&mut self, key: Key) {
+        if self.search.is_typing() {
+            if self.search.handle_key(key) {
+                self.table.set_filter(self.search.filter());
+            }
+            return;
+        }
+        match key {
+            Key::Char('j') => self.table.next(),
+            Key::Char('k') => self.table.previous(),
+            Key::Char('s') => self.table.cycle_sort(),
+            Key::Char('S') => self.table.toggle_sort_order(),
+            Key::Char('/') => self.search.open(),
+            Key::Esc if self.search.is_visible() => {
+                self.search.clear();
+                self.table.set_filter(None);
+            }
+            Key::Char('?') => {
+                self.should_show_help = !self.should_show_help;
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_mouse(&mut self, event: MouseEvent) {
+        if self.should_show_help {
+            return;
+        }
+        if let MouseEvent::Press(MouseButton::Left, x, y) = event {
+            let has_selection = self.table.state.selected().is_some();
+            let widths = column_widths(&self.table.widths(), self.table_area, has_selection);
+            let (x, y) = (x.saturating_sub(1), y.saturating_sub(1));
+            if let Some(c) = header_column(self.table_area, &widths, has_selection, x, y) {
+                self.table.sort_by(c);
+            }
+        }
+    }
+
+    fn update(&mut self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let len = fs::metadata(path).map_or(0, |m| m.len());
+        if self.read_len == Some(len) {
+            return;
+        }
+        self.read_len = Some(len);
+        match read_log(path) {
+            Ok(mut entries) => {
+                entries.reverse();
+                self.error = None;
+                self.table.update(entries);
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+
+    fn captures_input(&self) -> bool {
+        self.search.is_typing()
+    }
+}
@@ -0,0 +1,596 @@
+use super::{centered_rect, column_widths, Drawable, StatefulPane, HIGHLIGHT_SYMBOL};
+use crate::{
+    audit::rfc3339,
+    config::{AppConfig, Profile},
+    definitions::{self, Change, Status},
+    models::Effect,
+    safety::{Action, Safety},
+    topology,
+    widgets::{
+        chart::now, confirmation::ConfirmationBox, files::FileNavigator, help::Help,
+        notif::Notifier,
+    },
+    Datatable, ManagementClient, Rowable,
+};
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs, iter,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use serde_json::Value;
+use termion::event::Key;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::Text,
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, Wrap},
+    Frame,
+};
+
+const HELP: &str = "The Definitions tab saves and restores the \
+topology of the broker: vhosts, users, permissions, policies, \
+exchanges, queues and bindings, of the whole broker or of \
+a single vhost.
+
+Keys:
+  - h: previous tab
+  - l: next tab
+  - k: previous scope
+  - j: next scope
+  - r: reload the vhosts
+  - x: export the selected scope into a directory
+  - i: import a definitions file into the selected scope
+  - c: compare the topology with a definitions file
+  - p: compare the topology with the broker of another profile
+  - ?: close the help menu
+
+In the file explorer:
+  - j/k: move
+  - return: open a directory, or pick the file to import
+    or compare with
+  - backspace: go to the parent directory
+  - s: export into the directory shown
+  - esc: close
+
+Before importing, a preview lists what the file would \
+create and what it holds differently from the broker. \
+Return imports after a confirmation, esc cancels.
+
+Comparisons cover exchanges, queues, bindings and policies. \
+Objects only on this broker show as added, those only in \
+the file or on the other broker as removed, and changed \
+ones list their differing fields, other side first.";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Purpose {
+    Export,
+    Import,
+    Compare,
+}
+
+/// A definitions file about to be imported.
+struct Preview {
+    // Vhost imported into, None for the whole broker.
+    scope: Option<String>,
+    path: PathBuf,
+    defs: Value,
+    table: Datatable<Change>,
+    unchanged: usize,
+}
+
+/// How the topology differs from that of a file
+/// or of another profile's broker.
+struct Comparison {
+    against: String,
+    scope: Option<String>,
+    table: Datatable<Change>,
+}
+
+pub struct DefinitionsPane<'a, M>
+where
+    M: ManagementClient,
+{
+    client: Arc<M>,
+    notifier: Notifier,
+    safety: Safety,
+    // The whole broker, then every vhost.
+    scopes: Datatable<Option<String>>,
+    explorer: Option<(FileNavigator, Purpose)>,
+    preview: Option<Preview>,
+    profiles: HashMap<String, Profile>,
+    // Profiles to compare with, while picking one.
+    picker: Option<Datatable<String>>,
+    comparison: Option<Comparison>,
+    confirmation: ConfirmationBox<'a>,
+    should_confirm: bool,
+    should_show_help: bool,
+}
+
+fn scope_label(scope: &Option<String>) -> String {
+    match scope {
+        Some(v) => format!("vhost {}", v),
+        None => "the whole broker".to_string(),
+    }
+}
+
+/// The whole broker, then every vhost that has exchanges,
+/// which every vhost has.
+fn load_scopes<M: ManagementClient>(client: &M) -> Datatable<Option<String>> {
+    let mut vhosts: Vec<_> = client
+        .get_exchange_overview()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|e| e.vhost)
+        .collect();
+    vhosts.sort();
+    vhosts.dedup();
+    let mut table = Datatable::new(
+        iter::once(None)
+            .chain(vhosts.into_iter().map(Some))
+            .collect(),
+    );
+    table.state.select(Some(0));
+    table
+}
+
+fn export<M: ManagementClient>(
+    client: &M,
+    scope: Option<&str>,
+    dir: &Path,
+) -> Result<PathBuf, Box<dyn Error>> {
+    let name = match scope {
+        None => "all".to_string(),
+        Some("/") => "default".to_string(),
+        Some(v) => v.replace('/', "_"),
+    };
+    let stamp: String = rfc3339(now())
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == 'T')
+        .take(15)
+        .collect();
+    let path = dir.join(format!("definitions-{}-{}.json", name, stamp));
+    let defs = client.get_definitions(scope)?;
+    definitions::write_file(&path, &defs)?;
+    Ok(path)
+}
+
+fn read_preview<M: ManagementClient>(
+    client: &M,
+    path: &Path,
+    scope: Option<&str>,
+) -> Result<(Value, Vec<Change>, usize), Box<dyn Error>> {
+    let defs: Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+    // What is posted is what the preview shows.
+    let defs = definitions::scoped(&defs, scope);
+    let current = client.get_definitions(scope)?;
+    let (changes, unchanged) = definitions::import_preview(&current, &defs, scope);
+    Ok((defs, changes, unchanged))
+}
+
+impl<'a, M> DefinitionsPane<'a, M>
+where
+    M: ManagementClient,
+{
+    pub fn new(client: Arc<M>, config: &AppConfig, notifier: Notifier) -> Self {
+        Self {
+            scopes: load_scopes(&*client),
+            client,
+            notifier,
+            safety: config.safety.clone(),
+            explorer: None,
+            preview: None,
+            profiles: config.profiles.clone(),
+            picker: None,
+            comparison: None,
+            confirmation: ConfirmationBox::default(),
+            should_confirm: false,
+            should_show_help: false,
+        }
+    }
+
+    fn selected_scope(&self) -> Option<Option<String>> {
+        self.scopes.selected().cloned()
+    }
+
+    fn export_into(&mut self, dir: PathBuf) {
+        let Some(scope) = self.selected_scope() else {
+            return;
+        };
+        match export(&*self.client, scope.as_deref(), &dir) {
+            Ok(path) => {
+                self.notifier
+                    .success(format!("Exported to {}", path.display()));
+                self.explorer = None;
+            }
+            Err(e) => self.notifier.error(format!("Export failed: {}", e)),
+        }
+    }
+
+    fn open_preview(&mut self, path: PathBuf) {
+        let Some(scope) = self.selected_scope() else {
+            return;
+        };
+        match read_preview(&*self.client, &path, scope.as_deref()) {
+            Ok((_, changes, unchanged)) if changes.is_empty() => {
+                self.notifier.info(format!(
+                    "Nothing to import, the {} objects of the file are already there",
+                    unchanged
+                ));
+            }
+            Ok((defs, changes, unchanged)) => {
+                let mut table = Datatable::default();
+                table.set_columns(Change::default_columns());
+                table.update(changes);
+                self.preview = Some(Preview {
+                    scope,
+                    path,
+                    defs,
+                    table,
+                    unchanged,
+                });
+                self.explorer = None;
+            }
+            Err(e) => self
+                .notifier
+                .error(format!("Unable to preview {}: {}", path.display(), e)),
+        }
+    }
+
+    fn ask_confirmation(&mut self) {
+        let Some(preview) = &self.preview else {
+            return;
+        };
+        let cluster = self
+            .client
+            .get_overview()
+            .map_or_else(|_| "unknown".to_string(), |o| o.cluster_name);
+        self.confirmation.set_action(format!(
+            "Import {}\ninto {} on cluster {}\n{} objects created or differing",
+            preview.path.display(),
+            scope_label(&preview.scope),
+            cluster,
+            preview.table.total()
+        ));
+        // Definitions hold no messages, only
+        // production profiles ask for the name.
+        if self.safety.needs_typed_confirmation(0) {
+            let name = preview.scope.clone().unwrap_or(cluster);
+            self.confirmation.require_typing(name);
+        }
+        self.should_confirm = true;
+    }
+
+    fn confirm_import(&mut self) {
+        if self.confirmation.is_confirmed() {
+            if let Some(preview) = self.preview.take() {
+                match self
+                    .client
+                    .post_definitions(preview.scope.as_deref(), &preview.defs)
+                {
+                    Ok(Effect::DryRun) => self
+                        .notifier
+                        .info(format!("Dry run: would import {}", preview.path.display())),
+                    Ok(Effect::Applied) => self
+                        .notifier
+                        .success(format!("Imported {}!", preview.path.display())),
+                    Err(e) => self.notifier.error(format!("Import failed: {}", e)),
+                }
+            }
+        } else if self.confirmation.is_typing() {
+            self.notifier.warning("Name not matching, nothing imported");
+        }
+        self.confirmation.reset();
+        self.should_confirm = false;
+    }
+
+    fn handle_explorer_key(&mut self, key: Key) {
+        let Some((explorer, purpose)) = &mut self.explorer else {
+            return;
+        };
+        match key {
+            Key::Char('j') => explorer.next(),
+            Key::Char('k') => explorer.previous(),
+            Key::Backspace => explorer.select_parent(),
+            Key::Esc => self.explorer = None,
+            Key::Char('s') if *purpose == Purpose::Export => {
+                let dir = explorer.root().to_path_buf();
+                self.export_into(dir);
+            }
+            Key::Char('\n') => {
+                let purpose = *purpose;
+                match (explorer.select(), purpose) {
+                    (Some(path), Purpose::Import) => self.open_preview(path),
+                    (Some(path), Purpose::Compare) => {
+                        let reference = topology::of_file(&path);
+                        self.compare_with(path.display().to_string(), reference);
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn compare_with(&mut self, against: String, reference: Result<Value, Box<dyn Error>>) {
+        let Some(scope) = self.selected_scope() else {
+            return;
+        };
+        let changes = reference.and_then(|r| {
+            let current = topology::fetch(&*self.client)?;
+            Ok(topology::compare(&r, &current, scope.as_deref()))
+        });
+        match changes {
+            Ok(changes) if changes.is_empty() => {
+                self.notifier.info(format!(
+                    "No difference with {} in {}",
+                    against,
+                    scope_label(&scope)
+                ));
+            }
+            Ok(changes) => {
+                let mut table = Datatable::default();
+                table.set_columns(Change::default_columns());
+                table.update(changes);
+                self.comparison = Some(Comparison {
+                    against,
+                    scope,
+                    table,
+                });
+                self.explorer = None;
+            }
+            Err(e) => self
+                .notifier
+                .error(format!("Unable to compare with {}: {}", against, e)),
+        }
+    }
+
+    fn pick_profile(&mut self) {
+        let Some(name) = self.picker.as_ref().and_then(|p| p.selected()).cloned() else {
+            return;
+        };
+        self.picker = None;
+        let reference = topology::of_profile(&self.profiles[&name]).map_err(Into::into);
+        self.compare_with(format!("profile {}", name), reference);
+    }
+
+    fn draw_picker<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
+        let Some(picker) = &mut self.picker else {
+            return;
+        };
+        let pop_area = centered_rect(30, 40, area);
+        let rows = picker.data.get().iter().map(|n| Row::new(vec![n.clone()]));
+        let t = Table::new(rows)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Compare with profile"),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol(HIGHLIGHT_SYMBOL)
+            .widths(&[Constraint::Percentage(100)]);
+        f.render_widget(Clear, pop_area);
+        f.render_stateful_widget(t, pop_area, &mut picker.state);
+    }
+}
+
+fn draw_changes<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    table: &mut Datatable<Change>,
+    title: String,
+) {
+    let area = Layout::default()
+        .constraints([Constraint::Percentage(100)])
+        .margin(1)
+        .split(area)[0];
+    let header_cells = table
+        .headers()
+        .into_iter()
+        .map(|h| Cell::from(h).style(Style::default().fg(Color::Green)));
+    let header = Row::new(header_cells).height(1).bottom_margin(1);
+    let has_selection = table.state.selected().is_some();
+    let widths = column_widths(&table.widths(), area, has_selection);
+    let rows = table.rows().map(|c| {
+        let color = match c.status {
+            Status::Added => Color::Green,
+            Status::Removed => Color::Red,
+            Status::Changed => Color::Yellow,
+        };
+        Row::new(table.row(c)).style(Style::default().fg(color))
+    });
+    let t = Table::new(rows)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol(HIGHLIGHT_SYMBOL)
+        .widths(&widths);
+    f.render_stateful_widget(t, area, &mut table.state);
+}
+
+impl<M, B> Drawable<B> for DefinitionsPane<'_, M>
+where
+    M: ManagementClient,
+    B: Backend,
+{
+    fn draw(&mut self, f: &mut Frame<B>, area: Rect) {
+        if let Some(preview) = &mut self.preview {
+            let title = format!(
+                "Import {} into {}: {} to create or differing, {} already there - return to import, esc to cancel",
+                preview.path.display(),
+                scope_label(&preview.scope),
+                preview.table.total(),
+                preview.unchanged
+            );
+            draw_changes(f, area, &mut preview.table, title);
+        } else if let Some(comparison) = &mut self.comparison {
+            let count = |s| {
+                comparison
+                    .table
+                    .rows()
+                    .filter(|c: &&Change| c.status == s)
+                    .count()
+            };
+            let title = format!(
+                "Topology of {} compared with {}: {} added, {} removed, {} changed - esc to close",
+                scope_label(&comparison.scope),
+                comparison.against,
+                count(Status::Added),
+                count(Status::Removed),
+                count(Status::Changed)
+            );
+            draw_changes(f, area, &mut comparison.table, title);
+        } else {
+            let chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
+                .margin(1)
+                .split(area);
+            let rows = self.scopes.data.get().iter().map(|s| {
+                let label = match s {
+                    Some(v) => v.clone(),
+                    None => "(whole broker)".to_string(),
+                };
+                Row::new(vec![label])
+            });
+            let t = Table::new(rows)
+                .block(Block::default().borders(Borders::ALL).title("Scope"))
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+                .highlight_symbol(HIGHLIGHT_SYMBOL)
+                .widths(&[Constraint::Percentage(100)]);
+            f.render_stateful_widget(t, chunks[0], &mut self.scopes.state);
+            let scope = self.scopes.selected().map(scope_label).unwrap_or_default();
+            let text = match &self.explorer {
+                Some((_, Purpose::Export)) => format!(
+                    "Exporting {}. Open the directory to export into, then press s.",
+                    scope
+                ),
+                Some((_, Purpose::Import)) => format!(
+                    "Importing into {}. Pick a definitions file, a preview of what \
+                     it would change comes first.",
+                    scope
+                ),
+                Some((_, Purpose::Compare)) => format!(
+                    "Comparing the topology of {} with a definitions file. Pick the file.",
+                    scope
+                ),
+                None => format!(
+                    "Definitions of {}.\n\nx: export them to a file\ni: import a file\n\
+                     c: compare with a file\np: compare with another profile\n?: help",
+                    scope
+                ),
+            };
+            let p = Paragraph::new(Text::raw(text))
+                .block(Block::default().borders(Borders::ALL).title("Definitions"))
+                .wrap(Wrap { trim: true });
+            f.render_widget(p, chunks[1]);
+        }
+        if let Some((explorer, _)) = &mut self.explorer {
+            explorer.draw(f, area);
+        }
+        self.draw_picker(f, area);
+        if self.should_confirm {
+            self.confirmation.draw(f, area);
+        }
+        if self.should_show_help {
+            Help::new(HELP).draw(f, area);
+        }
+    }
+}
+
+impl<M, B> StatefulPane<B> for DefinitionsPane<'_, M>
+where
+    M: ManagementClient,
+    B: Backend,
+{
+    fn handle_key(&mut self, key: Key) {
+        if self.should_confirm {
+            match key {
+                Key::Char('\n') => self.confirm_import(),
+                Key::Esc => {
+                    self.confirmation.reset();
+                    self.should_confirm = false;
+                }
+                _ => self.confirmation.handle_key(key),
+            }
+            return;
+        }
+        if self.explorer.is_some() {
+            self.handle_explorer_key(key);
+            return;
+        }
+        if let Some(picker) = &mut self.picker {
+            match key {
+                Key::Char('j') => picker.next(),
+                Key::Char('k') => picker.previous(),
+                Key::Char('\n') => self.pick_profile(),
+                Key::Esc => self.picker = None,
+                _ => {}
+            }
+            return;
+        }
+        if let Some(comparison) = &mut self.comparison {
+            match key {
+                Key::Char('j') => comparison.table.next(),
+                Key::Char('k') => comparison.table.previous(),
+                Key::Esc => self.comparison = None,
+                _ => {}
+            }
+            return;
+        }
+        if let Some(preview) = &mut self.preview {
+            match key {
+                Key::Char('j') => preview.table.next(),
+                Key::Char('k') => preview.table.previous(),
+                Key::Char('\n') => self.ask_confirmation(),
+                Key::Esc => self.preview = None,
+                _ => {}
+            }
+            return;
+        }
+        match key {
+            Key::Char('j') => self.scopes.next(),
+            Key::Char('k') => self.scopes.previous(),
+            Key::Char('r') => self.scopes = load_scopes(&*self.client),
+            Key::Char('x') => {
+                self.explorer = Some((FileNavigator::default(), Purpose::Export));
+            }
+            Key::Char('i') => {
+                if let Err(why) = self.safety.check(Action::Import) {
+                    self.notifier.warning(why);
+                } else {
+                    self.explorer = Some((FileNavigator::default(), Purpose::Import));
+                }
+            }
+            Key::Char('c') => {
+                self.explorer = Some((FileNavigator::default(), Purpose::Compare));
+            }
+            Key::Char('p') => {
+                let mut names: Vec<_> = self.profiles.keys().cloned().collect();
+                if names.is_empty() {
+                    self.notifier
+                        .warning("No profile in the config to compare with");
+                } else {
+                    names.sort();
+                    let mut picker = Datatable::new(names);
+                    picker.state.select(Some(0));
+                    self.picker = Some(picker);
+                }
+            }
+            Key::Char('?') => {
+                self.should_show_help = !self.should_show_help;
+            }
+            _ => {}
+        }
+    }
+
+    fn update(&mut self) {}
+
+    fn captures_input(&self) -> bool {
+        self.should_confirm && self.confirmation.is_typing()
+    }
+}
@@ -1,18 +1,28 @@
-use super::{centered_rect, Drawable, StatefulPane};
+use super::{
+    centered_rect, column_widths, header_column, Drawable, StatefulPane, HIGHLIGHT_SYMBOL,
+};
 use crate::{
+    config::AppConfig,
     models::{ExchangeBindings, ExchangeInfo},
-    widgets::help::Help,
-    DataContainer, Datatable, ManagementClient, Rowable,
+    routing::{self, Message, Outcome},
+    widgets::{
+        columns::ColumnChooser,
+        help::Help,
+        notif::Notifier,
+        search::{highlight_cell, SearchBar},
+    },
+    Datatable, ManagementClient, Rowable,
 };
 
 use std::sync::{mpsc, Arc};
 
-use termion::event::Key;
+use termion::event::{Key, MouseButton, MouseEvent};
 use tui::{
     backend::Backend,
-    layout::{Constraint, Layout, Rect},
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    widgets::{Block, Borders, Cell, Clear, Row, Table},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, Wrap},
     Frame,
 };
 
@@ -24,53 +34,211 @@ Keys:
   - l: next tab
   - k: previous row
   - j: next row
+  - s: sort by the next column, S: toggle ascending/descending
+  - click on a header: sort by that column, again to reverse
+  - c: choose the columns shown, their order and widths
+  - /: filter rows, tab switches between substring, glob \
+and regex, return keeps the filter, esc clears it
   - return: open/close drilldown for selected exchange
+  - t: test which queues a message published to the \
+selected exchange would reach, from its routing key and \
+headers, without publishing it
   - ?: close the help menu";
 
+const SIMULATOR_HINT: &str = "tab: switch field | return: route | esc: close";
+
+/// Where a message published to an exchange would go,
+/// worked out from the bindings instead of publishing.
+struct Simulator {
+    vhost: String,
+    exchange: String,
+    routing_key: String,
+    // Typed as key=value pairs separated by commas.
+    headers: String,
+    editing_headers: bool,
+    outcome: Option<Result<Outcome, String>>,
+}
+
+impl Simulator {
+    fn new(exchange: &ExchangeInfo) -> Self {
+        Self {
+            vhost: exchange.vhost.clone(),
+            exchange: exchange.name.clone(),
+            routing_key: String::new(),
+            headers: String::new(),
+            editing_headers: false,
+            outcome: None,
+        }
+    }
+
+    fn field(&mut self) -> &mut String {
+        if self.editing_headers {
+            &mut self.headers
+        } else {
+            &mut self.routing_key
+        }
+    }
+
+    fn run<M: ManagementClient>(&mut self, client: &M) {
+        let outcome = routing::parse_headers(&self.headers).and_then(|headers| {
+            let message = Message {
+                routing_key: self.routing_key.clone(),
+                headers,
+            };
+            let exchanges = client.get_exchange_overview().map_err(|e| e.to_string())?;
+            let bindings = client.get_bindings().map_err(|e| e.to_string())?;
+            let policies = client.get_policies().map_err(|e| e.to_string())?;
+            Ok(routing::route(
+                &self.vhost,
+                &self.exchange,
+                &message,
+                &exchanges,
+                &bindings,
+                &policies,
+            ))
+        });
+        self.outcome = Some(outcome);
+    }
+
+    fn draw<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+        let pop_area = centered_rect(60, 50, area);
+        let name = match self.exchange.as_str() {
+            "" => "(default)",
+            n => n,
+        };
+        let block = Block::default().borders(Borders::ALL).title(format!(
+            "Route a message published to {} in vhost {}",
+            name, self.vhost
+        ));
+        let inner = block.inner(pop_area);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(2),
+                Constraint::Min(0),
+            ])
+            .split(inner);
+        let field_style = |active: bool| match active {
+            true => Style::default().fg(Color::Yellow),
+            false => Style::default(),
+        };
+        let key = Spans::from(vec![
+            Span::styled("Routing key: ", field_style(!self.editing_headers)),
+            Span::raw(self.routing_key.as_str()),
+        ]);
+        let headers = Spans::from(vec![
+            Span::styled("Headers: ", field_style(self.editing_headers)),
+            Span::raw(self.headers.as_str()),
+        ]);
+        let hint = Span::styled(SIMULATOR_HINT, Style::default().fg(Color::DarkGray));
+        let mut lines = Vec::new();
+        match &self.outcome {
+            None => {}
+            Some(Err(e)) => lines.push(Spans::from(Span::styled(
+                e.as_str(),
+                Style::default().fg(Color::Red),
+            ))),
+            Some(Ok(outcome)) => {
+                if outcome.deliveries.is_empty() {
+                    lines.push(Spans::from(Span::styled(
+                        "Unroutable, the message would be dropped",
+                        Style::default().fg(Color::Red),
+                    )));
+                }
+                for d in &outcome.deliveries {
+                    lines.push(Spans::from(vec![
+                        Span::styled(d.queue.as_str(), Style::default().fg(Color::Green)),
+                        Span::raw(format!(" via {}", d.via.join(" → "))),
+                    ]));
+                }
+                for u in &outcome.unsupported {
+                    lines.push(Spans::from(Span::styled(
+                        format!("Not followed past {}: {}", u.exchange, u.reason),
+                        Style::default().fg(Color::Yellow),
+                    )));
+                }
+            }
+        }
+        f.render_widget(Clear, pop_area);
+        f.render_widget(block, pop_area);
+        f.render_widget(Paragraph::new(key), chunks[0]);
+        f.render_widget(Paragraph::new(headers), chunks[1]);
+        f.render_widget(Paragraph::new(hint), chunks[2]);
+        f.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), chunks[3]);
+        let (row, label, text) = match self.editing_headers {
+            true => (chunks[1], "Headers: ", &self.headers),
+            false => (chunks[0], "Routing key: ", &self.routing_key),
+        };
+        let x = row.x + (label.len() + text.chars().count()) as u16;
+        if x < row.right() {
+            f.set_cursor(x, row.y);
+        }
+    }
+}
+
 pub struct ExchangePane<M>
 where
     M: ManagementClient,
 {
     table: Datatable<ExchangeInfo>,
+    search: SearchBar,
+    chooser: Option<ColumnChooser>,
+    // Where the table was last drawn, to map
+    // header clicks to columns.
+    table_area: Rect,
     bindings_table: Datatable<ExchangeBindings>,
+    simulator: Option<Simulator>,
     data_chan: mpsc::Receiver<Vec<ExchangeInfo>>,
     should_fetch_bindings: bool,
     should_draw_popout: bool,
     should_show_help: bool,
     client: Arc<M>,
+    notifier: Notifier,
 }
 
 impl<M> ExchangePane<M>
 where
     M: ManagementClient,
 {
-    pub fn new(client: Arc<M>, data_chan: mpsc::Receiver<Vec<ExchangeInfo>>) -> Self {
-        let data = client.get_exchange_overview();
-        let table = Datatable::<ExchangeInfo>::new(data);
+    pub fn new(
+        client: Arc<M>,
+        data_chan: mpsc::Receiver<Vec<ExchangeInfo>>,
+        config: &AppConfig,
+        notifier: Notifier,
+    ) -> Self {
+        // Refresh errors are notified by the data thread.
+        let data = client.get_exchange_overview().unwrap_or_default();
+        let mut table = Datatable::<ExchangeInfo>::new(data);
+        table.set_columns(config.exchange_columns.clone());
         Self {
             table,
+            search: SearchBar::default(),
+            chooser: None,
+            table_area: Rect::default(),
             data_chan,
             bindings_table: Datatable::default(),
+            simulator: None,
             should_fetch_bindings: false,
             should_draw_popout: false,
             should_show_help: false,
             client: Arc::clone(&client),
+            notifier,
         }
     }
 
     fn draw_popout<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
-        let data = self.bindings_table.data.get();
-        let b_header_lits = ExchangeBindings::headers();
-        let b_header_cells = b_header_lits
+        let b_header_cells = ExchangeBindings::columns()
             .iter()
-            .map(|h| Cell::from(*h).style(Style::default().fg(Color::Yellow)));
+            .map(|d| Cell::from(d.title).style(Style::default().fg(Color::Yellow)));
         let b_header = Row::new(b_header_cells)
             .style(Style::default())
             .height(1)
             .bottom_margin(1);
-        let b_rows = data.iter().map(|r| {
-            let vecd = r.to_row();
-            let cells = vecd.iter().map(|c| Cell::from(c.clone()));
+        let columns = ExchangeBindings::default_columns();
+        let b_rows = self.bindings_table.rows().map(|r| {
+            let cells = r.to_row(&columns).into_iter().map(Cell::from);
             Row::new(cells).bottom_margin(1)
         });
         let selected_style = Style::default().add_modifier(Modifier::REVERSED);
@@ -112,48 +280,59 @@ where
     B: Backend,
 {
     fn draw(&mut self, f: &mut Frame<B>, area: Rect) {
-        let row_data = self.table.data.get();
+        let search_height = if self.search.is_visible() { 1 } else { 0 };
         let rects = Layout::default()
-            .constraints([Constraint::Percentage(100)].as_ref())
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(search_height)].as_ref())
             .margin(1)
             .split(area);
         let selected_style = Style::default().add_modifier(Modifier::REVERSED);
         let normal_style = Style::default();
-        let header_literals = ExchangeInfo::headers();
-        let header_cells = header_literals
-            .iter()
-            .map(|h| Cell::from(*h).style(Style::default().fg(Color::Green)));
+        let header_cells = self
+            .table
+            .headers()
+            .into_iter()
+            .map(|h| Cell::from(h).style(Style::default().fg(Color::Green)));
         let header = Row::new(header_cells)
             .style(normal_style)
             .height(1)
             .bottom_margin(1);
-        let rows = row_data.iter().map(|r| {
-            let vecd = r.to_row();
-            let cells = vecd
-                .iter()
-                // TODO this clone here is bad
-                .map(|c| Cell::from(c.clone()));
+        let has_selection = self.table.state.selected().is_some();
+        let widths = column_widths(&self.table.widths(), rects[0], has_selection);
+        let filter = self.table.filter();
+        let rows = self.table.rows().map(|r| {
+            let cells = self
+                .table
+                .row(r)
+                .into_iter()
+                .map(|c| highlight_cell(c, filter));
             Row::new(cells).bottom_margin(1)
         });
         let t = Table::new(rows)
             .header(header)
             .block(Block::default().borders(Borders::ALL).title("Exchanges"))
             .highlight_style(selected_style)
-            .highlight_symbol(">> ")
-            .widths(&[
-                Constraint::Percentage(40),
-                Constraint::Percentage(30),
-                Constraint::Percentage(15),
-                Constraint::Percentage(15),
-            ]);
+            .highlight_symbol(HIGHLIGHT_SYMBOL)
+            .widths(&widths);
+        self.table_area = rects[0];
         f.render_stateful_widget(t, rects[0], &mut self.table.state);
+        if self.search.is_visible() {
+            self.search
+                .draw(f, rects[1], self.table.len(), self.table.total());
+        }
         if self.should_draw_popout {
-            match self.table.state.selected() {
+            match self.table.selected() {
                 None => {}
-                Some(i) => {
+                Some(drilldown) => {
                     if self.should_fetch_bindings {
-                        let drilldown = &row_data[i];
-                        let binding_data = self.client.get_exchange_bindings(drilldown);
+                        let binding_data = match self.client.get_exchange_bindings(drilldown) {
+                            Ok(b) => b,
+                            Err(e) => {
+                                self.notifier
+                                    .error(format!("Unable to get bindings: {}", e));
+                                Vec::new()
+                            }
+                        };
                         self.bindings_table = Datatable::<ExchangeBindings>::new(binding_data);
                         self.should_fetch_bindings = false;
                     }
@@ -162,6 +341,14 @@ where
             }
         }
 
+        if let Some(chooser) = &mut self.chooser {
+            chooser.draw(f, area);
+        }
+
+        if let Some(simulator) = &self.simulator {
+            simulator.draw(f, area);
+        }
+
         if self.should_show_help {
             let help = Help::new(HELP);
             help.draw(f, area);
@@ -175,6 +362,36 @@ where
     B: Backend,
 {
     fn handle_key(&mut self, key: Key) {
+        if self.search.is_typing() {
+            if self.search.handle_key(key) {
+                self.table.set_filter(self.search.filter());
+            }
+            return;
+        }
+        if let Some(simulator) = &mut self.simulator {
+            match key {
+                Key::Esc => self.simulator = None,
+                Key::Char('\t') => simulator.editing_headers = !simulator.editing_headers,
+                Key::Char('\n') => simulator.run(&*self.client),
+                Key::Backspace => {
+                    simulator.field().pop();
+                }
+                Key::Char(c) => simulator.field().push(c),
+                _ => {}
+            }
+            return;
+        }
+        if let Some(chooser) = &mut self.chooser {
+            match key {
+                Key::Esc | Key::Char('c') | Key::Char('\n') => self.chooser = None,
+                _ => {
+                    if chooser.handle_key(key) {
+                        self.table.set_columns(chooser.columns());
+                    }
+                }
+            }
+            return;
+        }
         match key {
             Key::Char('j') => {
                 self.forward_table();
@@ -186,6 +403,25 @@ where
                 self.should_fetch_bindings = true;
                 self.should_draw_popout = !self.should_draw_popout;
             }
+            Key::Char('/') if !self.should_draw_popout => {
+                self.search.open();
+            }
+            Key::Esc if self.search.is_visible() => {
+                self.search.clear();
+                self.table.set_filter(None);
+            }
+            Key::Char('s') => {
+                self.table.cycle_sort();
+            }
+            Key::Char('S') => {
+                self.table.toggle_sort_order();
+            }
+            Key::Char('c') if !self.should_draw_popout => {
+                self.chooser = Some(ColumnChooser::new::<ExchangeInfo>(self.table.columns()));
+            }
+            Key::Char('t') => {
+                self.simulator = self.table.selected().map(Simulator::new);
+            }
             Key::Char('?') => {
                 self.should_show_help = !self.should_show_help;
             }
@@ -193,9 +429,31 @@ where
         }
     }
 
+    fn handle_mouse(&mut self, event: MouseEvent) {
+        if self.should_draw_popout
+            || self.should_show_help
+            || self.chooser.is_some()
+            || self.simulator.is_some()
+        {
+            return;
+        }
+        if let MouseEvent::Press(MouseButton::Left, x, y) = event {
+            let has_selection = self.table.state.selected().is_some();
+            let widths = column_widths(&self.table.widths(), self.table_area, has_selection);
+            let (x, y) = (x.saturating_sub(1), y.saturating_sub(1));
+            if let Some(c) = header_column(self.table_area, &widths, has_selection, x, y) {
+                self.table.sort_by(c);
+            }
+        }
+    }
+
     fn update(&mut self) {
         if let Some(d) = self.data_chan.try_iter().next() {
-            self.table.data = DataContainer { entries: d };
+            self.table.update(d);
         }
     }
+
+    fn captures_input(&self) -> bool {
+        self.search.is_typing() || self.simulator.is_some()
+    }
 }
@@ -1,11 +1,20 @@
+pub mod alerts;
+pub mod audit;
+pub mod definitions;
 pub mod exchange;
+pub mod notifications;
 pub mod overview;
+pub mod queue_detail;
 pub mod queues;
+pub mod topology;
 
-use termion::event::Key;
+use std::io;
+
+use termion::event::{Key, MouseEvent};
 use tui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
+    widgets::{Block, Borders},
     Frame,
 };
 
@@ -27,6 +36,81 @@ where
 {
     fn handle_key(&mut self, key: Key);
     fn update(&mut self);
+
+    /// Mouse events, with the terminal's 1-based coordinates.
+    fn handle_mouse(&mut self, _event: MouseEvent) {}
+
+    /// Whether the pane is taking free text input, in
+    /// which case app wide keys are forwarded to it too.
+    fn captures_input(&self) -> bool {
+        false
+    }
+
+    /// Returns the text the pane wants edited in the user's
+    /// external editor, if any. The app hands the terminal
+    /// over and reports back through `external_edit_done`.
+    fn external_edit(&mut self) -> Option<String> {
+        None
+    }
+
+    fn external_edit_done(&mut self, _result: io::Result<String>) {}
+}
+
+/// Symbol marking the selected row of tables.
+pub const HIGHLIGHT_SYMBOL: &str = ">> ";
+
+/// Widths of columns taking `percents` of a bordered table
+/// drawn in `area`. Percentages are turned into exact lengths,
+/// floored and cut to the space left, as tui rounds each
+/// percentage up or down on its own and rows could otherwise
+/// be drawn past the right border.
+pub fn column_widths(percents: &[u16], area: Rect, has_selection: bool) -> Vec<Constraint> {
+    let inner = Block::default().borders(Borders::ALL).inner(area);
+    let symbol = if has_selection {
+        HIGHLIGHT_SYMBOL.len() as u16
+    } else {
+        0
+    };
+    let spacing = (percents.len() as u16).saturating_sub(1);
+    let available = inner.width.saturating_sub(symbol + spacing);
+    let mut left = available;
+    percents
+        .iter()
+        .map(|&p| {
+            let w = ((p as u32 * available as u32) / 100).min(left as u32) as u16;
+            left -= w;
+            Constraint::Length(w)
+        })
+        .collect()
+}
+
+/// Column whose header is at `(x, y)` in a bordered table
+/// drawn in `area` with the given `column_widths`.
+pub fn header_column(
+    area: Rect,
+    widths: &[Constraint],
+    has_selection: bool,
+    x: u16,
+    y: u16,
+) -> Option<usize> {
+    let inner = Block::default().borders(Borders::ALL).inner(area);
+    if y != inner.y || x < inner.x || x >= inner.right() {
+        return None;
+    }
+    let mut start = inner.x;
+    if has_selection {
+        start += HIGHLIGHT_SYMBOL.len() as u16;
+    }
+    for (i, w) in widths.iter().enumerate() {
+        let Constraint::Length(w) = *w else {
+            return None;
+        };
+        if x >= start && x < start + w {
+            return Some(i);
+        }
+        start += w + 1;
+    }
+    None
 }
 
 /// helper function to create a centered rect using up
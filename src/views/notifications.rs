@@ -0,0 +1,176 @@
+use super::{column_widths, header_column, Drawable, StatefulPane, HIGHLIGHT_SYMBOL};
+use crate::{
+    widgets::{
+        help::Help,
+        notif::Notification,
+        search::{highlight_cell, SearchBar},
+    },
+    Datatable, Rowable,
+};
+
+use std::{collections::VecDeque, sync::mpsc};
+
+use termion::event::{Key, MouseButton, MouseEvent};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Cell, Row, Table},
+    Frame,
+};
+
+/// Most notifications kept, older ones being dropped.
+const MAX_HISTORY: usize = 1_000;
+
+const HELP: &str = "The Notifications tab keeps the last \
+thousand notifications of the session, newest first, \
+including the errors of the management API.
+
+Keys:
+  - h: previous tab
+  - l: next tab
+  - k: previous row
+  - j: next row
+  - s: sort by the next column, S: toggle ascending/descending
+  - click on a header: sort by that column, again to reverse
+  - /: filter rows, tab switches between substring, glob \
+and regex, return keeps the filter, esc clears it
+  - x: clear the history
+  - ?: close the help menu";
+
+pub struct NotificationsPane {
+    // The history, newest first.
+    table: Datatable<Notification>,
+    next_id: u64,
+    search: SearchBar,
+    // Where the table was last drawn, to map
+    // header clicks to columns.
+    table_area: Rect,
+    data_chan: mpsc::Receiver<Notification>,
+    should_show_help: bool,
+}
+
+impl NotificationsPane {
+    pub fn new(data_chan: mpsc::Receiver<Notification>) -> Self {
+        let mut table = Datatable::default();
+        table.set_columns(Notification::default_columns());
+        Self {
+            table,
+            next_id: 0,
+            search: SearchBar::default(),
+            table_area: Rect::default(),
+            data_chan,
+            should_show_help: false,
+        }
+    }
+}
+
+impl<B: Backend> Drawable<B> for NotificationsPane {
+    fn draw(&mut self, f: &mut Frame<B>, area: Rect) {
+        let search_height = if self.search.is_visible() { 1 } else { 0 };
+        let rects = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(search_height)].as_ref())
+            .margin(1)
+            .split(area);
+        let header_cells = self
+            .table
+            .headers()
+            .into_iter()
+            .map(|h| Cell::from(h).style(Style::default().fg(Color::Green)));
+        let header = Row::new(header_cells).height(1).bottom_margin(1);
+        let has_selection = self.table.state.selected().is_some();
+        let widths = column_widths(&self.table.widths(), rects[0], has_selection);
+        let filter = self.table.filter();
+        let rows = self.table.rows().map(|n| {
+            let cells = self
+                .table
+                .row(n)
+                .into_iter()
+                .map(|c| highlight_cell(c, filter));
+            Row::new(cells).style(Style::default().fg(n.level.color()))
+        });
+        let t = Table::new(rows)
+            .header(header)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Notifications"),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol(HIGHLIGHT_SYMBOL)
+            .widths(&widths);
+        self.table_area = rects[0];
+        f.render_stateful_widget(t, rects[0], &mut self.table.state);
+        if self.search.is_visible() {
+            self.search
+                .draw(f, rects[1], self.table.len(), self.table.total());
+        }
+        if self.should_show_help {
+            let help = Help::new(HELP);
+            help.draw(f, area);
+        }
+    }
+}
+
+impl<B: Backend> StatefulPane<B> for NotificationsPane {
+    fn handle_key(&mut self, key: Key) {
+        if self.search.is_typing() {
+            if self.search.handle_key(key) {
+                self.table.set_filter(self.search.filter());
+            }
+            return;
+        }
+        match key {
+            Key::Char('j') => self.table.next(),
+            Key::Char('k') => self.table.previous(),
+            Key::Char('s') => self.table.cycle_sort(),
+            Key::Char('S') => self.table.toggle_sort_order(),
+            Key::Char('/') => self.search.open(),
+            Key::Esc if self.search.is_visible() => {
+                self.search.clear();
+                self.table.set_filter(None);
+            }
+            Key::Char('x') => {
+                self.table.update(Vec::new());
+            }
+            Key::Char('?') => {
+                self.should_show_help = !self.should_show_help;
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_mouse(&mut self, event: MouseEvent) {
+        if self.should_show_help {
+            return;
+        }
+        if let MouseEvent::Press(MouseButton::Left, x, y) = event {
+            let has_selection = self.table.state.selected().is_some();
+            let widths = column_widths(&self.table.widths(), self.table_area, has_selection);
+            let (x, y) = (x.saturating_sub(1), y.saturating_sub(1));
+            if let Some(c) = header_column(self.table_area, &widths, has_selection, x, y) {
+                self.table.sort_by(c);
+            }
+        }
+    }
+
+    fn update(&mut self) {
+        let mut received = VecDeque::new();
+        for mut n in self.data_chan.try_iter() {
+            n.id = self.next_id;
+            self.next_id += 1;
+            received.push_front(n);
+        }
+        if !received.is_empty() {
+            self.table.update_with(|history| {
+                history.splice(0..0, received);
+                history.truncate(MAX_HISTORY);
+            });
+        }
+    }
+
+    fn captures_input(&self) -> bool {
+        self.search.is_typing()
+    }
+}
@@ -1,8 +1,8 @@
 use super::{Drawable, StatefulPane};
 use crate::{
-    models::Overview,
+    models::{HistoryWindow, Overview},
     widgets::{
-        chart::{ChartData, RChart},
+        chart::{lengths, rates, ChartData, ChartStyle, RChart, Unit},
         help::Help,
     },
     ManagementClient,
@@ -16,7 +16,7 @@ use tui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Span, Spans},
-    widgets::{Block, Borders, List, ListItem},
+    widgets::{Block, Borders, List, ListItem, Paragraph},
     Frame,
 };
 
@@ -24,76 +24,234 @@ const HELP: &str = "Welcome to RabbiTui! The help displayed \
 here is relevant to the Overview tab. Every help panel will \
 be specific to the tab you are in.
 
-The overview pane shows high level throughput analytics.
+The overview pane shows high level throughput analytics: \
+queued messages, message rates, object totals and the \
+versions of the cluster.
+
+Charts are seeded with the history kept by the \
+management API, going back as far as the window.
 
 Keys:
   - h: previous tab
   - l: next tab
+  - w: switch the window between 1m, 10m, 1h and 8h
+  - g: draw charts with lines, dots or bars
   - ?: close the help menu";
 
+const RATE_COLORS: [Color; 5] = [
+    Color::Green,
+    Color::Cyan,
+    Color::Magenta,
+    Color::Blue,
+    Color::Red,
+];
+
 #[derive(Default)]
 struct OverviewData {
     overall: ChartData,
     ready: ChartData,
     unacked: ChartData,
-    disk_read_rate: ChartData,
-    disk_write_rate: ChartData,
+    publish: ChartData,
+    deliver: ChartData,
+    ack: ChartData,
+    confirm: ChartData,
+    redeliver: ChartData,
 }
 
-pub struct OverviewPane {
+impl OverviewData {
+    fn charts(&mut self) -> [&mut ChartData; 8] {
+        [
+            &mut self.overall,
+            &mut self.ready,
+            &mut self.unacked,
+            &mut self.publish,
+            &mut self.deliver,
+            &mut self.ack,
+            &mut self.confirm,
+            &mut self.redeliver,
+        ]
+    }
+
+    fn push(&mut self, overview: &Overview) {
+        let totals = &overview.queue_totals;
+        let stats = &overview.message_stats;
+        self.overall.push(totals.messages);
+        self.ready.push(totals.messages_ready);
+        self.unacked.push(totals.messages_unacked);
+        self.publish.push(stats.publish_details.rate);
+        self.deliver.push(stats.deliver_get_details.rate);
+        self.ack.push(stats.ack_details.rate);
+        self.confirm.push(stats.confirm_details.rate);
+        self.redeliver.push(stats.redeliver_details.rate);
+    }
+}
+
+pub struct OverviewPane<M>
+where
+    M: ManagementClient,
+{
     data: OverviewData,
+    // The latest overview, for the rates
+    // that are listed but not charted.
+    last: Overview,
     data_chan: mpsc::Receiver<Overview>,
-    counter: f64,
+    client: Arc<M>,
+    window: HistoryWindow,
+    style: ChartStyle,
     should_show_help: bool,
 }
 
-impl OverviewPane {
-    pub fn new<M>(client: Arc<M>, data_chan: mpsc::Receiver<Overview>) -> Self
-    where
-        M: ManagementClient,
-    {
-        let data = client.get_overview();
-        let mut overall = ChartData::default();
-        overall.push(data.queue_totals.messages);
-        let mut ready = ChartData::default();
-        ready.push(data.queue_totals.messages_ready);
-        let mut unacked = ChartData::default();
-        unacked.push(data.queue_totals.messages_unacked);
-        let mut disk_read_rate = ChartData::default();
-        disk_read_rate.push(data.message_stats.disk_reads_details.rate);
-        let mut disk_write_rate = ChartData::default();
-        disk_write_rate.push(data.message_stats.disk_writes_details.rate);
-        Self {
-            counter: 0.,
+impl<M> OverviewPane<M>
+where
+    M: ManagementClient,
+{
+    pub fn new(client: Arc<M>, data_chan: mpsc::Receiver<Overview>) -> Self {
+        // Refresh errors are notified by the data thread.
+        let last = client.get_overview().unwrap_or_default();
+        let mut data = OverviewData::default();
+        data.push(&last);
+        let mut pane = Self {
             data_chan,
-            data: OverviewData {
-                overall,
-                ready,
-                unacked,
-                disk_read_rate,
-                disk_write_rate,
-            },
+            data,
+            last,
+            client,
+            window: HistoryWindow::default(),
+            style: ChartStyle::default(),
             should_show_help: false,
-        }
+        };
+        pane.load_history();
+        pane
+    }
+
+    /// Seeds the charts with the samples the API keeps
+    /// for the current window. Charts keep their points
+    /// when history is unavailable.
+    fn load_history(&mut self) {
+        let window = self.window;
+        self.data
+            .charts()
+            .into_iter()
+            .for_each(|c| c.set_window(window));
+        let Ok(h) = self.client.get_overview_history(window) else {
+            return;
+        };
+        let totals = &h.queue_totals;
+        let stats = &h.message_stats;
+        self.data
+            .overall
+            .seed(lengths(&totals.messages_details.samples));
+        self.data
+            .ready
+            .seed(lengths(&totals.messages_ready_details.samples));
+        self.data
+            .unacked
+            .seed(lengths(&totals.messages_unacked_details.samples));
+        self.data
+            .publish
+            .seed(rates(&stats.publish_details.samples));
+        self.data
+            .deliver
+            .seed(rates(&stats.deliver_get_details.samples));
+        self.data.ack.seed(rates(&stats.ack_details.samples));
+        self.data
+            .confirm
+            .seed(rates(&stats.confirm_details.samples));
+        self.data
+            .redeliver
+            .seed(rates(&stats.redeliver_details.samples));
     }
 
     fn draw_messages_panel<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
         let datasets = [&self.data.overall, &self.data.ready, &self.data.unacked];
         let colors = [Color::Yellow, Color::Cyan, Color::Red];
-        RChart::new(datasets, colors).draw(f, area);
+        let title = format!(
+            "Messages, last {} ({})",
+            self.window.name(),
+            self.style.name()
+        );
+        RChart::new(datasets, colors)
+            .title(title)
+            .names(["Total", "Ready", "Unacked"])
+            .style(self.style)
+            .draw(f, area);
     }
 
     fn draw_message_rates_panel<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
-        let datasets = [&self.data.disk_read_rate, &self.data.disk_write_rate];
-        let colors = [Color::Magenta, Color::Green];
-        RChart::new(datasets, colors).draw(f, area);
+        let datasets = [
+            &self.data.publish,
+            &self.data.deliver,
+            &self.data.ack,
+            &self.data.confirm,
+            &self.data.redeliver,
+        ];
+        let title = format!(
+            "Message rates, last {} ({})",
+            self.window.name(),
+            self.style.name()
+        );
+        RChart::new(datasets, RATE_COLORS)
+            .title(title)
+            .names(["Publish", "Deliver/get", "Ack", "Confirm", "Redeliver"])
+            .unit(Unit::Rate)
+            .style(self.style)
+            .draw(f, area);
     }
 
     fn draw_message_rates_list<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
-        let datasets = [&self.data.disk_read_rate, &self.data.disk_write_rate];
-        let colors = [Color::Magenta, Color::Green];
-        let labels = ["Disk read", "Disk write"];
-        self.draw_info_list(f, area, labels, datasets, colors, "/s");
+        let stats = &self.last.message_stats;
+        let labels = [
+            "Publish",
+            "Deliver/get",
+            "Ack",
+            "Confirm",
+            "Redeliver",
+            "Publish in",
+            "Publish out",
+            "Returned",
+            "Dropped",
+        ];
+        let values = [
+            stats.publish_details.rate,
+            stats.deliver_get_details.rate,
+            stats.ack_details.rate,
+            stats.confirm_details.rate,
+            stats.redeliver_details.rate,
+            stats.publish_in_details.rate,
+            stats.publish_out_details.rate,
+            stats.return_unroutable_details.rate,
+            stats.drop_unroutable_details.rate,
+        ];
+        // Rates that are not charted have no legend color.
+        let mut colors = [Color::Gray; 9];
+        colors[..RATE_COLORS.len()].copy_from_slice(&RATE_COLORS);
+        self.draw_info_list(f, area, labels, values, colors, Unit::Rate);
+    }
+
+    fn draw_cluster_info<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
+        let o = &self.last;
+        let t = &o.object_totals;
+        let label = |l: &'static str| Span::styled(l, Style::default().fg(Color::Gray));
+        let value = |v: String| Span::styled(v, Style::default().add_modifier(Modifier::BOLD));
+        let spans = Spans::from(vec![
+            label("Cluster "),
+            value(o.cluster_name.clone()),
+            label("  RabbitMQ "),
+            value(o.rabbitmq_version.clone()),
+            label("  Erlang "),
+            value(o.erlang_version.clone()),
+            label("  |  Connections "),
+            value(t.connections.to_string()),
+            label("  Channels "),
+            value(t.channels.to_string()),
+            label("  Consumers "),
+            value(t.consumers.to_string()),
+            label("  Queues "),
+            value(t.queues.to_string()),
+            label("  Exchanges "),
+            value(t.exchanges.to_string()),
+        ]);
+        let p = Paragraph::new(spans).block(Block::default().borders(Borders::ALL));
+        f.render_widget(p, area);
     }
 
     fn draw_info_list<B, const W: usize>(
@@ -101,29 +259,30 @@ impl OverviewPane {
         f: &mut Frame<B>,
         area: Rect,
         labels: [&str; W],
-        values: [&ChartData; W],
+        values: [f64; W],
         colors: [Color; W],
-        suffix: &str,
+        unit: Unit,
     ) where
         B: Backend,
     {
+        // Rows are only spaced out when they all fit.
+        let spaced = area.height as usize >= W * 2 + 2;
         let items: Vec<ListItem> = labels
             .iter()
             .enumerate()
             .map(|(i, l)| {
-                let val = values[i].last_value();
-                ListItem::new(vec![
-                    Spans::from(vec![
-                        Span::styled(format!("{:<10}", l), Style::default().fg(colors[i])),
-                        Span::raw(" "),
-                        Span::styled(
-                            format!("{}{}", val, suffix),
-                            Style::default().add_modifier(Modifier::BOLD),
-                        ),
-                    ]),
-                    // for putting visual space between rows
-                    Spans::from(""),
-                ])
+                let mut lines = vec![Spans::from(vec![
+                    Span::styled(format!("{:<11}", l), Style::default().fg(colors[i])),
+                    Span::raw(" "),
+                    Span::styled(
+                        unit.format(values[i]),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ),
+                ])];
+                if spaced {
+                    lines.push(Spans::from(""));
+                }
+                ListItem::new(lines)
             })
             .collect();
         let list = List::new(items).block(Block::default().borders(Borders::ALL));
@@ -131,30 +290,43 @@ impl OverviewPane {
     }
 
     fn draw_message_list<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
-        let datasets = [&self.data.ready, &self.data.overall, &self.data.unacked];
+        let values = [
+            self.data.ready.last_value(),
+            self.data.overall.last_value(),
+            self.data.unacked.last_value(),
+        ];
         let labels = ["Ready", "Total", "Unacked"];
         let colors = [Color::Yellow, Color::Cyan, Color::Red];
-        self.draw_info_list(f, area, labels, datasets, colors, "");
+        self.draw_info_list(f, area, labels, values, colors, Unit::Count);
     }
 }
 
-impl<B> Drawable<B> for OverviewPane
+impl<M, B> Drawable<B> for OverviewPane<M>
 where
+    M: ManagementClient,
     B: Backend,
 {
     fn draw(&mut self, f: &mut Frame<B>, area: Rect) {
         let chunks = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)].as_ref())
+            .constraints(
+                [
+                    Constraint::Length(3),
+                    Constraint::Ratio(1, 2),
+                    Constraint::Ratio(1, 2),
+                ]
+                .as_ref(),
+            )
             .split(area);
         let count_chunks = Layout::default()
             .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(90), Constraint::Percentage(10)].as_ref())
-            .split(chunks[0]);
+            .constraints([Constraint::Percentage(85), Constraint::Percentage(15)].as_ref())
+            .split(chunks[1]);
         let rate_chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([Constraint::Percentage(85), Constraint::Percentage(15)].as_ref())
-            .split(chunks[1]);
+            .split(chunks[2]);
+        self.draw_cluster_info(f, chunks[0]);
         self.draw_messages_panel(f, count_chunks[0]);
         self.draw_message_list(f, count_chunks[1]);
         self.draw_message_rates_panel(f, rate_chunks[0]);
@@ -166,12 +338,20 @@ where
     }
 }
 
-impl<B> StatefulPane<B> for OverviewPane
+impl<M, B> StatefulPane<B> for OverviewPane<M>
 where
+    M: ManagementClient,
     B: Backend,
 {
     fn handle_key(&mut self, key: Key) {
         match key {
+            Key::Char('w') => {
+                self.window = self.window.next();
+                self.load_history();
+            }
+            Key::Char('g') => {
+                self.style = self.style.next();
+            }
             Key::Char('?') => {
                 self.should_show_help = !self.should_show_help;
             }
@@ -181,16 +361,8 @@ where
 
     fn update(&mut self) {
         if let Some(update) = self.data_chan.try_iter().next() {
-            self.counter += 1.0;
-            self.data.ready.push(update.queue_totals.messages_ready);
-            self.data.overall.push(update.queue_totals.messages);
-            self.data.unacked.push(update.queue_totals.messages_unacked);
-            self.data
-                .disk_write_rate
-                .push(update.message_stats.disk_writes_details.rate);
-            self.data
-                .disk_read_rate
-                .push(update.message_stats.disk_reads_details.rate);
+            self.data.push(&update);
+            self.last = update;
         }
     }
 }
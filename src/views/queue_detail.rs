@@ -0,0 +1,226 @@
+use super::Drawable;
+use crate::{
+    models::{HistoryWindow, QueueInfo},
+    widgets::chart::{lengths, rates, ChartData, RChart, Unit},
+    Identifiable, Rowable,
+};
+
+use serde_json::Value;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+const DEPTH_COLORS: [Color; 2] = [Color::Yellow, Color::Red];
+const RATE_COLORS: [Color; 3] = [Color::Green, Color::Cyan, Color::Magenta];
+const MEMORY_COLORS: [Color; 1] = [Color::Blue];
+
+/// Charts and details of a single queue, seeded with
+/// its history and fed with every refresh of the
+/// queues table.
+pub struct QueueDetail {
+    info: QueueInfo,
+    window: HistoryWindow,
+    ready: ChartData,
+    unacked: ChartData,
+    publish: ChartData,
+    deliver: ChartData,
+    ack: ChartData,
+    // Only fed from refreshes, the API
+    // keeping no history of it.
+    memory: ChartData,
+}
+
+impl QueueDetail {
+    pub fn new(info: &QueueInfo) -> Self {
+        let mut detail = Self {
+            info: info.clone(),
+            window: HistoryWindow::default(),
+            ready: ChartData::default(),
+            unacked: ChartData::default(),
+            publish: ChartData::default(),
+            deliver: ChartData::default(),
+            ack: ChartData::default(),
+            memory: ChartData::default(),
+        };
+        detail.push(info);
+        detail
+    }
+
+    /// (vhost, name) of the queue shown.
+    pub fn id(&self) -> (String, String) {
+        self.info.id()
+    }
+
+    pub fn window(&self) -> HistoryWindow {
+        self.window
+    }
+
+    fn charts(&mut self) -> [&mut ChartData; 6] {
+        [
+            &mut self.ready,
+            &mut self.unacked,
+            &mut self.publish,
+            &mut self.deliver,
+            &mut self.ack,
+            &mut self.memory,
+        ]
+    }
+
+    /// Moves to the next window. The charts should be
+    /// seeded again for the longer windows.
+    pub fn next_window(&mut self) {
+        self.window = self.window.next();
+        let window = self.window;
+        self.charts().into_iter().for_each(|c| c.set_window(window));
+    }
+
+    /// Replaces the charted points with the samples of
+    /// `history`, fetched for the current window.
+    pub fn seed(&mut self, history: &QueueInfo) {
+        let stats = &history.message_stats;
+        self.ready
+            .seed(lengths(&history.messages_ready_details.samples));
+        self.unacked
+            .seed(lengths(&history.messages_unacknowledged_details.samples));
+        self.publish.seed(rates(&stats.publish_details.samples));
+        self.deliver.seed(rates(&stats.deliver_get_details.samples));
+        self.ack.seed(rates(&stats.ack_details.samples));
+    }
+
+    pub fn push(&mut self, info: &QueueInfo) {
+        let stats = &info.message_stats;
+        self.ready.push(info.ready as f64);
+        self.unacked.push(info.unacked as f64);
+        self.publish.push(stats.publish_details.rate);
+        self.deliver.push(stats.deliver_get_details.rate);
+        self.ack.push(stats.ack_details.rate);
+        self.memory.push(info.memory as f64);
+        self.info = info.clone();
+    }
+
+    fn cell(&self, key: &str) -> String {
+        QueueInfo::columns()
+            .iter()
+            .position(|d| d.key == key)
+            .map(|i| self.info.cell(i))
+            .unwrap_or_default()
+    }
+
+    fn legend<'a>(label: &'a str, value: String, color: Color) -> Spans<'a> {
+        Spans::from(vec![
+            Span::styled(format!("{:<10}", label), Style::default().fg(color)),
+            Span::styled(value, Style::default().add_modifier(Modifier::BOLD)),
+        ])
+    }
+
+    fn draw_details<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+        let info = &self.info;
+        let stats = &info.message_stats;
+        let field = |label: &'static str, value: String| {
+            Spans::from(vec![
+                Span::styled(format!("{:<11}", label), Style::default().fg(Color::Gray)),
+                Span::raw(value),
+            ])
+        };
+        let mut lines = vec![
+            Self::legend("Ready", info.ready.to_string(), DEPTH_COLORS[0]),
+            Self::legend("Unacked", info.unacked.to_string(), DEPTH_COLORS[1]),
+            Self::legend(
+                "Publish",
+                Unit::Rate.format(stats.publish_details.rate),
+                RATE_COLORS[0],
+            ),
+            Self::legend(
+                "Deliver",
+                Unit::Rate.format(stats.deliver_get_details.rate),
+                RATE_COLORS[1],
+            ),
+            Self::legend(
+                "Ack",
+                Unit::Rate.format(stats.ack_details.rate),
+                RATE_COLORS[2],
+            ),
+            Spans::from(""),
+            field("Vhost", info.vhost.clone()),
+            field("Type", info.t.clone()),
+            field("State", info.state.clone()),
+            field("Node", info.node.clone()),
+            field("Durable", info.durable.to_string()),
+            field("Consumers", info.consumers.to_string()),
+            field("Memory", self.cell("memory")),
+            field(
+                "Policy",
+                info.policy.clone().unwrap_or_else(|| "-".to_string()),
+            ),
+            field(
+                "Idle since",
+                info.idle_since.clone().unwrap_or_else(|| "-".to_string()),
+            ),
+            Spans::from(""),
+            Spans::from(Span::styled("Arguments", Style::default().fg(Color::Gray))),
+        ];
+        if info.arguments.is_empty() {
+            lines.push(Spans::from("  -"));
+        }
+        for (k, v) in &info.arguments {
+            let v = match v {
+                Value::String(s) => s.clone(),
+                v => v.to_string(),
+            };
+            lines.push(Spans::from(format!("  {}: {}", k, v)));
+        }
+        let p = Paragraph::new(lines)
+            .block(Block::default().borders(Borders::ALL).title("Details"))
+            .wrap(Wrap { trim: false });
+        f.render_widget(p, area);
+    }
+}
+
+impl<B: Backend> Drawable<B> for QueueDetail {
+    fn draw(&mut self, f: &mut Frame<B>, area: Rect) {
+        let title = format!(
+            "{} on {}, last {} (w: window, esc: close)",
+            self.info.name,
+            self.info.vhost,
+            self.window.name()
+        );
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(Span::styled(title, Style::default().fg(Color::Green)));
+        let inner = block.inner(area);
+        f.render_widget(Clear, area);
+        f.render_widget(block, area);
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(75), Constraint::Percentage(25)].as_ref())
+            .split(inner);
+        let charts = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Ratio(2, 5),
+                    Constraint::Ratio(2, 5),
+                    Constraint::Ratio(1, 5),
+                ]
+                .as_ref(),
+            )
+            .split(columns[0]);
+        RChart::new([&self.ready, &self.unacked], DEPTH_COLORS)
+            .names(["Ready", "Unacked"])
+            .draw(f, charts[0]);
+        RChart::new([&self.publish, &self.deliver, &self.ack], RATE_COLORS)
+            .names(["Publish", "Deliver", "Ack"])
+            .unit(Unit::Rate)
+            .draw(f, charts[1]);
+        RChart::new([&self.memory], MEMORY_COLORS)
+            .names(["Memory"])
+            .unit(Unit::Bytes)
+            .draw(f, charts[2]);
+        self.draw_details(f, columns[1]);
+    }
+}
@@ -1,48 +1,124 @@
-use super::{Drawable, StatefulPane};
+use super::{
+    column_widths, header_column, queue_detail::QueueDetail, Drawable, StatefulPane,
+    HIGHLIGHT_SYMBOL,
+};
 use crate::{
-    models::QueueInfo,
+    bulk::{self, BulkJob},
+    client::ApiError,
+    clip::Clipboard,
+    config::AppConfig,
+    models::{Effect, PublishResponse, QueueInfo},
+    safety::{Action, Safety},
+    series::TimeSeries,
     widgets::{
-        confirmation::ConfirmationBox, files::FileNavigator, help::Help, notif::Notification,
+        chart::{now, sparkline},
+        columns::ColumnChooser,
+        confirmation::ConfirmationBox,
+        editor::TextEditor,
+        files::FileNavigator,
+        help::Help,
+        notif::Notifier,
+        payload::PayloadViewer,
+        progress::ProgressBox,
+        search::{highlight_cell, SearchBar},
     },
-    DataContainer, Datatable, ManagementClient, Rowable,
+    Datatable, Identifiable, ManagementClient, Rowable,
 };
 
 use std::{
-    fs,
+    collections::{HashMap, HashSet},
+    fs, io,
+    path::Path,
     sync::{mpsc, Arc},
 };
 
-use clipboard::{ClipboardContext, ClipboardProvider};
-use termion::event::Key;
+use reqwest::StatusCode;
+use termion::event::{Key, MouseButton, MouseEvent};
 use tui::{
     backend::Backend,
-    layout::{Constraint, Layout, Rect},
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
+    text::Span,
     widgets::{Block, Borders, Cell, Row, Table},
     Frame,
 };
 
+/// Refreshes of queue depth kept for the trend column.
+const TREND_POINTS: usize = 30;
+
 const HELP: &str = "The Queues tab is where you can view information \
-on existing queues.
+on existing queues. The trend column draws the total \
+depth of each queue over the last refreshes, red when \
+it grew and green when it drained.
 
 Keys:
   - h: previous tab
   - l: next tab
   - k: previous row
   - j: next row
+  - s: sort by the next column, S: toggle ascending/descending
+  - click on a header: sort by that column, again to reverse
+  - c: choose the columns shown, their order and widths
+  - /: filter rows, tab switches between substring, glob \
+and regex, return keeps the filter, esc clears it
   - p: drop message into queue from clipboard
   - ctrl + p: pop message from queue onto clipboard
+  - v: view the next message of the queue, leaving it queued
   - d: purge selected queue
-  - return: select
+  - e: compose a message in the payload editor
+  - E: compose a message in $EDITOR, published on exit
+  - return: open the detail view of the selected queue, \
+or select in the file explorer and confirmation
   - f: open/close file explorer
   - backspace: go to parent in file explorer
-  - ?: close the help menu";
+  - b: bulk publish the highlighted file or directory
+  - esc: cancel a running bulk publish
+  - ?: close the help menu
+
+Purging asks for a confirmation: j/k to pick, return to \
+confirm and esc to cancel. Queues with many ready messages, \
+above confirm_above in the config, and every queue of a \
+production profile need their name typed instead.
+
+The queue detail view charts the depth and rates of the \
+queue, starting with the history kept by the management API, \
+and its memory from when the view opens. w switches the window between 1m, 10m, 1h and 8h, esc or \
+return closes it.
+
+In the payload viewer:
+  - j/k: scroll
+  - m: switch between JSON, XML, text and hex
+  - /: search, n/N: next/previous match
+  - y: copy the payload to the clipboard
+  - esc: close
+
+In the payload editor:
+  - ctrl + s: publish to the selected queue
+  - ctrl + f: pretty-print JSON
+  - esc: close, keeping the draft
+
+A .jsonl file is published one message per line. Lines \
+may be objects with a payload and an optional routing_key, \
+properties and headers. A directory publishes every file \
+directly inside it.
+
+In read-only mode, from --read-only or a read_only \
+profile, publishing and purging are disabled. Viewing \
+and copying messages still work, as they are requeued.";
 
 pub struct QueuesPane<'a, M>
 where
     M: ManagementClient,
 {
     table: Datatable<QueueInfo>,
+    search: SearchBar,
+    chooser: Option<ColumnChooser>,
+    // Where the table was last drawn, to map
+    // header clicks to columns.
+    table_area: Rect,
+    // Depths of each queue over the last
+    // refreshes, keyed by (vhost, name).
+    trends: HashMap<(String, String), TimeSeries>,
     confirmation: ConfirmationBox<'a>,
     data_chan: mpsc::Receiver<Vec<QueueInfo>>,
     explorer: FileNavigator,
@@ -51,81 +127,337 @@ where
     // to the parent app. Probably not best
     // for an indv pane to have a clipboard context
     // when there is only 1 system clipboard..
-    clipboard: ClipboardContext,
-    notif: Option<Notification>,
+    clipboard: Clipboard,
+    notifier: Notifier,
+    safety: Safety,
+    bulk: Option<BulkJob>,
+    publish_rate: u32,
+    editor: TextEditor,
+    // (vhost, name) of the queue a composed
+    // message will be published to.
+    edit_target: Option<(String, String)>,
+    should_edit: bool,
+    should_edit_external: bool,
+    viewer: Option<PayloadViewer>,
+    detail: Option<QueueDetail>,
     should_show_help: bool,
     should_confirm: bool,
+    // (vhost, name) of the queue the open
+    // confirmation will purge.
+    purge_target: Option<(String, String)>,
     should_open_files: bool,
 }
 
+/// Adds the depth of every queue to its trend,
+/// forgetting the queues that are gone.
+fn record_trends(trends: &mut HashMap<(String, String), TimeSeries>, queues: &[QueueInfo]) {
+    let t = now();
+    let ids: HashSet<_> = queues.iter().map(|q| q.id()).collect();
+    trends.retain(|id, _| ids.contains(id));
+    for q in queues {
+        trends
+            .entry(q.id())
+            .or_insert_with(|| TimeSeries::with_capacity(TREND_POINTS))
+            .push(t, q.total as f64);
+    }
+}
+
 impl<'a, M> QueuesPane<'a, M>
 where
-    M: ManagementClient,
+    M: ManagementClient + 'static,
 {
-    pub fn new(client: Arc<M>, data_chan: mpsc::Receiver<Vec<QueueInfo>>) -> Self {
-        let data = client.get_queues_info();
-        let table = Datatable::<QueueInfo>::new(data);
+    pub fn new(
+        client: Arc<M>,
+        data_chan: mpsc::Receiver<Vec<QueueInfo>>,
+        config: &AppConfig,
+        notifier: Notifier,
+    ) -> Self {
+        // Refresh errors are notified by the data thread.
+        let data = client.get_queues_info().unwrap_or_default();
+        let mut trends = HashMap::new();
+        record_trends(&mut trends, &data);
+        let mut table = Datatable::<QueueInfo>::new(data);
+        table.set_columns(config.queue_columns.clone());
         Self {
             table,
+            search: SearchBar::default(),
+            chooser: None,
+            table_area: Rect::default(),
+            trends,
             confirmation: ConfirmationBox::default(),
             explorer: FileNavigator::default(),
-            notif: None,
+            notifier,
+            safety: config.safety.clone(),
+            bulk: None,
+            publish_rate: config.publish_rate,
+            editor: TextEditor::default(),
+            edit_target: None,
+            should_edit: false,
+            should_edit_external: false,
+            viewer: None,
+            detail: None,
             data_chan,
             client: Arc::clone(&client),
-            // TODO handle unable to make clipboard?
-            clipboard: ClipboardProvider::new().unwrap(),
+            clipboard: Clipboard::default(),
             should_show_help: false,
             should_confirm: false,
+            purge_target: None,
             should_open_files: false,
         }
     }
+
+    /// Publishes every message found at `path` into the
+    /// vhost of the selected queue. Messages without their
+    /// own routing key go to the selected queue.
+    fn start_bulk(&mut self, path: &Path) {
+        let Some(info) = self.table.selected() else {
+            return;
+        };
+        match bulk::load_messages(path, &info.name) {
+            Ok(messages) => {
+                self.bulk = Some(BulkJob::spawn(
+                    Arc::clone(&self.client),
+                    info.vhost.clone(),
+                    messages,
+                    self.publish_rate,
+                ));
+                self.should_open_files = false;
+            }
+            Err(e) => {
+                self.notifier.error(format!("Unable to read: {}", e));
+            }
+        }
+    }
+
+    /// Whether `action` may be taken,
+    /// telling why when it may not.
+    fn allows(&self, action: Action) -> bool {
+        match self.safety.check(action) {
+            Ok(()) => true,
+            Err(why) => {
+                self.notifier.warning(why);
+                false
+            }
+        }
+    }
+
+    /// (vhost, name) of the selected queue, as
+    /// `Identifiable::id` gives it.
+    fn selected_target(&self) -> Option<(String, String)> {
+        self.table.selected().map(|info| info.id())
+    }
+
+    /// Notifies how a publish to `queue` went, with
+    /// `done` once it went out.
+    fn report_publish(&self, res: Result<PublishResponse, ApiError>, queue: &str, done: String) {
+        match res {
+            Ok(r) if r.effect == Effect::DryRun => self
+                .notifier
+                .info(format!("Dry run: would publish to {}", queue)),
+            Ok(_) => self.notifier.success(done),
+            Err(e) => self.notifier.error(format!("Publish failed: {}", e)),
+        }
+    }
+
+    /// Publishes a composed payload to the queue
+    /// the editor was opened for.
+    fn publish_composed(&mut self, payload: String) {
+        if let Some((vhost, name)) = &self.edit_target {
+            let res = self.client.post_queue_payload(name.clone(), vhost, payload);
+            self.report_publish(res, name, format!("Published to {}!", name));
+        }
+    }
+
+    fn handle_editor_key(&mut self, key: Key) {
+        match key {
+            Key::Esc => {
+                self.should_edit = false;
+            }
+            Key::Ctrl('s') => {
+                if self.editor.is_empty() {
+                    self.notifier.warning("Nothing to publish!");
+                } else {
+                    let payload = self.editor.text();
+                    self.publish_composed(payload);
+                    self.editor = TextEditor::default();
+                    self.should_edit = false;
+                }
+            }
+            Key::Ctrl('f') => {
+                if let Err(e) = self.editor.format_json() {
+                    self.notifier.error(format!("Invalid JSON: {}", e));
+                }
+            }
+            _ => self.editor.handle_key(key),
+        }
+    }
+
+    fn handle_viewer_key(&mut self, key: Key) {
+        let Some(viewer) = &mut self.viewer else {
+            return;
+        };
+        if viewer.is_searching() {
+            viewer.handle_key(key);
+            return;
+        }
+        match key {
+            Key::Esc => self.viewer = None,
+            Key::Char('y') => match self.clipboard.set_contents(viewer.payload_text()) {
+                Ok(_) => self
+                    .notifier
+                    .success(format!("Copied to clipboard ({})!", self.clipboard.name())),
+                Err(e) => self.notifier.error(format!("Clipboard unavailable: {}", e)),
+            },
+            _ => viewer.handle_key(key),
+        }
+    }
+
+    fn load_detail_history(&mut self) {
+        let Some(detail) = &mut self.detail else {
+            return;
+        };
+        let (vhost, name) = detail.id();
+        match self
+            .client
+            .get_queue_history(&name, &vhost, detail.window())
+        {
+            Ok(h) => detail.seed(&h),
+            Err(e) => {
+                self.notifier.warning(format!("No history: {}", e));
+            }
+        }
+    }
+
+    fn handle_chooser_key(&mut self, key: Key) {
+        let Some(chooser) = &mut self.chooser else {
+            return;
+        };
+        match key {
+            Key::Esc | Key::Char('c') | Key::Char('\n') => self.chooser = None,
+            _ => {
+                if chooser.handle_key(key) {
+                    self.table.set_columns(chooser.columns());
+                }
+            }
+        }
+    }
+
+    /// Purges the queue the open confirmation was for,
+    /// if confirmed, and closes the confirmation.
+    fn confirm_purge(&mut self) {
+        // The purge goes to the queue shown when the box
+        // was opened, never to whatever is selected now.
+        let target = self.purge_target.take();
+        let confirmed = target.filter(|_| self.confirmation.is_confirmed());
+        if let Some((vhost, name)) = confirmed {
+            // The table may be a refresh behind, so the
+            // broker is asked whether the queue is still there.
+            match self.client.get_queue(&name, &vhost) {
+                Err(e) if e.status() == Some(StatusCode::NOT_FOUND) => self
+                    .notifier
+                    .warning(format!("{} no longer exists, nothing purged", name)),
+                Err(e) => self
+                    .notifier
+                    .error(format!("Unable to check {}, nothing purged: {}", name, e)),
+                Ok(_) => match self.client.purge_queue(&name, &vhost) {
+                    Ok(Effect::DryRun) => {
+                        self.notifier.info(format!("Dry run: would purge {}", name))
+                    }
+                    Ok(Effect::Applied) => self.notifier.success(format!("Purged {}!", name)),
+                    Err(e) => self.notifier.error(format!("Purge failed: {}", e)),
+                },
+            }
+        } else if self.confirmation.is_typing() {
+            self.notifier.warning("Name not matching, nothing purged");
+        }
+        self.confirmation.reset();
+        self.should_confirm = false;
+    }
+
+    fn handle_search_key(&mut self, key: Key) {
+        if self.search.handle_key(key) {
+            self.table.set_filter(self.search.filter());
+        }
+    }
+
+    fn handle_bulk_key(&mut self, key: Key) {
+        if let Some(job) = &self.bulk {
+            match key {
+                Key::Esc if !job.summary.finished => job.cancel(),
+                Key::Char('\n') if job.summary.finished => self.bulk = None,
+                _ => {}
+            }
+        }
+    }
 }
 
 impl<M, B> Drawable<B> for QueuesPane<'_, M>
 where
-    M: ManagementClient,
+    M: ManagementClient + 'static,
     B: Backend,
 {
     fn draw(&mut self, f: &mut Frame<B>, area: Rect) {
-        let data = self.table.data.get();
+        let search_height = if self.search.is_visible() { 1 } else { 0 };
         let rects = Layout::default()
-            .constraints([Constraint::Percentage(100)].as_ref())
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(search_height)].as_ref())
             .margin(1)
             .split(area);
         let selected_style = Style::default().add_modifier(Modifier::REVERSED);
         let normal_style = Style::default();
-        let header_literals = QueueInfo::headers();
-        let header_cells = header_literals
-            .iter()
-            .map(|h| Cell::from(*h).style(Style::default().fg(Color::Green)));
+        let header_cells = self
+            .table
+            .headers()
+            .into_iter()
+            .map(|h| Cell::from(h).style(Style::default().fg(Color::Green)));
         let header = Row::new(header_cells)
             .style(normal_style)
             .height(1)
             .bottom_margin(1);
-        let rows = data.iter().map(|r| {
-            let vecd = r.to_row();
-            let cells = vecd.iter().map(|c| Cell::from(c.clone()));
+        let has_selection = self.table.state.selected().is_some();
+        let widths = column_widths(&self.table.widths(), rects[0], has_selection);
+        let filter = self.table.filter();
+        let trend = self
+            .table
+            .columns()
+            .iter()
+            .position(|c| QueueInfo::columns()[c.field].key == "trend");
+        let trend_width = match trend.map(|i| widths[i]) {
+            Some(Constraint::Length(w)) => w as usize,
+            _ => 0,
+        };
+        let rows = self.table.rows().map(|r| {
+            let mut cells: Vec<Cell> = self
+                .table
+                .row(r)
+                .into_iter()
+                .map(|c| highlight_cell(c, filter))
+                .collect();
+            if let (Some(i), Some(series)) = (trend, self.trends.get(&r.id())) {
+                let color = match (series.first(), series.last()) {
+                    (Some(a), Some(b)) if b.1 > a.1 => Color::Red,
+                    (Some(a), Some(b)) if b.1 < a.1 => Color::Green,
+                    _ => Color::Gray,
+                };
+                let line = sparkline(series, trend_width);
+                cells[i] = Cell::from(Span::styled(line, Style::default().fg(color)));
+            }
             Row::new(cells).bottom_margin(1)
         });
         let t = Table::new(rows)
             .header(header)
             .block(Block::default().borders(Borders::ALL).title("Queues"))
             .highlight_style(selected_style)
-            .highlight_symbol(">> ")
-            .widths(&[
-                Constraint::Percentage(20),
-                Constraint::Percentage(10),
-                Constraint::Percentage(10),
-                Constraint::Percentage(10),
-                Constraint::Percentage(10),
-                Constraint::Percentage(10),
-                Constraint::Percentage(10),
-                Constraint::Percentage(10),
-                Constraint::Percentage(10),
-            ]);
+            .highlight_symbol(HIGHLIGHT_SYMBOL)
+            .widths(&widths);
+        self.table_area = rects[0];
         f.render_stateful_widget(t, rects[0], &mut self.table.state);
-        if let Some(n) = &self.notif {
-            n.draw(f, area);
+        if self.search.is_visible() {
+            self.search
+                .draw(f, rects[1], self.table.len(), self.table.total());
+        }
+        if let Some(detail) = &mut self.detail {
+            detail.draw(f, area);
         }
         if self.should_confirm {
             self.confirmation.draw(f, area);
@@ -133,6 +465,22 @@ where
         if self.should_open_files {
             self.explorer.draw(f, area);
         }
+        if let Some(job) = &self.bulk {
+            ProgressBox::new(&job.summary).draw(f, area);
+        }
+        if let Some(chooser) = &mut self.chooser {
+            chooser.draw(f, area);
+        }
+        if let Some(viewer) = &mut self.viewer {
+            viewer.draw(f, area);
+        }
+        if self.should_edit {
+            let title = match &self.edit_target {
+                Some((_, name)) => format!("Publish to {}", name),
+                None => "Publish".to_string(),
+            };
+            self.editor.draw(f, area, &title);
+        }
         if self.should_show_help {
             Help::new(HELP).draw(f, area);
         }
@@ -141,98 +489,193 @@ where
 
 impl<'a, M, B> StatefulPane<B> for QueuesPane<'a, M>
 where
-    M: ManagementClient,
+    M: ManagementClient + 'static,
     B: Backend,
 {
     fn handle_key(&mut self, key: Key) {
-        self.notif = None;
+        if self.bulk.is_some() {
+            self.handle_bulk_key(key);
+            return;
+        }
+        if self.should_edit {
+            self.handle_editor_key(key);
+            return;
+        }
+        if self.viewer.is_some() {
+            self.handle_viewer_key(key);
+            return;
+        }
+        if self.chooser.is_some() {
+            self.handle_chooser_key(key);
+            return;
+        }
+        if let Some(detail) = &mut self.detail {
+            match key {
+                Key::Esc | Key::Char('\n') => self.detail = None,
+                Key::Char('w') => {
+                    detail.next_window();
+                    self.load_detail_history();
+                }
+                _ => {}
+            }
+            return;
+        }
+        if self.search.is_typing() {
+            self.handle_search_key(key);
+            return;
+        }
+        if self.should_confirm {
+            match key {
+                Key::Char('\n') => self.confirm_purge(),
+                Key::Esc => {
+                    self.purge_target = None;
+                    self.confirmation.reset();
+                    self.should_confirm = false;
+                }
+                _ => self.confirmation.handle_key(key),
+            }
+            return;
+        }
         match key {
             Key::Char('j') => {
-                if self.should_confirm {
-                    self.confirmation.next();
-                } else if self.should_open_files {
+                if self.should_open_files {
                     self.explorer.next();
                 } else {
                     self.table.next();
                 }
             }
             Key::Char('k') => {
-                if self.should_confirm {
-                    self.confirmation.previous();
-                } else if self.should_open_files {
+                if self.should_open_files {
                     self.explorer.previous();
                 } else {
                     self.table.previous();
                 }
             }
             Key::Char('p') => {
-                if let Some(i) = self.table.state.selected() {
-                    // TODO handle clipboard fail.
-                    let body = self.clipboard.get_contents().unwrap();
-                    let queue_info = &self.table.data.get()[i];
-                    self.client.post_queue_payload(
-                        queue_info.name.clone(),
-                        &queue_info.vhost,
-                        body,
-                    );
-                    self.notif = Some(Notification::new("Pasted from clipboard!".to_string()));
+                if self.table.selected().is_none() || !self.allows(Action::Publish) {
+                    return;
+                }
+                if let Some(queue_info) = self.table.selected() {
+                    match self.clipboard.get_contents() {
+                        Ok(body) => {
+                            let res = self.client.post_queue_payload(
+                                queue_info.name.clone(),
+                                &queue_info.vhost,
+                                body,
+                            );
+                            let done = "Pasted from clipboard!".to_string();
+                            self.report_publish(res, &queue_info.name, done);
+                        }
+                        Err(e) => self.notifier.error(format!("Clipboard unavailable: {}", e)),
+                    }
                 }
             }
             Key::Ctrl('p') => {
-                if let Some(i) = self.table.state.selected() {
-                    let info = &self.table.data.get()[i];
+                if let Some(info) = self.table.selected() {
                     let res = self.client.pop_queue_item(&info.name, &info.vhost);
                     match res {
-                        Some(m) => {
-                            self.clipboard.set_contents(m.payload).unwrap();
-                            self.notif = Some(Notification::new("Copied to clipboard!".to_string()));
-                        }
-                        None => {
-                            self.notif = Some(Notification::new("No messages to copy!".to_string()));
-                        }
+                        Ok(Some(m)) => match self.clipboard.set_contents(m.payload) {
+                            Ok(_) => self.notifier.success(format!(
+                                "Copied to clipboard ({})!",
+                                self.clipboard.name()
+                            )),
+                            Err(e) => self.notifier.error(format!("Clipboard unavailable: {}", e)),
+                        },
+                        Ok(None) => self.notifier.info("No messages to copy!"),
+                        Err(e) => self.notifier.error(format!("Unable to get message: {}", e)),
+                    }
+                }
+            }
+            Key::Char('v') => {
+                if let Some(info) = self.table.selected() {
+                    match self.client.pop_queue_item(&info.name, &info.vhost) {
+                        Ok(Some(m)) => self.viewer = Some(PayloadViewer::new(&m)),
+                        Ok(None) => self.notifier.info("No messages to view!"),
+                        Err(e) => self.notifier.error(format!("Unable to get message: {}", e)),
                     }
                 }
             }
             Key::Char('d') => {
-                if self.table.state.selected().is_some() {
+                if self.table.selected().is_none() || !self.allows(Action::Purge) {
+                    return;
+                }
+                if let Some(info) = self.table.selected() {
+                    let cluster = self
+                        .client
+                        .get_overview()
+                        .map_or_else(|_| "unknown".to_string(), |o| o.cluster_name);
+                    self.confirmation.set_action(format!(
+                        "Purge {}\n{} ready messages will be lost\nvhost {} on cluster {}",
+                        info.name, info.ready, info.vhost, cluster
+                    ));
+                    if self.safety.needs_typed_confirmation(info.ready) {
+                        self.confirmation.require_typing(info.name.clone());
+                    }
+                    self.purge_target = Some(info.id());
                     self.should_confirm = true;
                 }
             }
-            Key::Char('f') => {
+            // The file explorer only serves to publish.
+            Key::Char('f') if self.should_open_files || self.allows(Action::Publish) => {
                 self.should_open_files = !self.should_open_files;
             }
+            Key::Char('e') if self.table.selected().is_some() && self.allows(Action::Publish) => {
+                self.edit_target = self.selected_target();
+                self.should_edit = true;
+            }
+            Key::Char('E') if self.table.selected().is_some() && self.allows(Action::Publish) => {
+                self.edit_target = self.selected_target();
+                self.should_edit_external = true;
+            }
             Key::Char('\n') => {
-                if self.should_confirm {
-                    // The confirmation box is already open and a
-                    // second enter command has been issued.
-                    if self.confirmation.is_confirmed() {
-                        if let Some(i) = self.table.state.selected() {
-                            let info = &self.table.data.get()[i];
-                            self.client.purge_queue(&info.name, &info.vhost);
-                            self.notif = Some(Notification::new("Queue purged!".to_string()));
-                        }
-                    }
-                    self.confirmation.reset();
-                    self.should_confirm = false;
-                } else if self.should_open_files {
+                if self.should_open_files {
                     if let Some(f) = self.explorer.select() {
-                        if let Some(i) = self.table.state.selected() {
-                            // TODO handle unable to read content
-                            let body = fs::read_to_string(f).unwrap();
-                            let info = &self.table.data.get()[i];
-                            self.client
-                                .post_queue_payload(info.name.clone(), &info.vhost, body);
+                        if bulk::is_jsonl(&f) {
+                            self.start_bulk(&f);
+                        } else if let Some(info) = self.table.selected() {
+                            match fs::read_to_string(f) {
+                                Ok(body) => {
+                                    let res = self.client.post_queue_payload(
+                                        info.name.clone(),
+                                        &info.vhost,
+                                        body,
+                                    );
+                                    let done = "Posted from file!".to_string();
+                                    self.report_publish(res, &info.name, done);
+                                }
+                                Err(e) => self.notifier.error(format!("Unable to read: {}", e)),
+                            }
                             self.should_open_files = false;
-                            self.notif = Some(Notification::new("Posted from file!".to_string()));
                         }
                     }
+                } else if let Some(info) = self.table.selected() {
+                    self.detail = Some(QueueDetail::new(info));
+                    self.load_detail_history();
                 }
             }
-            Key::Backspace => {
-                if self.should_open_files {
-                    self.explorer.select_parent();
+            Key::Char('b') if self.should_open_files => {
+                if let Some(path) = self.explorer.highlighted().cloned() {
+                    self.start_bulk(&path);
                 }
             }
+            Key::Backspace if self.should_open_files => {
+                self.explorer.select_parent();
+            }
+            Key::Char('/') if !self.should_open_files => {
+                self.search.open();
+            }
+            Key::Esc if self.search.is_visible() => {
+                self.handle_search_key(key);
+            }
+            Key::Char('s') => {
+                self.table.cycle_sort();
+            }
+            Key::Char('S') => {
+                self.table.toggle_sort_order();
+            }
+            Key::Char('c') => {
+                self.chooser = Some(ColumnChooser::new::<QueueInfo>(self.table.columns()));
+            }
             Key::Char('?') => {
                 self.should_show_help = !self.should_show_help;
             }
@@ -240,9 +683,68 @@ where
         }
     }
 
+    fn handle_mouse(&mut self, event: MouseEvent) {
+        let modal = self.bulk.is_some()
+            || self.should_edit
+            || self.viewer.is_some()
+            || self.chooser.is_some()
+            || self.detail.is_some()
+            || self.should_confirm
+            || self.should_open_files
+            || self.should_show_help;
+        if modal {
+            return;
+        }
+        if let MouseEvent::Press(MouseButton::Left, x, y) = event {
+            let has_selection = self.table.state.selected().is_some();
+            let widths = column_widths(&self.table.widths(), self.table_area, has_selection);
+            let (x, y) = (x.saturating_sub(1), y.saturating_sub(1));
+            if let Some(c) = header_column(self.table_area, &widths, has_selection, x, y) {
+                self.table.sort_by(c);
+            }
+        }
+    }
+
     fn update(&mut self) {
         if let Some(d) = self.data_chan.try_iter().next() {
-            self.table.data = DataContainer { entries: d };
+            record_trends(&mut self.trends, &d);
+            self.table.update(d);
+            if let Some(detail) = &mut self.detail {
+                if let Some(info) = self.table.find(&detail.id()) {
+                    detail.push(info);
+                }
+            }
+        }
+        if let Some(job) = &mut self.bulk {
+            job.poll();
+        }
+    }
+
+    fn captures_input(&self) -> bool {
+        self.should_edit
+            || self.search.is_typing()
+            || (self.should_confirm && self.confirmation.is_typing())
+            || self.viewer.as_ref().is_some_and(|v| v.is_searching())
+    }
+
+    fn external_edit(&mut self) -> Option<String> {
+        if self.should_edit_external {
+            self.should_edit_external = false;
+            Some(String::new())
+        } else {
+            None
+        }
+    }
+
+    fn external_edit_done(&mut self, result: io::Result<String>) {
+        match result {
+            Ok(payload) if payload.trim().is_empty() => {
+                self.notifier.warning("Nothing to publish!");
+            }
+            Ok(payload) => self.publish_composed(payload),
+            Err(e) => {
+                self.notifier.error(format!("Editor failed: {}", e));
+            }
         }
     }
 }
@@ -0,0 +1,264 @@
+use super::{Drawable, StatefulPane, HIGHLIGHT_SYMBOL};
+use crate::{
+    models::{ExchangeBindings, QueueInfo},
+    topology::{self, Node, TreeLine},
+    widgets::{help::Help, notif::Notifier},
+    Datatable, ManagementClient,
+};
+
+use std::{collections::HashMap, sync::Arc};
+
+use termion::event::Key;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Paragraph, Row, Table, Wrap},
+    Frame,
+};
+
+const HELP: &str = "The Topology tab shows where messages \
+published to an exchange can go, as a tree of the bindings \
+of a vhost: exchange, routing key, then queue or exchange, \
+down to dead-letter exchanges set in queue arguments or \
+policies. The bindings of the default exchange are left \
+out. What follows an exchange reached several ways is \
+drawn the first time only, later ones read (see above).
+
+Keys:
+  - h: previous tab
+  - l: next tab
+  - k: previous line
+  - j: next line
+  - v: next vhost, V: previous vhost
+  - r: reload the bindings
+  - ?: close the help menu
+
+The path of the selected line, from the exchange messages \
+are published to, shows at the bottom and in green.";
+
+pub struct TopologyPane<M>
+where
+    M: ManagementClient,
+{
+    client: Arc<M>,
+    notifier: Notifier,
+    vhosts: Datatable<String>,
+    bindings: Vec<ExchangeBindings>,
+    queues: Vec<QueueInfo>,
+    // Exchange types, by vhost and name.
+    types: HashMap<(String, String), String>,
+    lines: Datatable<TreeLine>,
+    should_show_help: bool,
+}
+
+impl<M> TopologyPane<M>
+where
+    M: ManagementClient,
+{
+    pub fn new(client: Arc<M>, notifier: Notifier) -> Self {
+        let mut pane = Self {
+            client,
+            notifier,
+            vhosts: Datatable::default(),
+            bindings: Vec::new(),
+            queues: Vec::new(),
+            types: HashMap::new(),
+            lines: Datatable::default(),
+            should_show_help: false,
+        };
+        pane.reload();
+        pane
+    }
+
+    fn reload(&mut self) {
+        let fetched = self.client.get_exchange_overview().and_then(|e| {
+            Ok((
+                e,
+                self.client.get_bindings()?,
+                self.client.get_queues_info()?,
+            ))
+        });
+        let (exchanges, bindings, queues) = match fetched {
+            Ok(f) => f,
+            Err(e) => {
+                self.notifier
+                    .error(format!("Unable to get the bindings: {}", e));
+                return;
+            }
+        };
+        let selected = self.vhosts.selected().cloned();
+        let mut vhosts: Vec<_> = exchanges.iter().map(|e| e.vhost.clone()).collect();
+        vhosts.sort();
+        vhosts.dedup();
+        let index = selected
+            .and_then(|s| vhosts.iter().position(|v| *v == s))
+            .unwrap_or(0);
+        self.vhosts = Datatable::new(vhosts);
+        if !self.vhosts.is_empty() {
+            self.vhosts.state.select(Some(index));
+        }
+        self.types = exchanges
+            .into_iter()
+            .map(|e| ((e.vhost, e.name), e.t))
+            .collect();
+        self.bindings = bindings;
+        self.queues = queues;
+        self.build_tree();
+    }
+
+    fn build_tree(&mut self) {
+        let lines = match self.vhosts.selected() {
+            Some(vhost) => topology::tree(&topology::edges(vhost, &self.bindings, &self.queues)),
+            None => Vec::new(),
+        };
+        let empty = lines.is_empty();
+        self.lines = Datatable::new(lines);
+        if !empty {
+            self.lines.state.select(Some(0));
+        }
+    }
+
+    fn node_label(&self, node: &Node) -> String {
+        match node {
+            Node::Exchange(name) => {
+                let vhost = self.vhosts.selected().cloned().unwrap_or_default();
+                match self.types.get(&(vhost, name.clone())) {
+                    Some(t) => format!("exchange {} ({})", node.name(), t),
+                    None => format!("exchange {}", node.name()),
+                }
+            }
+            Node::Queue(_) => format!("queue {}", node.name()),
+        }
+    }
+
+    fn line_label(&self, line: &TreeLine) -> String {
+        let mut label = line.prefix.clone();
+        if let Some(link) = &line.link {
+            label.push_str(&link.describe());
+            label.push_str(" → ");
+        }
+        label.push_str(&self.node_label(&line.node));
+        if line.cycle {
+            label.push_str(" (cycle)");
+        } else if line.repeat {
+            label.push_str(" (see above)");
+        }
+        label
+    }
+
+    /// Indexes of the lines on the way to the selected one.
+    fn selected_path(&self) -> Vec<usize> {
+        match self.lines.state.selected() {
+            Some(i) => topology::path(self.lines.data.get(), i),
+            None => Vec::new(),
+        }
+    }
+}
+
+impl<M, B> Drawable<B> for TopologyPane<M>
+where
+    M: ManagementClient,
+    B: Backend,
+{
+    fn draw(&mut self, f: &mut Frame<B>, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(20), Constraint::Percentage(80)])
+            .margin(1)
+            .split(area);
+        let right = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(4)])
+            .split(chunks[1]);
+        let selected_style = Style::default().add_modifier(Modifier::REVERSED);
+        let rows = self
+            .vhosts
+            .data
+            .get()
+            .iter()
+            .map(|v| Row::new(vec![v.clone()]));
+        let t = Table::new(rows)
+            .block(Block::default().borders(Borders::ALL).title("Vhosts"))
+            .highlight_style(selected_style)
+            .widths(&[Constraint::Percentage(100)]);
+        f.render_stateful_widget(t, chunks[0], &mut self.vhosts.state);
+
+        let path = self.selected_path();
+        let lines = self.lines.data.get();
+        let description = path
+            .iter()
+            .map(|&i| match &lines[i].link {
+                Some(link) => format!("{} → {}", link.describe(), self.node_label(&lines[i].node)),
+                None => self.node_label(&lines[i].node),
+            })
+            .collect::<Vec<_>>()
+            .join(" → ");
+        let rows: Vec<_> = lines
+            .iter()
+            .enumerate()
+            .map(|(i, l)| {
+                let style = if l.cycle {
+                    Style::default().fg(Color::Red)
+                } else if path.contains(&i) {
+                    Style::default().fg(Color::Green)
+                } else {
+                    Style::default()
+                };
+                Row::new(vec![self.line_label(l)]).style(style)
+            })
+            .collect();
+        let title = match self.vhosts.selected() {
+            Some(v) => format!("Routing in vhost {}", v),
+            None => "Routing".to_string(),
+        };
+        let t = Table::new(rows)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(selected_style)
+            .highlight_symbol(HIGHLIGHT_SYMBOL)
+            .widths(&[Constraint::Percentage(100)]);
+        f.render_stateful_widget(t, right[0], &mut self.lines.state);
+
+        let text = if description.is_empty() {
+            "No bindings in this vhost".to_string()
+        } else {
+            description
+        };
+        let p = Paragraph::new(text)
+            .block(Block::default().borders(Borders::ALL).title("Path"))
+            .wrap(Wrap { trim: true });
+        f.render_widget(p, right[1]);
+
+        if self.should_show_help {
+            Help::new(HELP).draw(f, area);
+        }
+    }
+}
+
+impl<M, B> StatefulPane<B> for TopologyPane<M>
+where
+    M: ManagementClient,
+    B: Backend,
+{
+    fn handle_key(&mut self, key: Key) {
+        match key {
+            Key::Char('j') => self.lines.next(),
+            Key::Char('k') => self.lines.previous(),
+            Key::Char('v') => {
+                self.vhosts.next();
+                self.build_tree();
+            }
+            Key::Char('V') => {
+                self.vhosts.previous();
+                self.build_tree();
+            }
+            Key::Char('r') => self.reload(),
+            Key::Char('?') => {
+                self.should_show_help = !self.should_show_help;
+            }
+            _ => {}
+        }
+    }
+
+    fn update(&mut self) {}
+}
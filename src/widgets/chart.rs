@@ -1,6 +1,15 @@
+use crate::{
+    models::{human_bytes, HistoryWindow, Sample},
+    series::TimeSeries,
+};
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::{Local, TimeZone};
+
 use tui::{
     backend::Backend,
-    layout::Rect,
+    layout::{Constraint, Rect},
     style::{Color, Modifier, Style},
     symbols,
     text::Span,
@@ -8,58 +17,228 @@ use tui::{
     Frame,
 };
 
-const X_WINDOW: u64 = 100;
 const Y_PADDING: f64 = 1.1;
 
+/// What the values of a chart measure,
+/// for its labels.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum Unit {
+    #[default]
+    Count,
+    Rate,
+    Bytes,
+}
+
+impl Unit {
+    pub fn format(self, n: f64) -> String {
+        match self {
+            Unit::Count => scaled(n),
+            Unit::Rate => format!("{} msg/s", scaled(n)),
+            Unit::Bytes => human_bytes(n.max(0.) as u64),
+        }
+    }
+}
+
+/// Formats `n` with a k, M or G suffix past a thousand,
+/// keeping up to two decimals for small values.
+pub fn scaled(n: f64) -> String {
+    const SUFFIXES: [(f64, &str); 3] = [(1e9, "G"), (1e6, "M"), (1e3, "k")];
+    let trim = |s: String| match s.contains('.') {
+        true => s.trim_end_matches('0').trim_end_matches('.').to_string(),
+        false => s,
+    };
+    for (scale, suffix) in SUFFIXES {
+        if n.abs() >= scale {
+            return format!("{}{}", trim(format!("{:.1}", n / scale)), suffix);
+        }
+    }
+    trim(format!("{:.2}", n))
+}
+
+/// Local wall-clock time of `secs` since the epoch,
+/// with seconds when `precise`.
+pub fn clock(secs: f64, precise: bool) -> String {
+    let Some(t) = Local.timestamp_opt(secs as i64, 0).earliest() else {
+        return String::new();
+    };
+    let format = if precise { "%H:%M:%S" } else { "%H:%M" };
+    t.format(format).to_string()
+}
+
+/// How the points of a chart are drawn.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum ChartStyle {
+    #[default]
+    Line,
+    Dot,
+    Bar,
+}
+
+impl ChartStyle {
+    pub fn next(self) -> Self {
+        match self {
+            ChartStyle::Line => ChartStyle::Dot,
+            ChartStyle::Dot => ChartStyle::Bar,
+            ChartStyle::Bar => ChartStyle::Line,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ChartStyle::Line => "line",
+            ChartStyle::Dot => "dot",
+            ChartStyle::Bar => "bar",
+        }
+    }
+
+    /// The points to plot for `data`. Bars are drawn
+    /// as a line going up and down from zero at
+    /// each point.
+    fn points(self, data: Vec<(f64, f64)>) -> Vec<(f64, f64)> {
+        match self {
+            ChartStyle::Bar => data
+                .into_iter()
+                .flat_map(|(x, y)| [(x, 0.), (x, y), (x, 0.)])
+                .collect(),
+            _ => data,
+        }
+    }
+}
+
+/// Seconds since the epoch, the x axis of charts.
+pub fn now() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0., |d| d.as_secs_f64())
+}
+
+/// Points of length samples, oldest first.
+pub fn lengths(samples: &[Sample]) -> Vec<(f64, f64)> {
+    let mut points: Vec<_> = samples
+        .iter()
+        .map(|s| (s.timestamp as f64 / 1000., s.sample))
+        .collect();
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+    points
+}
+
+/// Per second rates of counter samples, oldest first. A
+/// counter going down (node restart) counts as no traffic.
+pub fn rates(samples: &[Sample]) -> Vec<(f64, f64)> {
+    lengths(samples)
+        .windows(2)
+        .filter(|w| w[1].0 > w[0].0)
+        .map(|w| (w[1].0, ((w[1].1 - w[0].1) / (w[1].0 - w[0].0)).max(0.)))
+        .collect()
+}
+
+/// Bars of a sparkline, lowest first.
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Draws `series` as a line of bars at most `width`
+/// chars long, scaled between its min and max.
+pub fn sparkline(series: &TimeSeries, width: usize) -> String {
+    let (Some(min), Some(max)) = (series.min(), series.max()) else {
+        return String::new();
+    };
+    let top = (SPARKS.len() - 1) as f64;
+    series
+        .downsample(width)
+        .into_iter()
+        .map(|(_, v)| match max > min {
+            true => SPARKS[((v - min) / (max - min) * top).round() as usize],
+            false => SPARKS[0],
+        })
+        .collect()
+}
+
+/// Most points kept by a chart series. Pushes closer
+/// together than the window allows for are dropped.
+const CAPACITY: usize = 1024;
+
 /// Container for data
 /// format that feeds into
-/// chart draw.
-#[derive(Default)]
+/// chart draw. Points are
+/// (seconds since the epoch, value).
 pub struct ChartData {
-    data: Vec<(f64, f64)>,
-    counter: f64,
+    series: TimeSeries,
+    window: f64,
+    // Latest value pushed, even when
+    // too close to the last point.
+    last: f64,
+    // How far the clock of the broker the points
+    // were seeded from is ahead of the local one.
+    offset: f64,
+}
+
+impl Default for ChartData {
+    fn default() -> Self {
+        Self {
+            series: TimeSeries::with_capacity(CAPACITY),
+            window: HistoryWindow::default().secs() as f64,
+            last: 0.,
+            offset: 0.,
+        }
+    }
 }
 
 impl ChartData {
-    /// pushes to the data vec, but only keeps
-    /// as many points as X_WINDOW.
+    /// pushes a value taken now, only keeping
+    /// the points within the window. It is stamped
+    /// with the time of the seeded points, and never
+    /// before the last point.
     pub fn push(&mut self, n: f64) {
-        self.data.push((self.counter.clone(), n));
-        self.counter += 1.0;
-        if self.data.len() > X_WINDOW as usize {
-            self.data.remove(0);
+        let t = now() + self.offset;
+        let t = self.series.last().map_or(t, |p| t.max(p.0));
+        self.last = n;
+        let resolution = self.window / self.series.capacity() as f64;
+        if self.series.last().is_none_or(|p| t - p.0 >= resolution) {
+            self.series.push(t, n);
+        }
+        self.trim();
+    }
+
+    /// Replaces the points with past ones, oldest first,
+    /// the last one taken now by the broker's clock.
+    /// Nothing changes when there are none.
+    pub fn seed(&mut self, points: Vec<(f64, f64)>) {
+        if let Some(p) = points.last() {
+            self.last = p.1;
+            self.offset = p.0 - now();
+            self.series.clear();
+            points.into_iter().for_each(|(t, n)| self.series.push(t, n));
+            self.trim();
         }
     }
 
+    pub fn set_window(&mut self, window: HistoryWindow) {
+        self.window = window.secs() as f64;
+        self.trim();
+    }
+
+    fn trim(&mut self) {
+        self.series.retain_since(self.x_max() - self.window);
+    }
+
     pub fn y_max(&self) -> f64 {
-        self.data
-            .iter()
-            .cloned()
-            .map(|n| n.1)
-            .fold(0. / 0., f64::max)
+        self.series.max().unwrap_or(f64::NAN)
     }
+
     pub fn y_min(&self) -> f64 {
-        self.data
-            .iter()
-            .cloned()
-            .map(|n| n.1)
-            .fold(0. / 0., f64::min)
+        self.series.min().unwrap_or(f64::NAN)
     }
 
     pub fn x_max(&self) -> f64 {
-        // save an iteration because
-        // we know that the x values
-        // only increase and that
-        // the most recent value is
-        // equal to the counter.
-        self.counter
+        self.series.last().map_or(0., |p| p.0)
     }
 
     pub fn last_value(&self) -> f64 {
-        // this is safe where this is used because we always seed with
-        // at least 1 value. If we wanted to be abstract, we would
-        // check that data is not empty.
-        self.data.iter().last().unwrap().1
+        self.last
+    }
+
+    /// At most `n` points to plot.
+    fn points(&self, n: usize) -> Vec<(f64, f64)> {
+        self.series.downsample(n)
     }
 }
 
@@ -68,56 +247,146 @@ impl ChartData {
 pub struct RChart<'a, const W: usize> {
     data: [&'a ChartData; W],
     colors: [Color; W],
+    names: [&'a str; W],
+    title: String,
+    unit: Unit,
+    style: ChartStyle,
 }
 
 impl<'a, const W: usize> RChart<'a, W> {
     pub fn new(data: [&'a ChartData; W], colors: [Color; W]) -> Self {
-        Self { data, colors }
+        Self {
+            data,
+            colors,
+            names: [""; W],
+            title: String::new(),
+            unit: Unit::default(),
+            style: ChartStyle::default(),
+        }
+    }
+
+    pub fn title(mut self, title: String) -> Self {
+        self.title = title;
+        self
+    }
+
+    /// Names of the series, shown in the legend.
+    pub fn names(mut self, names: [&'a str; W]) -> Self {
+        self.names = names;
+        self
+    }
+
+    pub fn unit(mut self, unit: Unit) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    pub fn style(mut self, style: ChartStyle) -> Self {
+        self.style = style;
+        self
     }
 
     pub fn draw<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
-        let y_max = self.data.iter().map(|d| d.y_max()).fold(0. / 0., f64::max);
-        let y_min = self.data.iter().map(|d| d.y_min()).fold(0. / 0., f64::min);
+        let y_max = self.data.iter().map(|d| d.y_max()).fold(f64::NAN, f64::max);
+        // Charts start at zero unless something went below.
+        let y_bottom = self.data.iter().map(|d| d.y_min()).fold(0., f64::min);
         let x_max = self.data.iter().map(|d| d.x_max()).fold(0., f64::max);
-        let datasets: Vec<Dataset> = self
+        // A flat line at zero still gets a scale.
+        let y_top = match y_max * Y_PADDING {
+            top if top > 0. => top,
+            _ => 1.,
+        };
+        // Braille packs two points across a cell, more
+        // would not show.
+        let resolution = area.width as usize * 2;
+        let points: Vec<Vec<(f64, f64)>> = self
             .data
+            .iter()
+            .map(|d| self.style.points(d.points(resolution)))
+            .collect();
+        let (marker, graph_type) = match self.style {
+            ChartStyle::Line | ChartStyle::Bar => (symbols::Marker::Braille, GraphType::Line),
+            ChartStyle::Dot => (symbols::Marker::Dot, GraphType::Scatter),
+        };
+        let datasets: Vec<Dataset> = points
             .iter()
             .enumerate()
-            .map(|(i, d)| {
+            .map(|(i, p)| {
                 Dataset::default()
-                    .marker(symbols::Marker::Dot)
+                    .name(self.names[i])
+                    .marker(marker)
                     .style(Style::default().fg(self.colors[i]))
-                    .graph_type(GraphType::Line)
-                    .data(d.data.as_slice())
+                    .graph_type(graph_type)
+                    .data(p.as_slice())
             })
             .collect();
-        let lb = if x_max < X_WINDOW as f64 {
-            0.
-        } else {
-            x_max - X_WINDOW as f64
-        };
+        let window = self.data.iter().map(|d| d.window).fold(0., f64::max);
+        let lb = x_max - window;
+        let precise = window <= HistoryWindow::OneMinute.secs() as f64;
+        let label = |s: String| Span::styled(s, Style::default().add_modifier(Modifier::BOLD));
+        let x_labels = [lb, lb + window / 2., x_max]
+            .iter()
+            .map(|&x| label(clock(x, precise)))
+            .collect();
+        let y_labels = [y_bottom, (y_bottom + y_top) / 2., y_top]
+            .iter()
+            .map(|&y| label(self.unit.format(y)))
+            .collect();
         let chart = Chart::new(datasets)
-            .block(Block::default().borders(Borders::ALL))
+            // Legends only give way on small charts.
+            .hidden_legend_constraints((Constraint::Ratio(1, 3), Constraint::Ratio(3, 4)))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(self.title.as_str()),
+            )
             .x_axis(
                 Axis::default()
                     .style(Style::default().fg(Color::Gray))
+                    .labels(x_labels)
                     .bounds([lb, x_max]),
             )
             .y_axis(
                 Axis::default()
                     .style(Style::default().fg(Color::Gray))
-                    .labels(vec![
-                        Span::styled(
-                            format!("{}", y_min as u64),
-                            Style::default().add_modifier(Modifier::BOLD),
-                        ),
-                        Span::styled(
-                            format!("{}", (y_max * Y_PADDING) as u64),
-                            Style::default().add_modifier(Modifier::BOLD),
-                        ),
-                    ])
-                    .bounds([0., y_max * Y_PADDING]),
+                    .labels(y_labels)
+                    .bounds([y_bottom, y_top]),
             );
         f.render_widget(chart, area);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{now, ChartData};
+    use crate::models::HistoryWindow;
+
+    use std::{thread, time::Duration};
+
+    /// Points seeded 5s apart over the last minute of a
+    /// broker clock `skew` seconds ahead, then a value
+    /// pushed a little later.
+    fn pushed_after_seed(skew: f64) -> ChartData {
+        let mut data = ChartData::default();
+        data.set_window(HistoryWindow::OneMinute);
+        let start = now() + skew - 45.;
+        data.seed((0..10).map(|i| (start + i as f64 * 5., i as f64)).collect());
+        thread::sleep(Duration::from_millis(100));
+        data.push(42.);
+        data
+    }
+
+    #[test]
+    fn live_points_follow_a_broker_clock_ahead() {
+        let data = pushed_after_seed(3600.);
+        assert_eq!(data.series.iter().count(), 11);
+        assert_eq!(data.series.last().map(|p| p.1), Some(42.));
+    }
+
+    #[test]
+    fn live_points_keep_history_from_a_broker_clock_behind() {
+        let data = pushed_after_seed(-3600.);
+        assert_eq!(data.series.iter().count(), 11);
+        assert_eq!(data.series.iter().next().map(|p| p.1), Some(0.));
+    }
+}
@@ -0,0 +1,136 @@
+use crate::{views::centered_rect, Column, ColumnDef, Datatable, Rowable};
+
+use termion::event::Key;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Rect},
+    style::{Color, Modifier, Style},
+    text::Span,
+    widgets::{Block, Borders, Cell, Clear, Row, Table},
+    Frame,
+};
+
+const WIDTH_STEP: u16 = 2;
+
+struct Entry {
+    field: usize,
+    width: u16,
+    shown: bool,
+}
+
+/// Popout picking which columns of a table are shown,
+/// in what order and how wide.
+pub struct ColumnChooser {
+    defs: &'static [ColumnDef],
+    table: Datatable<Entry>,
+}
+
+impl ColumnChooser {
+    /// Lists the `current` columns first, in order,
+    /// followed by the hidden ones.
+    pub fn new<T: Rowable>(current: &[Column]) -> Self {
+        let defs = T::columns();
+        let mut entries: Vec<Entry> = current
+            .iter()
+            .map(|c| Entry {
+                field: c.field,
+                width: c.width,
+                shown: true,
+            })
+            .collect();
+        for (field, d) in defs.iter().enumerate() {
+            if !current.iter().any(|c| c.field == field) {
+                entries.push(Entry {
+                    field,
+                    width: d.width,
+                    shown: false,
+                });
+            }
+        }
+        let mut table = Datatable::new(entries);
+        table.state.select(Some(0));
+        Self { defs, table }
+    }
+
+    pub fn columns(&self) -> Vec<Column> {
+        self.table
+            .rows()
+            .filter(|e| e.shown)
+            .map(|e| Column {
+                field: e.field,
+                width: e.width,
+            })
+            .collect()
+    }
+
+    fn swap(&mut self, up: bool) {
+        let Some(i) = self.table.state.selected() else {
+            return;
+        };
+        let entries = self.table.data.get_mut();
+        let j = match up {
+            true if i > 0 => i - 1,
+            false if i + 1 < entries.len() => i + 1,
+            _ => return,
+        };
+        entries.swap(i, j);
+        self.table.state.select(Some(j));
+    }
+
+    /// Handles a key, returning whether the
+    /// columns changed.
+    pub fn handle_key(&mut self, key: Key) -> bool {
+        let shown = self.columns().len();
+        let i = self.table.state.selected();
+        let entry = i.and_then(|i| self.table.data.get_mut().get_mut(i));
+        match (key, entry) {
+            (Key::Char('j'), _) => self.table.next(),
+            (Key::Char('k'), _) => self.table.previous(),
+            (Key::Char('J'), _) => self.swap(false),
+            (Key::Char('K'), _) => self.swap(true),
+            // The last shown column cannot be hidden.
+            (Key::Char(' '), Some(e)) if !e.shown || shown > 1 => e.shown = !e.shown,
+            (Key::Char('+'), Some(e)) => e.width = (e.width + WIDTH_STEP).min(100),
+            (Key::Char('-'), Some(e)) => e.width = e.width.saturating_sub(WIDTH_STEP).max(1),
+            _ => return false,
+        }
+        true
+    }
+
+    pub fn draw<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
+        let pop_area = centered_rect(40, 60, area);
+        let rows = self.table.rows().map(|e| {
+            let (mark, style) = match e.shown {
+                true => ("[x]", Style::default()),
+                false => ("[ ]", Style::default().fg(Color::DarkGray)),
+            };
+            Row::new(vec![
+                Cell::from(mark),
+                Cell::from(self.defs[e.field].title),
+                Cell::from(format!("{}%", e.width)),
+            ])
+            .style(style)
+        });
+        let total: u16 = self.columns().iter().map(|c| c.width).sum();
+        let title = format!(
+            "Columns ({}% used) space: show/hide, J/K: move, +/-: width",
+            total
+        );
+        let selected_style = Style::default().add_modifier(Modifier::REVERSED);
+        let t = Table::new(rows)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(Span::styled(title, Style::default().fg(Color::Yellow))),
+            )
+            .highlight_style(selected_style)
+            .highlight_symbol(">> ")
+            .widths(&[
+                Constraint::Length(3),
+                Constraint::Percentage(70),
+                Constraint::Length(5),
+            ]);
+        f.render_widget(Clear, pop_area);
+        f.render_stateful_widget(t, pop_area, &mut self.table.state);
+    }
+}
@@ -1,10 +1,11 @@
 use crate::{views::centered_rect, Datatable};
 
+use termion::event::Key;
 use tui::{
     backend::Backend,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    text::{Span, Text},
+    text::{Span, Spans, Text},
     widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, Wrap},
     Frame,
 };
@@ -13,15 +14,45 @@ const TEXT: &str = "This is a destructive action. Confirm action:";
 
 pub struct ConfirmationBox<'a> {
     table: Datatable<&'a str>,
+    action: String,
+    // Name to type for the action to be confirmed,
+    // in place of picking Yes.
+    expected: Option<String>,
+    typed: String,
 }
 
 impl<'a> ConfirmationBox<'a> {
     pub fn reset(&mut self) {
         self.table.state.select(Some(0));
+        self.action.clear();
+        self.expected = None;
+        self.typed.clear();
+    }
+
+    /// Describes what will happen on confirmation,
+    /// shown under the warning.
+    pub fn set_action(&mut self, action: String) {
+        self.action = action;
+    }
+
+    /// Asks for `name` to be typed out
+    /// rather than a pick of Yes.
+    pub fn require_typing(&mut self, name: String) {
+        self.expected = Some(name);
+        self.typed.clear();
+    }
+
+    /// Whether the box is waiting for a name to be typed,
+    /// taking every key as text.
+    pub fn is_typing(&self) -> bool {
+        self.expected.is_some()
     }
 
     pub fn draw<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
-        let pop_area = centered_rect(30, 30, area);
+        let pop_area = match self.expected {
+            Some(_) => centered_rect(40, 40, area),
+            None => centered_rect(30, 30, area),
+        };
         let background = Block::default()
             .title(Span::styled("Warning", Style::default().fg(Color::Yellow)))
             .borders(Borders::ALL)
@@ -30,17 +61,39 @@ impl<'a> ConfirmationBox<'a> {
             .direction(Direction::Vertical)
             .constraints([
                 Constraint::Percentage(10),
-                Constraint::Percentage(20),
+                Constraint::Percentage(50),
                 Constraint::Min(0),
             ])
             .margin(1)
             .split(pop_area);
-        let txt = Paragraph::new(Text::raw(TEXT))
+        let txt = Paragraph::new(Text::raw(format!("{}\n{}", TEXT, self.action)))
             .alignment(Alignment::Center)
             .wrap(Wrap { trim: true });
+        f.render_widget(Clear, pop_area);
+        f.render_widget(background, pop_area);
+        f.render_widget(txt, chunks[1]);
+        if let Some(expected) = &self.expected {
+            let color = match self.typed == *expected {
+                true => Color::Green,
+                false => Color::Red,
+            };
+            let text = vec![
+                Spans::from(format!("Type {} to confirm, esc to cancel:", expected)),
+                Spans::from(""),
+                Spans::from(Span::styled(
+                    format!("{}_", self.typed),
+                    Style::default().fg(color),
+                )),
+            ];
+            let input = Paragraph::new(text)
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: false });
+            f.render_widget(input, chunks[2]);
+            return;
+        }
         let data = self.table.data.get();
         let rows = data.iter().map(|r| {
-            let vecd = vec![r.to_string()];
+            let vecd = [r.to_string()];
             let cell = vecd.iter().map(|c| Cell::from(c.clone()));
             Row::new(cell).bottom_margin(1)
         });
@@ -50,22 +103,34 @@ impl<'a> ConfirmationBox<'a> {
             .highlight_style(selected_style)
             .highlight_symbol(">> ")
             .widths(&[Constraint::Percentage(100)]);
-        f.render_widget(Clear, pop_area);
-        f.render_widget(background, pop_area);
-        f.render_widget(txt, chunks[1]);
         f.render_stateful_widget(t, chunks[2], &mut self.table.state);
     }
 
     pub fn is_confirmed(&self) -> bool {
-        self.table.state.selected() == Some(1)
-    }
-
-    pub fn next(&mut self) {
-        self.table.next();
+        match &self.expected {
+            Some(expected) => self.typed == *expected,
+            None => self.table.state.selected() == Some(1),
+        }
     }
 
-    pub fn previous(&mut self) {
-        self.table.previous();
+    /// Moves between No and Yes, or edits the typed
+    /// name. Return and esc are left to the caller.
+    pub fn handle_key(&mut self, key: Key) {
+        if self.expected.is_some() {
+            match key {
+                Key::Char(c) => self.typed.push(c),
+                Key::Backspace => {
+                    self.typed.pop();
+                }
+                _ => {}
+            }
+            return;
+        }
+        match key {
+            Key::Char('j') | Key::Down => self.table.next(),
+            Key::Char('k') | Key::Up => self.table.previous(),
+            _ => {}
+        }
     }
 }
 
@@ -73,6 +138,11 @@ impl<'a> Default for ConfirmationBox<'a> {
     fn default() -> Self {
         let mut table = Datatable::<&'a str>::new(vec!["No", "Yes"]);
         table.state.select(Some(0));
-        Self { table }
+        Self {
+            table,
+            action: String::new(),
+            expected: None,
+            typed: String::new(),
+        }
     }
 }
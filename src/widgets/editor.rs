@@ -0,0 +1,263 @@
+use crate::views::centered_rect;
+
+use std::{
+    env, fs,
+    io::{self, Write},
+    os::unix::fs::{DirBuilderExt, OpenOptionsExt},
+    path::Path,
+    process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::Value;
+use termion::event::Key;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Span, Spans, Text},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+const HINT: &str = "ctrl + s: publish | ctrl + f: format JSON | esc: close";
+
+/// Returns the byte offset of the `col`th char of `line`.
+fn byte_idx(line: &str, col: usize) -> usize {
+    line.char_indices()
+        .nth(col)
+        .map(|(i, _)| i)
+        .unwrap_or(line.len())
+}
+
+fn char_len(line: &str) -> usize {
+    line.chars().count()
+}
+
+/// A minimal multi-line text editor for
+/// composing payloads.
+pub struct TextEditor {
+    lines: Vec<String>,
+    row: usize,
+    col: usize,
+    scroll: usize,
+}
+
+impl Default for TextEditor {
+    fn default() -> Self {
+        Self {
+            lines: vec![String::new()],
+            row: 0,
+            col: 0,
+            scroll: 0,
+        }
+    }
+}
+
+impl TextEditor {
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    pub fn set_text(&mut self, text: &str) {
+        self.lines = text.split('\n').map(String::from).collect();
+        self.row = 0;
+        self.col = 0;
+        self.scroll = 0;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.iter().all(|l| l.trim().is_empty())
+    }
+
+    /// Rewrites the buffer as pretty-printed JSON. The
+    /// buffer is left untouched if it is not valid JSON.
+    pub fn format_json(&mut self) -> Result<(), serde_json::Error> {
+        let v = serde_json::from_str::<Value>(&self.text())?;
+        // serializing a Value never fails.
+        let pretty = serde_json::to_string_pretty(&v).unwrap_or_default();
+        self.set_text(&pretty);
+        Ok(())
+    }
+
+    /// Describes whether the buffer holds valid JSON. Buffers
+    /// that do not look like JSON at all are plain text.
+    fn validation(&self) -> Span<'static> {
+        let text = self.text();
+        let trimmed = text.trim_start();
+        if !trimmed.starts_with('{') && !trimmed.starts_with('[') {
+            return Span::styled("plain text", Style::default().fg(Color::Gray));
+        }
+        match serde_json::from_str::<Value>(&text) {
+            Ok(_) => Span::styled("valid JSON", Style::default().fg(Color::Green)),
+            Err(e) => Span::styled(
+                format!("invalid JSON: {}", e),
+                Style::default().fg(Color::Red),
+            ),
+        }
+    }
+
+    fn line(&self) -> &String {
+        &self.lines[self.row]
+    }
+
+    fn clamp_col(&mut self) {
+        self.col = self.col.min(char_len(self.line()));
+    }
+
+    fn insert(&mut self, c: char) {
+        let i = byte_idx(self.line(), self.col);
+        self.lines[self.row].insert(i, c);
+        self.col += 1;
+    }
+
+    fn newline(&mut self) {
+        let i = byte_idx(self.line(), self.col);
+        let rest = self.lines[self.row].split_off(i);
+        self.row += 1;
+        self.lines.insert(self.row, rest);
+        self.col = 0;
+    }
+
+    fn backspace(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+            let i = byte_idx(self.line(), self.col);
+            self.lines[self.row].remove(i);
+        } else if self.row > 0 {
+            let line = self.lines.remove(self.row);
+            self.row -= 1;
+            self.col = char_len(self.line());
+            self.lines[self.row].push_str(&line);
+        }
+    }
+
+    fn delete(&mut self) {
+        if self.col < char_len(self.line()) {
+            let i = byte_idx(self.line(), self.col);
+            self.lines[self.row].remove(i);
+        } else if self.row + 1 < self.lines.len() {
+            let next = self.lines.remove(self.row + 1);
+            self.lines[self.row].push_str(&next);
+        }
+    }
+
+    pub fn handle_key(&mut self, key: Key) {
+        match key {
+            Key::Char('\n') => self.newline(),
+            Key::Char('\t') => (0..4).for_each(|_| self.insert(' ')),
+            Key::Char(c) => self.insert(c),
+            Key::Backspace => self.backspace(),
+            Key::Delete => self.delete(),
+            Key::Left => {
+                if self.col > 0 {
+                    self.col -= 1;
+                } else if self.row > 0 {
+                    self.row -= 1;
+                    self.col = char_len(self.line());
+                }
+            }
+            Key::Right => {
+                if self.col < char_len(self.line()) {
+                    self.col += 1;
+                } else if self.row + 1 < self.lines.len() {
+                    self.row += 1;
+                    self.col = 0;
+                }
+            }
+            Key::Up if self.row > 0 => {
+                self.row -= 1;
+                self.clamp_col();
+            }
+            Key::Down if self.row + 1 < self.lines.len() => {
+                self.row += 1;
+                self.clamp_col();
+            }
+            Key::Home => self.col = 0,
+            Key::End => self.col = char_len(self.line()),
+            _ => {}
+        }
+    }
+
+    pub fn draw<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect, title: &str) {
+        let pop_area = centered_rect(70, 70, area);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(Span::styled(title, Style::default().fg(Color::Yellow)));
+        let inner = block.inner(pop_area);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(2)])
+            .split(inner);
+        let height = chunks[0].height as usize;
+        if self.row < self.scroll {
+            self.scroll = self.row;
+        } else if height > 0 && self.row >= self.scroll + height {
+            self.scroll = self.row + 1 - height;
+        }
+        let lines: Vec<Spans> = self
+            .lines
+            .iter()
+            .skip(self.scroll)
+            .take(height)
+            .map(|l| Spans::from(l.as_str()))
+            .collect();
+        let status = Text::from(vec![
+            Spans::from(self.validation()),
+            Spans::from(Span::styled(HINT, Style::default().fg(Color::DarkGray))),
+        ]);
+        f.render_widget(Clear, pop_area);
+        f.render_widget(block, pop_area);
+        f.render_widget(Paragraph::new(lines), chunks[0]);
+        f.render_widget(Paragraph::new(status), chunks[1]);
+        let x = chunks[0].x + self.col as u16;
+        let y = chunks[0].y + (self.row - self.scroll) as u16;
+        if x < chunks[0].right() && y < chunks[0].bottom() {
+            f.set_cursor(x, y);
+        }
+    }
+}
+
+/// Opens `$VISUAL`, `$EDITOR` or vi on a temporary file
+/// seeded with `initial`, and returns what was saved. The
+/// terminal must have been handed over before calling this.
+pub fn edit_external(initial: &str) -> io::Result<String> {
+    // The payload goes in a directory only we can read, created
+    // anew so that nothing already there can be followed.
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or_default();
+    let dir = env::temp_dir().join(format!("rabbitui-{}-{}", process::id(), nanos));
+    fs::DirBuilder::new().mode(0o700).create(&dir)?;
+    let res = edit_in(&dir, initial);
+    let _ = fs::remove_dir_all(&dir);
+    res
+}
+
+fn edit_in(dir: &Path, initial: &str) -> io::Result<String> {
+    let path = dir.join("payload");
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&path)?
+        .write_all(initial.as_bytes())?;
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    // the variable may carry arguments, eg. "code --wait".
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+    process::Command::new(program)
+        .args(parts)
+        .arg(&path)
+        .status()
+        .and_then(|s| {
+            if s.success() {
+                fs::read_to_string(&path)
+            } else {
+                Err(io::Error::other(format!("{} exited with {}", program, s)))
+            }
+        })
+}
@@ -1,6 +1,6 @@
 use crate::{views::centered_rect, Datatable};
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use std::fs;
 
@@ -17,14 +17,14 @@ pub struct FileNavigator {
     file_table: Datatable<PathBuf>,
 }
 
-fn file_name_helper(f: &PathBuf) -> &str {
+fn file_name_helper(f: &Path) -> &str {
     match f.file_name() {
         Some(n) => n.to_str().unwrap_or(""),
         None => "",
     }
 }
 
-fn table_from_path(path: &PathBuf) -> Datatable<PathBuf> {
+fn table_from_path(path: &Path) -> Datatable<PathBuf> {
     let files: Vec<PathBuf> = fs::read_dir(path)
         .unwrap()
         .map(|r| r.unwrap().path())
@@ -102,6 +102,17 @@ impl FileNavigator {
         }
     }
 
+    /// Directory being shown.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns the highlighted entry, file or directory,
+    /// without navigating into it.
+    pub fn highlighted(&self) -> Option<&PathBuf> {
+        self.file_table.selected()
+    }
+
     pub fn select(&mut self) -> Option<PathBuf> {
         if let Some(i) = self.file_table.state.selected() {
             let f = self.file_table.data.get()[i].clone();
@@ -1,5 +1,10 @@
 pub mod chart;
+pub mod columns;
 pub mod confirmation;
+pub mod editor;
 pub mod files;
 pub mod help;
 pub mod notif;
+pub mod payload;
+pub mod progress;
+pub mod search;
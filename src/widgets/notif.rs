@@ -1,43 +1,208 @@
+use super::chart::{clock, now};
+use crate::{models::column, ColumnDef, Identifiable, Rowable, SortValue, Sortable};
+
+use std::{
+    collections::VecDeque,
+    sync::mpsc,
+    time::{Duration, Instant},
+};
+
 use tui::{
     backend::Backend,
-    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Rect},
     style::{Color, Style},
     text::Text,
     widgets::{Block, Borders, Clear, Paragraph},
     Frame,
 };
 
-fn notif_rect(r: Rect) -> Rect {
-    let layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Percentage(93), Constraint::Percentage(7)].as_ref())
-        .split(r);
+/// Most toasts shown at once, the oldest
+/// giving way to newer ones.
+const MAX_TOASTS: usize = 4;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Level {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl Level {
+    pub fn name(self) -> &'static str {
+        match self {
+            Level::Info => "info",
+            Level::Success => "success",
+            Level::Warning => "warning",
+            Level::Error => "error",
+        }
+    }
 
-    Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(85), Constraint::Percentage(15)].as_ref())
-        .split(layout[1])[1]
+    pub fn color(self) -> Color {
+        match self {
+            Level::Info => Color::Cyan,
+            Level::Success => Color::Green,
+            Level::Warning => Color::Yellow,
+            Level::Error => Color::Red,
+        }
+    }
+
+    /// How long toasts of this level stay up.
+    fn timeout(self) -> Duration {
+        match self {
+            Level::Info | Level::Success => Duration::from_secs(4),
+            Level::Warning => Duration::from_secs(8),
+            Level::Error => Duration::from_secs(12),
+        }
+    }
 }
 
+#[derive(Clone, Debug)]
 pub struct Notification {
-    msg: String,
+    pub level: Level,
+    pub msg: String,
+    /// Seconds since the epoch.
+    pub at: f64,
+    // Order of arrival, set by the history.
+    pub(crate) id: u64,
+}
+
+/// Handle posting notifications from anywhere in the app,
+/// including other threads. Each one is shown as a toast
+/// and kept in the session history.
+#[derive(Clone)]
+pub struct Notifier {
+    toasts: mpsc::Sender<Notification>,
+    history: mpsc::Sender<Notification>,
+}
+
+/// Creates a notifier along with the toasts and
+/// the history feed it posts to.
+pub fn channel() -> (Notifier, Toasts, mpsc::Receiver<Notification>) {
+    let (toasts_tx, toasts_rx) = mpsc::channel();
+    let (history_tx, history_rx) = mpsc::channel();
+    let notifier = Notifier {
+        toasts: toasts_tx,
+        history: history_tx,
+    };
+    let toasts = Toasts {
+        chan: toasts_rx,
+        shown: VecDeque::new(),
+    };
+    (notifier, toasts, history_rx)
+}
+
+impl Notifier {
+    pub fn post(&self, level: Level, msg: impl Into<String>) {
+        let n = Notification {
+            level,
+            msg: msg.into(),
+            at: now(),
+            id: 0,
+        };
+        // Nothing is left to tell once the app is gone.
+        let _ = self.history.send(n.clone());
+        let _ = self.toasts.send(n);
+    }
+
+    pub fn info(&self, msg: impl Into<String>) {
+        self.post(Level::Info, msg);
+    }
+
+    pub fn success(&self, msg: impl Into<String>) {
+        self.post(Level::Success, msg);
+    }
+
+    pub fn warning(&self, msg: impl Into<String>) {
+        self.post(Level::Warning, msg);
+    }
+
+    pub fn error(&self, msg: impl Into<String>) {
+        self.post(Level::Error, msg);
+    }
 }
 
-impl Notification {
-    pub fn new(msg: String) -> Self {
-        Self { msg }
+/// Stack of notifications in the bottom right
+/// corner, each going away after its timeout.
+pub struct Toasts {
+    chan: mpsc::Receiver<Notification>,
+    shown: VecDeque<(Notification, Instant)>,
+}
+
+impl Toasts {
+    /// Takes in the new notifications and
+    /// drops the ones that timed out.
+    pub fn update(&mut self) {
+        let now = Instant::now();
+        self.shown.extend(self.chan.try_iter().map(|n| (n, now)));
+        self.shown
+            .retain(|(n, since)| now.duration_since(*since) < n.level.timeout());
+        while self.shown.len() > MAX_TOASTS {
+            self.shown.pop_front();
+        }
     }
 
+    /// Draws the newest toast at the bottom of
+    /// `area`, older ones stacked above it.
     pub fn draw<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
-        let notif = Paragraph::new(Text::raw(&self.msg))
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::Yellow)),
-            )
-            .alignment(Alignment::Center);
-        let pop_area = notif_rect(area);
-        f.render_widget(Clear, pop_area);
-        f.render_widget(notif, pop_area);
+        let mut bottom = area.bottom();
+        for (n, _) in self.shown.iter().rev() {
+            if bottom < area.top() + 3 {
+                break;
+            }
+            let width = (n.msg.chars().count() as u16 + 4)
+                .max(area.width * 15 / 100)
+                .min(area.width);
+            let pop_area = Rect::new(area.right() - width, bottom - 3, width, 3);
+            let toast = Paragraph::new(Text::raw(&n.msg))
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(n.level.color())),
+                )
+                .alignment(Alignment::Center);
+            f.render_widget(Clear, pop_area);
+            f.render_widget(toast, pop_area);
+            bottom -= 3;
+        }
+    }
+}
+
+const NOTIFICATION_COLUMNS: [ColumnDef; 3] = [
+    column("at", "Time", 10, true),
+    column("level", "Level", 10, true),
+    column("msg", "Message", 80, true),
+];
+
+impl Rowable for Notification {
+    fn columns() -> &'static [ColumnDef] {
+        &NOTIFICATION_COLUMNS
+    }
+
+    fn cell(&self, field: usize) -> String {
+        match NOTIFICATION_COLUMNS[field].key {
+            "at" => clock(self.at, true),
+            "level" => self.level.name().to_string(),
+            "msg" => self.msg.clone(),
+            _ => String::new(),
+        }
+    }
+}
+
+impl Sortable for Notification {
+    fn sort_value(&self, field: usize) -> SortValue {
+        match NOTIFICATION_COLUMNS[field].key {
+            "at" => SortValue::Number(self.id as f64),
+            "level" => SortValue::Number(self.level as u8 as f64),
+            _ => SortValue::Text(self.cell(field).to_lowercase()),
+        }
+    }
+}
+
+impl Identifiable for Notification {
+    type Id = u64;
+
+    fn id(&self) -> Self::Id {
+        self.id
     }
 }
@@ -0,0 +1,534 @@
+use crate::{models::MQMessage, views::centered_rect};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::Value;
+use termion::event::Key;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+const HEX_WIDTH: usize = 16;
+const HINT: &str =
+    "j/k: scroll | m: mode | /: search | n/N: next/prev match | y: copy | esc: close";
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ViewMode {
+    Json,
+    Xml,
+    Text,
+    Hex,
+}
+
+impl ViewMode {
+    fn next(self) -> Self {
+        match self {
+            ViewMode::Json => ViewMode::Xml,
+            ViewMode::Xml => ViewMode::Text,
+            ViewMode::Text => ViewMode::Hex,
+            ViewMode::Hex => ViewMode::Json,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            ViewMode::Json => "JSON",
+            ViewMode::Xml => "XML",
+            ViewMode::Text => "text",
+            ViewMode::Hex => "hex",
+        }
+    }
+}
+
+/// A line as styled segments, before search highlighting.
+type Segments = Vec<(String, Style)>;
+
+/// Guesses how to display a payload, trusting the content
+/// type first and sniffing the bytes otherwise.
+fn detect(content_type: Option<&str>, bytes: &[u8]) -> ViewMode {
+    if let Some(ct) = content_type {
+        if ct.contains("json") {
+            return ViewMode::Json;
+        }
+        if ct.contains("xml") {
+            return ViewMode::Xml;
+        }
+    }
+    let text = match std::str::from_utf8(bytes) {
+        Ok(t) => t,
+        Err(_) => return ViewMode::Hex,
+    };
+    let trimmed = text.trim_start();
+    if (trimmed.starts_with('{') || trimmed.starts_with('['))
+        && serde_json::from_str::<Value>(text).is_ok()
+    {
+        ViewMode::Json
+    } else if trimmed.starts_with('<') {
+        ViewMode::Xml
+    } else if text.chars().all(|c| !c.is_control() || c.is_whitespace()) {
+        ViewMode::Text
+    } else {
+        ViewMode::Hex
+    }
+}
+
+fn text_lines(bytes: &[u8]) -> Vec<Segments> {
+    String::from_utf8_lossy(bytes)
+        .lines()
+        .map(|l| vec![(l.to_string(), Style::default())])
+        .collect()
+}
+
+fn json_lines(bytes: &[u8]) -> Option<Vec<Segments>> {
+    let v = serde_json::from_slice::<Value>(bytes).ok()?;
+    let pretty = serde_json::to_string_pretty(&v).ok()?;
+    Some(pretty.lines().map(json_segments).collect())
+}
+
+/// Colours one line of pretty-printed JSON. Strings never
+/// span lines once pretty-printed, so lines stand alone.
+fn json_segments(line: &str) -> Segments {
+    let mut out = Vec::new();
+    let chars: Vec<char> = line.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        let style = match chars[i] {
+            '"' => {
+                i += 1;
+                while i < chars.len() && chars[i] != '"' {
+                    if chars[i] == '\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                i += 1;
+                let is_key = chars[i.min(chars.len())..]
+                    .iter()
+                    .find(|c| !c.is_whitespace())
+                    == Some(&':');
+                if is_key {
+                    Style::default().fg(Color::Cyan)
+                } else {
+                    Style::default().fg(Color::Green)
+                }
+            }
+            c if c == '-' || c.is_ascii_digit() => {
+                while i < chars.len() && "0123456789+-.eE".contains(chars[i]) {
+                    i += 1;
+                }
+                Style::default().fg(Color::Magenta)
+            }
+            c if c.is_ascii_alphabetic() => {
+                while i < chars.len() && chars[i].is_ascii_alphabetic() {
+                    i += 1;
+                }
+                Style::default().fg(Color::Yellow)
+            }
+            _ => {
+                i += 1;
+                Style::default().fg(Color::Gray)
+            }
+        };
+        let end = i.min(chars.len());
+        out.push((chars[start..end].iter().collect(), style));
+    }
+    out
+}
+
+/// Byte index of the end of the tag starting `rest`.
+fn xml_tag_end(rest: &str) -> usize {
+    let close = if rest.starts_with("<!--") {
+        "-->"
+    } else if rest.starts_with("<![CDATA[") {
+        "]]>"
+    } else {
+        ">"
+    };
+    rest.find(close)
+        .map(|i| i + close.len())
+        .unwrap_or(rest.len())
+}
+
+/// Re-indents an XML document one tag per line. Elements
+/// holding only text are kept on a single line.
+fn pretty_xml(text: &str) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    let mut depth = 0usize;
+    let mut last_open = false;
+    let mut rest = text.trim();
+    while !rest.is_empty() {
+        let indent = "  ".repeat(depth);
+        if rest.starts_with('<') {
+            let end = xml_tag_end(rest);
+            let tag = &rest[..end];
+            rest = rest[end..].trim_start();
+            if tag.starts_with("</") {
+                depth = depth.saturating_sub(1);
+                out.push(format!("{}{}", "  ".repeat(depth), tag));
+                last_open = false;
+            } else if tag.starts_with("<?") || tag.starts_with("<!") || tag.ends_with("/>") {
+                out.push(format!("{}{}", indent, tag));
+                last_open = false;
+            } else {
+                out.push(format!("{}{}", indent, tag));
+                depth += 1;
+                last_open = true;
+            }
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            let t = rest[..end].trim();
+            rest = &rest[end..];
+            // <a>text</a> stays on one line.
+            if last_open && rest.starts_with("</") {
+                let close_end = xml_tag_end(rest);
+                if let Some(l) = out.last_mut() {
+                    l.push_str(t);
+                    l.push_str(&rest[..close_end]);
+                }
+                rest = rest[close_end..].trim_start();
+                depth = depth.saturating_sub(1);
+            } else if !t.is_empty() {
+                out.push(format!("{}{}", indent, t));
+            }
+            last_open = false;
+        }
+    }
+    out
+}
+
+/// Colours one line of XML: tags in blue with their quoted
+/// attribute values in green, comments dimmed.
+fn xml_segments(line: &str) -> Segments {
+    let mut out = Vec::new();
+    let mut rest = line;
+    while !rest.is_empty() {
+        if rest.starts_with("<!--") {
+            let end = xml_tag_end(rest);
+            out.push((
+                rest[..end].to_string(),
+                Style::default().fg(Color::DarkGray),
+            ));
+            rest = &rest[end..];
+        } else if rest.starts_with('<') {
+            let end = xml_tag_end(rest);
+            let mut in_quote = false;
+            let mut seg_start = 0;
+            let tag = &rest[..end];
+            for (i, c) in tag.char_indices() {
+                if c == '"' || c == '\'' {
+                    let (from, to, style) = if in_quote {
+                        (seg_start, i + 1, Style::default().fg(Color::Green))
+                    } else {
+                        (seg_start, i, Style::default().fg(Color::Blue))
+                    };
+                    out.push((tag[from..to].to_string(), style));
+                    seg_start = to;
+                    in_quote = !in_quote;
+                }
+            }
+            out.push((
+                tag[seg_start..].to_string(),
+                Style::default().fg(Color::Blue),
+            ));
+            rest = &rest[end..];
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            out.push((rest[..end].to_string(), Style::default()));
+            rest = &rest[end..];
+        }
+    }
+    out
+}
+
+fn hex_lines(bytes: &[u8]) -> Vec<Segments> {
+    bytes
+        .chunks(HEX_WIDTH)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+            let ascii: String = chunk
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            vec![
+                (
+                    format!("{:08x}  ", i * HEX_WIDTH),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                (
+                    format!("{:<width$}  ", hex.join(" "), width = HEX_WIDTH * 3 - 1),
+                    Style::default(),
+                ),
+                (format!("|{}|", ascii), Style::default().fg(Color::Cyan)),
+            ]
+        })
+        .collect()
+}
+
+/// Splits segments further so that every case-insensitive
+/// occurrence of `query` stands out.
+fn highlight(segments: &Segments, query: &str) -> Spans<'static> {
+    let hl = Style::default()
+        .bg(Color::Yellow)
+        .fg(Color::Black)
+        .add_modifier(Modifier::BOLD);
+    let line: String = segments.iter().map(|(s, _)| s.as_str()).collect();
+    // ascii lowercasing keeps byte offsets identical.
+    let lower = line.to_ascii_lowercase();
+    let q = query.to_ascii_lowercase();
+    let ranges: Vec<(usize, usize)> = if q.is_empty() {
+        Vec::new()
+    } else {
+        lower
+            .match_indices(&q)
+            .map(|(i, m)| (i, i + m.len()))
+            .collect()
+    };
+    let mut spans = Vec::new();
+    let mut offset = 0;
+    for (text, style) in segments {
+        let end = offset + text.len();
+        let mut cur = offset;
+        for &(a, b) in &ranges {
+            if b <= cur || a >= end {
+                continue;
+            }
+            let (a, b) = (a.max(cur), b.min(end));
+            if a > cur {
+                spans.push(Span::styled(
+                    text[cur - offset..a - offset].to_string(),
+                    *style,
+                ));
+            }
+            spans.push(Span::styled(text[a - offset..b - offset].to_string(), hl));
+            cur = b;
+        }
+        if cur < end {
+            spans.push(Span::styled(text[cur - offset..].to_string(), *style));
+        }
+        offset = end;
+    }
+    Spans::from(spans)
+}
+
+/// Scrollable popout rendering a message payload as
+/// JSON, XML, plain text or a hex dump, with search.
+pub struct PayloadViewer {
+    title: String,
+    bytes: Vec<u8>,
+    detected: ViewMode,
+    mode: ViewMode,
+    lines: Vec<Segments>,
+    scroll: usize,
+    height: usize,
+    query: String,
+    // Some while the search prompt is open.
+    query_input: Option<String>,
+    matches: Vec<usize>,
+    match_idx: usize,
+}
+
+impl PayloadViewer {
+    pub fn new(msg: &MQMessage) -> Self {
+        let bytes = if msg.payload_encoding == "base64" {
+            STANDARD
+                .decode(msg.payload.trim())
+                .unwrap_or_else(|_| msg.payload.clone().into_bytes())
+        } else {
+            msg.payload.clone().into_bytes()
+        };
+        let detected = detect(msg.content_type(), &bytes);
+        let title = format!(
+            "{} | {} | {} bytes{}",
+            if msg.exchange.is_empty() {
+                "(AMQP DEFAULT)"
+            } else {
+                &msg.exchange
+            },
+            msg.routing_key,
+            msg.payload_bytes,
+            if msg.redelivered {
+                " | redelivered"
+            } else {
+                ""
+            }
+        );
+        let mut viewer = Self {
+            title,
+            bytes,
+            detected,
+            mode: detected,
+            lines: Vec::new(),
+            scroll: 0,
+            height: 0,
+            query: String::new(),
+            query_input: None,
+            matches: Vec::new(),
+            match_idx: 0,
+        };
+        viewer.render();
+        viewer
+    }
+
+    /// The payload as text, for copying.
+    pub fn payload_text(&self) -> String {
+        String::from_utf8_lossy(&self.bytes).into_owned()
+    }
+
+    /// Whether the search prompt is taking input.
+    pub fn is_searching(&self) -> bool {
+        self.query_input.is_some()
+    }
+
+    fn render(&mut self) {
+        self.lines = match self.mode {
+            ViewMode::Json => json_lines(&self.bytes).unwrap_or_else(|| text_lines(&self.bytes)),
+            ViewMode::Xml => pretty_xml(&String::from_utf8_lossy(&self.bytes))
+                .iter()
+                .map(|l| xml_segments(l))
+                .collect(),
+            ViewMode::Text => text_lines(&self.bytes),
+            ViewMode::Hex => hex_lines(&self.bytes),
+        };
+        self.scroll = 0;
+        self.find_matches();
+    }
+
+    fn find_matches(&mut self) {
+        let q = self.query.to_ascii_lowercase();
+        self.matches = if q.is_empty() {
+            Vec::new()
+        } else {
+            self.lines
+                .iter()
+                .enumerate()
+                .filter(|(_, segs)| {
+                    let line: String = segs.iter().map(|(s, _)| s.as_str()).collect();
+                    line.to_ascii_lowercase().contains(&q)
+                })
+                .map(|(i, _)| i)
+                .collect()
+        };
+        self.match_idx = 0;
+    }
+
+    fn jump_to_match(&mut self) {
+        if let Some(&line) = self.matches.get(self.match_idx) {
+            self.scroll = line;
+        }
+    }
+
+    fn scroll_by(&mut self, delta: isize) {
+        let max = self.lines.len().saturating_sub(1);
+        self.scroll = self.scroll.saturating_add_signed(delta).min(max);
+    }
+
+    pub fn handle_key(&mut self, key: Key) {
+        if let Some(input) = &mut self.query_input {
+            match key {
+                Key::Char('\n') => {
+                    self.query = self.query_input.take().unwrap_or_default();
+                    self.find_matches();
+                    self.jump_to_match();
+                }
+                Key::Esc => self.query_input = None,
+                Key::Backspace => {
+                    input.pop();
+                }
+                Key::Char(c) => input.push(c),
+                _ => {}
+            }
+            return;
+        }
+        let page = self.height.max(1) as isize;
+        match key {
+            Key::Char('j') | Key::Down => self.scroll_by(1),
+            Key::Char('k') | Key::Up => self.scroll_by(-1),
+            Key::PageDown | Key::Ctrl('d') => self.scroll_by(page),
+            Key::PageUp | Key::Ctrl('u') => self.scroll_by(-page),
+            Key::Char('g') | Key::Home => self.scroll = 0,
+            Key::Char('G') | Key::End => self.scroll_by(isize::MAX),
+            Key::Char('m') => {
+                self.mode = self.mode.next();
+                self.render();
+            }
+            Key::Char('/') => self.query_input = Some(String::new()),
+            Key::Char('n') if !self.matches.is_empty() => {
+                self.match_idx = (self.match_idx + 1) % self.matches.len();
+                self.jump_to_match();
+            }
+            Key::Char('N') if !self.matches.is_empty() => {
+                self.match_idx = (self.match_idx + self.matches.len() - 1) % self.matches.len();
+                self.jump_to_match();
+            }
+            _ => {}
+        }
+    }
+
+    pub fn draw<B: Backend>(&mut self, f: &mut Frame<B>, area: Rect) {
+        let pop_area = centered_rect(80, 80, area);
+        let block = Block::default().borders(Borders::ALL).title(Span::styled(
+            self.title.clone(),
+            Style::default().fg(Color::Yellow),
+        ));
+        let inner = block.inner(pop_area);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(2)])
+            .split(inner);
+        self.height = chunks[0].height as usize;
+        let body: Vec<Spans> = self
+            .lines
+            .iter()
+            .skip(self.scroll)
+            .take(self.height)
+            .map(|segs| highlight(segs, &self.query))
+            .collect();
+        let mode = if self.mode == self.detected {
+            format!("{} (detected)", self.mode.name())
+        } else {
+            self.mode.name().to_string()
+        };
+        let search = match &self.query_input {
+            Some(input) => format!("/{}", input),
+            None if !self.query.is_empty() => format!(
+                "\"{}\" {}/{}",
+                self.query,
+                if self.matches.is_empty() {
+                    0
+                } else {
+                    self.match_idx + 1
+                },
+                self.matches.len()
+            ),
+            None => String::new(),
+        };
+        let status = vec![
+            Spans::from(vec![
+                Span::styled(mode, Style::default().fg(Color::Green)),
+                Span::raw(format!(
+                    "  line {}/{}  ",
+                    (self.scroll + 1).min(self.lines.len()),
+                    self.lines.len()
+                )),
+                Span::styled(search, Style::default().fg(Color::Yellow)),
+            ]),
+            Spans::from(Span::styled(HINT, Style::default().fg(Color::DarkGray))),
+        ];
+        f.render_widget(Clear, pop_area);
+        f.render_widget(block, pop_area);
+        f.render_widget(Paragraph::new(body), chunks[0]);
+        f.render_widget(Paragraph::new(status), chunks[1]);
+    }
+}
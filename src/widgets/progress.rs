@@ -0,0 +1,82 @@
+use crate::{bulk::BulkSummary, views::centered_rect};
+
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Span, Spans, Text},
+    widgets::{Block, Borders, Clear, Gauge, Paragraph, Wrap},
+    Frame,
+};
+
+/// Popout tracking the progress of a bulk publish,
+/// and its final tally once done.
+pub struct ProgressBox<'a> {
+    summary: &'a BulkSummary,
+}
+
+impl<'a> ProgressBox<'a> {
+    pub fn new(summary: &'a BulkSummary) -> Self {
+        Self { summary }
+    }
+
+    pub fn draw<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+        let s = self.summary;
+        let pop_area = centered_rect(40, 30, area);
+        let title = if s.finished {
+            "Bulk publish done"
+        } else {
+            "Bulk publishing"
+        };
+        let background = Block::default()
+            .title(Span::styled(title, Style::default().fg(Color::Yellow)))
+            .borders(Borders::ALL);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .margin(1)
+            .split(pop_area);
+        let gauge = Gauge::default()
+            .block(Block::default().borders(Borders::ALL))
+            .gauge_style(Style::default().fg(Color::Green))
+            .label(format!("{}/{}", s.sent(), s.total))
+            .ratio(s.ratio());
+        let hint = if s.finished {
+            "return: close"
+        } else {
+            "esc: cancel"
+        };
+        let mut lines = vec![
+            Spans::from(vec![
+                Span::styled("Routed      ", Style::default().fg(Color::Green)),
+                Span::raw(s.routed.to_string()),
+            ]),
+            Spans::from(vec![
+                Span::styled("Unroutable  ", Style::default().fg(Color::Yellow)),
+                Span::raw(s.unroutable.to_string()),
+            ]),
+            Spans::from(vec![
+                Span::styled("Failed      ", Style::default().fg(Color::Red)),
+                Span::raw(s.failed.to_string()),
+            ]),
+        ];
+        if s.dry_run > 0 {
+            lines.push(Spans::from(vec![
+                Span::styled("Dry run     ", Style::default().fg(Color::Cyan)),
+                Span::raw(s.dry_run.to_string()),
+            ]));
+        }
+        if let Some(e) = &s.first_error {
+            lines.push(Spans::from(Span::styled(
+                format!("First error: {}", e),
+                Style::default().fg(Color::Red),
+            )));
+        }
+        lines.extend([Spans::from(""), Spans::from(hint)]);
+        let txt = Text::from(lines);
+        f.render_widget(Clear, pop_area);
+        f.render_widget(background, pop_area);
+        f.render_widget(gauge, chunks[0]);
+        f.render_widget(Paragraph::new(txt).wrap(Wrap { trim: true }), chunks[1]);
+    }
+}
@@ -0,0 +1,134 @@
+use crate::filter::{Filter, FilterMode};
+
+use termion::event::Key;
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Cell, Paragraph},
+    Frame,
+};
+
+/// The `/` prompt filtering a table. The query stays
+/// applied once the prompt is closed with enter, and
+/// is cleared with esc.
+pub struct SearchBar {
+    query: String,
+    mode: FilterMode,
+    typing: bool,
+    error: Option<String>,
+}
+
+impl Default for SearchBar {
+    fn default() -> Self {
+        Self {
+            query: String::new(),
+            mode: FilterMode::Substring,
+            typing: false,
+            error: None,
+        }
+    }
+}
+
+impl SearchBar {
+    pub fn open(&mut self) {
+        self.typing = true;
+    }
+
+    pub fn is_typing(&self) -> bool {
+        self.typing
+    }
+
+    /// Whether the bar takes a line under the table.
+    pub fn is_visible(&self) -> bool {
+        self.typing || !self.query.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self {
+            mode: self.mode,
+            ..Self::default()
+        };
+    }
+
+    /// Handles a key typed in the prompt, returning
+    /// whether the filter may have changed.
+    pub fn handle_key(&mut self, key: Key) -> bool {
+        match key {
+            Key::Esc => {
+                self.clear();
+                true
+            }
+            Key::Char('\n') => {
+                self.typing = false;
+                false
+            }
+            Key::Char('\t') => {
+                self.mode = self.mode.next();
+                true
+            }
+            Key::Backspace => self.query.pop().is_some(),
+            Key::Char(c) => {
+                self.query.push(c);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Compiles the current query. An invalid regex
+    /// filters nothing and is reported in the bar.
+    pub fn filter(&mut self) -> Option<Filter> {
+        self.error = None;
+        if self.query.is_empty() {
+            return None;
+        }
+        match Filter::new(&self.query, self.mode) {
+            Ok(f) => Some(f),
+            Err(e) => {
+                self.error = Some(e.to_string().lines().last().unwrap_or("").to_string());
+                None
+            }
+        }
+    }
+
+    pub fn draw<B: Backend>(&self, f: &mut Frame<B>, area: Rect, shown: usize, total: usize) {
+        let mut spans = vec![
+            Span::styled("/", Style::default().fg(Color::Yellow)),
+            Span::raw(self.query.as_str()),
+        ];
+        if self.typing {
+            spans.push(Span::styled(
+                " ",
+                Style::default().add_modifier(Modifier::REVERSED),
+            ));
+        }
+        spans.push(Span::styled(
+            format!("  [{}]", self.mode.name()),
+            Style::default().fg(Color::DarkGray),
+        ));
+        match &self.error {
+            Some(e) => spans.push(Span::styled(
+                format!("  {}", e),
+                Style::default().fg(Color::Red),
+            )),
+            None => spans.push(Span::raw(format!("  {}/{} rows", shown, total))),
+        }
+        f.render_widget(Paragraph::new(Spans::from(spans)), area);
+    }
+}
+
+/// Builds a table cell, highlighting the first
+/// match of `filter` in its text.
+pub fn highlight_cell<'a>(text: String, filter: Option<&Filter>) -> Cell<'a> {
+    let Some((start, end)) = filter.and_then(|f| f.find(&text)) else {
+        return Cell::from(text);
+    };
+    let style = Style::default().fg(Color::Black).bg(Color::Yellow);
+    Cell::from(Spans::from(vec![
+        Span::raw(text[..start].to_string()),
+        Span::styled(text[start..end].to_string(), style),
+        Span::raw(text[end..].to_string()),
+    ]))
+}